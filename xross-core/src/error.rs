@@ -0,0 +1,245 @@
+use crate::{XrossArray, XrossResult, XrossString};
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::sync::Once;
+
+/// What went wrong in a failed call.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XrossErrorKind {
+    /// The Rust code panicked.
+    Panic = 0,
+    /// The Rust code returned `Err`.
+    Err = 1,
+    /// An argument passed by the JVM was rejected before the call.
+    InvalidArgument = 2,
+    /// The receiver handle passed by the JVM was null.
+    NullHandle = 3,
+    /// A handle passed by the JVM was already dropped or handed back to Rust,
+    /// or does not refer to an object of the expected type.
+    /// Only reported with the `checked-handles` feature.
+    InvalidHandle = 4,
+}
+
+/// Failure reported in the `ptr` of an `XrossResult` whose `is_ok` is false.
+///
+/// Released with `xross_free_error`, which frees everything except `value`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct XrossError {
+    pub kind: XrossErrorKind,
+    pub message: XrossString,
+    /// Backtrace of the failure; a null `ptr` when not captured.
+    /// Panics are captured at the panic site by the hook installed with `xross_catch_panic`,
+    /// other failures where they are converted into an `XrossError`.
+    /// Capturing follows `RUST_BACKTRACE` / `RUST_LIB_BACKTRACE`.
+    pub backtrace: XrossString,
+    /// The `Display` output of each `source()` in the chain, nearest first, as `XrossString`s.
+    pub causes: XrossArray,
+    /// For `Err` values of object type, the handle to the error value; otherwise null.
+    /// Ownership passes to the caller.
+    pub value: *mut c_void,
+}
+
+unsafe impl Send for XrossError {}
+unsafe impl Sync for XrossError {}
+
+impl XrossError {
+    pub fn new(kind: XrossErrorKind, message: impl Into<String>) -> Self {
+        Self::with_causes(
+            kind,
+            message.into(),
+            capture_backtrace(),
+            Vec::new(),
+            std::ptr::null_mut(),
+        )
+    }
+
+    /// Builds the error for a returned `Err`, as produced by `xross_describe_err!`.
+    pub fn from_err(message: String, causes: Vec<String>, value: *mut c_void) -> Self {
+        Self::with_causes(XrossErrorKind::Err, message, capture_backtrace(), causes, value)
+    }
+
+    /// Builds the error for a panic on this thread, with the backtrace recorded by the panic hook.
+    pub fn panic(message: impl Into<String>) -> Self {
+        let backtrace = PANIC_BACKTRACE.with(|bt| bt.borrow_mut().take());
+        Self::with_causes(
+            XrossErrorKind::Panic,
+            message.into(),
+            backtrace,
+            Vec::new(),
+            std::ptr::null_mut(),
+        )
+    }
+
+    /// Builds the error for a caught panic payload.
+    pub fn from_panic(payload: &(dyn Any + Send)) -> Self {
+        Self::panic(panic_message(payload).unwrap_or("Unknown panic"))
+    }
+
+    fn with_causes(
+        kind: XrossErrorKind,
+        message: String,
+        backtrace: Option<String>,
+        causes: Vec<String>,
+        value: *mut c_void,
+    ) -> Self {
+        let backtrace = match backtrace {
+            Some(backtrace) => XrossString::from(backtrace),
+            None => XrossString { ptr: std::ptr::null_mut(), len: 0, cap: 0 },
+        };
+        let causes: Vec<XrossString> = causes.into_iter().map(XrossString::from).collect();
+        Self { kind, message: message.into(), backtrace, causes: causes.into(), value }
+    }
+
+    /// Boxes the error into a failed `XrossResult`.
+    pub fn into_result(self) -> XrossResult {
+        XrossResult { is_ok: false, ptr: Box::into_raw(Box::new(self)) as *mut c_void }
+    }
+}
+
+fn capture_backtrace() -> Option<String> {
+    let backtrace = Backtrace::capture();
+    (backtrace.status() == BacktraceStatus::Captured).then(|| backtrace.to_string())
+}
+
+thread_local! {
+    /// Backtrace of the last panic on this thread, recorded by the hook and taken by `XrossError::panic`.
+    static PANIC_BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Installs, once, a panic hook recording the backtrace of each panic for `XrossError::panic`
+/// before running the hook installed before it. A hook set afterwards replaces it, in which
+/// case panics are reported without a backtrace.
+pub fn xross_install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let backtrace = capture_backtrace();
+            let _ = PANIC_BACKTRACE.try_with(|bt| *bt.borrow_mut() = backtrace);
+            previous(info);
+        }));
+    });
+}
+
+/// A panic caught by `xross_catch_panic`, with the backtrace recorded at the panic site.
+pub struct XrossPanic {
+    payload: Box<dyn Any + Send>,
+    backtrace: Option<String>,
+}
+
+impl XrossPanic {
+    /// Converts the panic into an `XrossError`, using `default_message` for a non-string payload.
+    pub fn into_error(self, default_message: &str) -> XrossError {
+        let message = panic_message(&*self.payload).unwrap_or(default_message).to_string();
+        XrossError::with_causes(
+            XrossErrorKind::Panic,
+            message,
+            self.backtrace,
+            Vec::new(),
+            std::ptr::null_mut(),
+        )
+    }
+
+    /// Resumes the panic on the current thread, keeping its backtrace for `XrossError::from_panic`.
+    pub fn resume(self) -> ! {
+        PANIC_BACKTRACE.with(|bt| *bt.borrow_mut() = self.backtrace);
+        std::panic::resume_unwind(self.payload)
+    }
+}
+
+/// Runs `f` like `std::panic::catch_unwind`, recording the backtrace of a panic.
+pub fn xross_catch_panic<R>(f: impl FnOnce() -> R) -> Result<R, XrossPanic> {
+    xross_install_panic_hook();
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|payload| XrossPanic {
+        payload,
+        backtrace: PANIC_BACKTRACE.with(|bt| bt.borrow_mut().take()),
+    })
+}
+
+/// Returns the message of a panic payload raised with a string.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        Some(s)
+    } else {
+        payload.downcast_ref::<String>().map(String::as_str)
+    }
+}
+
+/// Frees an `XrossError` handed to the JVM. `value` is not released.
+///
+/// # Safety
+///
+/// `err` must come from a failed `XrossResult` and must not have been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xross_free_error(err: *mut XrossError) {
+    if err.is_null() {
+        return;
+    }
+    let err = unsafe { Box::from_raw(err) };
+    unsafe {
+        crate::xross_free_string(err.message);
+        crate::xross_free_string(err.backtrace);
+        for cause in err.causes.into_vec::<XrossString>() {
+            crate::xross_free_string(cause);
+        }
+    }
+}
+
+/// Wraps a returned `Err` value for `xross_describe_err!`.
+///
+/// The message and causes are taken from `std::error::Error` when implemented,
+/// otherwise from `Display`, otherwise the type name is used.
+#[doc(hidden)]
+pub struct XrossErrDescribe<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait XrossDescribeError {
+    fn xross_describe(self) -> (String, Vec<String>);
+}
+
+#[doc(hidden)]
+pub trait XrossDescribeDisplay {
+    fn xross_describe(self) -> (String, Vec<String>);
+}
+
+#[doc(hidden)]
+pub trait XrossDescribeOpaque {
+    fn xross_describe(self) -> (String, Vec<String>);
+}
+
+impl<T: std::error::Error> XrossDescribeError for &&&XrossErrDescribe<'_, T> {
+    fn xross_describe(self) -> (String, Vec<String>) {
+        let causes = std::iter::successors(self.0.source(), |e| e.source());
+        (self.0.to_string(), causes.map(|e| e.to_string()).collect())
+    }
+}
+
+impl<T: std::fmt::Display> XrossDescribeDisplay for &&XrossErrDescribe<'_, T> {
+    fn xross_describe(self) -> (String, Vec<String>) {
+        (self.0.to_string(), Vec::new())
+    }
+}
+
+impl<T> XrossDescribeOpaque for &XrossErrDescribe<'_, T> {
+    fn xross_describe(self) -> (String, Vec<String>) {
+        (std::any::type_name::<T>().to_string(), Vec::new())
+    }
+}
+
+/// Returns the message and `source()` chain of a reference to an `Err` value of any type.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! xross_describe_err {
+    ($err:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::{
+            XrossDescribeDisplay as _, XrossDescribeError as _, XrossDescribeOpaque as _,
+        };
+        (&&&$crate::XrossErrDescribe($err)).xross_describe()
+    }};
+}
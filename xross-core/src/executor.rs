@@ -0,0 +1,374 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, LazyLock, Mutex, OnceLock};
+use std::time::Duration;
+
+/// A boxed future handed to an executor.
+pub type XrossFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/// Drives the futures behind async exports.
+///
+/// With the `tokio` feature the default is `XrossTokioExecutor`; with only `thread-pool`
+/// it is `XrossThreadPool`. Any other executor can be installed with `xross_set_executor`.
+pub trait XrossExecutor: Send + Sync + 'static {
+    /// Runs `future` to completion in the background.
+    /// Dropping it unfinished (e.g. on shutdown) reports the task as cancelled.
+    fn spawn(&self, future: XrossFuture);
+
+    /// Runs a blocking function without stalling the executor.
+    /// Defaults to a shared pool of at most `DEFAULT_MAX_BLOCKING_THREADS` threads.
+    fn spawn_blocking(&self, f: Box<dyn FnOnce() + Send + 'static>) {
+        static POOL: LazyLock<Arc<BlockingPool>> =
+            LazyLock::new(|| Arc::new(BlockingPool::new(DEFAULT_MAX_BLOCKING_THREADS)));
+        POOL.spawn(f);
+    }
+}
+
+/// Thread limit of the default blocking pool, the same as tokio's.
+pub const DEFAULT_MAX_BLOCKING_THREADS: usize = 512;
+
+type BlockingJob = Box<dyn FnOnce() + Send + 'static>;
+
+/// Runs blocking functions on at most `max_threads` threads. Threads are started on demand
+/// and exit after being idle for a while; functions beyond the limit wait in a queue.
+pub(crate) struct BlockingPool {
+    state: Mutex<BlockingState>,
+    available: Condvar,
+    max_threads: usize,
+}
+
+struct BlockingState {
+    queue: VecDeque<BlockingJob>,
+    threads: usize,
+    idle: usize,
+    closed: bool,
+}
+
+impl BlockingPool {
+    const KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+    pub(crate) fn new(max_threads: usize) -> Self {
+        Self {
+            state: Mutex::new(BlockingState {
+                queue: VecDeque::new(),
+                threads: 0,
+                idle: 0,
+                closed: false,
+            }),
+            available: Condvar::new(),
+            max_threads: max_threads.max(1),
+        }
+    }
+
+    /// Queues `f`. After `close` it is dropped without running.
+    pub(crate) fn spawn(self: &Arc<Self>, f: BlockingJob) {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return;
+        }
+        state.queue.push_back(f);
+        if state.idle > 0 {
+            self.available.notify_one();
+        } else if state.threads < self.max_threads {
+            state.threads += 1;
+            let pool = self.clone();
+            let spawned = std::thread::Builder::new()
+                .name("xross-blocking".to_string())
+                .spawn(move || pool.run());
+            if spawned.is_err() {
+                // The queued function runs once another thread becomes free.
+                state.threads -= 1;
+            }
+        }
+    }
+
+    /// Drops the queued functions and lets idle threads exit. Running functions finish.
+    #[cfg(feature = "thread-pool")]
+    pub(crate) fn close(&self) {
+        let queue = {
+            let mut state = self.state.lock().unwrap();
+            state.closed = true;
+            std::mem::take(&mut state.queue)
+        };
+        self.available.notify_all();
+        drop(queue);
+    }
+
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(job) = state.queue.pop_front() {
+                drop(state);
+                // A panic must not take the thread down without it being accounted for.
+                let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(job));
+                state = self.state.lock().unwrap();
+                continue;
+            }
+            if state.closed {
+                break;
+            }
+            state.idle += 1;
+            let (next, timeout) = self.available.wait_timeout(state, Self::KEEP_ALIVE).unwrap();
+            state = next;
+            state.idle -= 1;
+            if timeout.timed_out() && state.queue.is_empty() {
+                break;
+            }
+        }
+        state.threads -= 1;
+    }
+}
+
+static EXECUTOR: OnceLock<Box<dyn XrossExecutor>> = OnceLock::new();
+
+/// Installs the executor used by async exports.
+/// Returns false if an executor is already in use.
+pub fn xross_set_executor(executor: impl XrossExecutor) -> bool {
+    EXECUTOR.set(Box::new(executor)).is_ok()
+}
+
+pub(crate) fn executor() -> &'static dyn XrossExecutor {
+    EXECUTOR.get_or_init(default_executor).as_ref()
+}
+
+#[cfg(feature = "tokio")]
+fn default_executor() -> Box<dyn XrossExecutor> {
+    Box::new(XrossTokioExecutor)
+}
+
+#[cfg(all(feature = "thread-pool", not(feature = "tokio")))]
+fn default_executor() -> Box<dyn XrossExecutor> {
+    Box::new(XrossThreadPool::default())
+}
+
+#[cfg(not(any(feature = "tokio", feature = "thread-pool")))]
+fn default_executor() -> Box<dyn XrossExecutor> {
+    panic!(
+        "No async executor: call xross_set_executor or enable the `tokio` or `thread-pool` feature"
+    )
+}
+
+/// Spawns futures on the tokio runtime managed by `XrossRuntimeBuilder`.
+#[cfg(feature = "tokio")]
+pub struct XrossTokioExecutor;
+
+#[cfg(feature = "tokio")]
+impl XrossExecutor for XrossTokioExecutor {
+    fn spawn(&self, future: XrossFuture) {
+        crate::runtime::handle().spawn(future);
+    }
+
+    fn spawn_blocking(&self, f: Box<dyn FnOnce() + Send + 'static>) {
+        crate::runtime::handle().spawn_blocking(f);
+    }
+}
+
+#[cfg(feature = "thread-pool")]
+pub use thread_pool::XrossThreadPool;
+
+#[cfg(feature = "thread-pool")]
+mod thread_pool {
+    use super::{BlockingPool, DEFAULT_MAX_BLOCKING_THREADS, XrossExecutor, XrossFuture};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::{Receiver, Sender, channel};
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Wake, Waker};
+
+    /// Minimal executor polling futures on a fixed set of worker threads.
+    ///
+    /// Blocking functions run on a separate pool of at most `DEFAULT_MAX_BLOCKING_THREADS`
+    /// threads unless set with `max_blocking_threads`.
+    pub struct XrossThreadPool {
+        queue: Sender<Message>,
+        closed: Arc<AtomicBool>,
+        workers: Mutex<Vec<std::thread::JoinHandle<()>>>,
+        blocking: Arc<BlockingPool>,
+    }
+
+    enum Message {
+        Poll(Arc<PoolTask>),
+        Stop,
+    }
+
+    struct PoolTask {
+        future: Mutex<Option<XrossFuture>>,
+        queue: Sender<Message>,
+        closed: Arc<AtomicBool>,
+    }
+
+    impl Wake for PoolTask {
+        fn wake(self: Arc<Self>) {
+            if self.closed.load(Ordering::Acquire) {
+                // Dropping the future reports its task as cancelled. If a worker is polling it
+                // right now, the worker drops it instead.
+                if let Ok(mut slot) = self.future.try_lock() {
+                    slot.take();
+                }
+                return;
+            }
+            let _ = self.queue.send(Message::Poll(self.clone()));
+        }
+    }
+
+    impl XrossThreadPool {
+        pub fn new(threads: usize) -> Self {
+            let (queue, rx) = channel::<Message>();
+            let rx = Arc::new(Mutex::new(rx));
+            let workers = (0..threads.max(1))
+                .map(|i| {
+                    let rx = rx.clone();
+                    std::thread::Builder::new()
+                        .name(format!("xross-worker-{}", i))
+                        .spawn(move || worker(&rx))
+                        .expect("Failed to spawn executor thread")
+                })
+                .collect();
+            Self {
+                queue,
+                closed: Arc::new(AtomicBool::new(false)),
+                workers: Mutex::new(workers),
+                blocking: Arc::new(BlockingPool::new(DEFAULT_MAX_BLOCKING_THREADS)),
+            }
+        }
+
+        /// Limits the threads running blocking functions; further calls wait for a free one.
+        pub fn max_blocking_threads(mut self, n: usize) -> Self {
+            self.blocking = Arc::new(BlockingPool::new(n));
+            self
+        }
+
+        /// Stops the worker threads once they finish their current poll and waits for them.
+        ///
+        /// Futures still queued or woken later are dropped, so their tasks report `Cancelled`.
+        /// Later spawns are dropped the same way. Blocking functions already running finish.
+        pub fn shutdown(&self) {
+            if self.closed.swap(true, Ordering::AcqRel) {
+                return;
+            }
+            self.blocking.close();
+            let workers = std::mem::take(&mut *self.workers.lock().unwrap());
+            for _ in &workers {
+                let _ = self.queue.send(Message::Stop);
+            }
+            let current = std::thread::current().id();
+            for handle in workers {
+                // A future shutting the pool down from a worker cannot wait for itself.
+                if handle.thread().id() != current {
+                    let _ = handle.join();
+                }
+            }
+        }
+    }
+
+    impl Default for XrossThreadPool {
+        fn default() -> Self {
+            Self::new(std::thread::available_parallelism().map_or(1, |n| n.get()))
+        }
+    }
+
+    impl Drop for XrossThreadPool {
+        fn drop(&mut self) {
+            self.shutdown();
+        }
+    }
+
+    impl XrossExecutor for XrossThreadPool {
+        fn spawn(&self, future: XrossFuture) {
+            if self.closed.load(Ordering::Acquire) {
+                return;
+            }
+            let task = Arc::new(PoolTask {
+                future: Mutex::new(Some(future)),
+                queue: self.queue.clone(),
+                closed: self.closed.clone(),
+            });
+            let _ = self.queue.send(Message::Poll(task));
+        }
+
+        fn spawn_blocking(&self, f: Box<dyn FnOnce() + Send + 'static>) {
+            self.blocking.spawn(f);
+        }
+    }
+
+    fn worker(rx: &Mutex<Receiver<Message>>) {
+        loop {
+            let Ok(Message::Poll(task)) = rx.lock().unwrap().recv() else { return };
+            let mut slot = task.future.lock().unwrap();
+            if task.closed.load(Ordering::Acquire) {
+                slot.take();
+                continue;
+            }
+            // `None` means the future already finished and this is a stale wake-up.
+            if let Some(mut future) = slot.take() {
+                let waker = Waker::from(task.clone());
+                let pending = future.as_mut().poll(&mut Context::from_waker(&waker)).is_pending();
+                if pending && !task.closed.load(Ordering::Acquire) {
+                    *slot = Some(future);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "thread-pool"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_pool_drives_futures_that_wake_later() {
+        let pool = XrossThreadPool::new(2);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (wake_tx, wake_rx) = futures::channel::oneshot::channel::<u32>();
+        pool.spawn(Box::pin(async move {
+            let value = wake_rx.await.unwrap();
+            tx.send(value * 2).unwrap();
+        }));
+        wake_tx.send(21).unwrap();
+        assert_eq!(rx.recv().unwrap(), 42);
+    }
+
+    #[test]
+    fn thread_pool_bounds_blocking_threads_and_drops_futures_on_shutdown() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let pool = XrossThreadPool::new(1).max_blocking_threads(2);
+        let (running, peak) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let (tx, rx) = std::sync::mpsc::channel();
+        for _ in 0..8 {
+            let (running, peak, tx) = (running.clone(), peak.clone(), tx.clone());
+            pool.spawn_blocking(Box::new(move || {
+                peak.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(5));
+                running.fetch_sub(1, Ordering::SeqCst);
+                tx.send(()).unwrap();
+            }));
+        }
+        for _ in 0..8 {
+            rx.recv().unwrap();
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+
+        struct OnDrop(std::sync::mpsc::Sender<()>);
+        impl Drop for OnDrop {
+            fn drop(&mut self) {
+                let _ = self.0.send(());
+            }
+        }
+        let (dropped_tx, dropped_rx) = std::sync::mpsc::channel();
+        let (wake_tx, wake_rx) = futures::channel::oneshot::channel::<()>();
+        let guard = OnDrop(dropped_tx.clone());
+        pool.spawn(Box::pin(async move {
+            let _guard = guard;
+            let _ = wake_rx.await;
+        }));
+        pool.shutdown();
+        drop(wake_tx);
+        dropped_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+
+        let guard = OnDrop(dropped_tx);
+        pool.spawn(Box::pin(async move {
+            let _guard = guard;
+        }));
+        dropped_rx.recv_timeout(Duration::from_secs(1)).unwrap();
+    }
+}
@@ -0,0 +1,335 @@
+use crate::{XrossError, XrossErrorKind};
+use std::any::TypeId;
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::ffi::c_void;
+use std::sync::{LazyLock, Mutex};
+
+/// Number of released slots kept to recognise stale handles by address; older ones are reused.
+const RELEASED_CAPACITY: usize = 1 << 16;
+
+/// An object handed out at `addr`, or the last one released there.
+struct Slot {
+    addr: usize,
+    /// Type the object was handed out as.
+    type_id: TypeId,
+    type_name: &'static str,
+    /// Bumped each time the slot is given to another object, so that keys to earlier ones
+    /// stop matching. Never 0.
+    generation: u32,
+    /// Live JVM handles to the object; above one only for `Arc` / `Rc` handles.
+    handles: usize,
+}
+
+/// Generational slot map of the handles handed to the JVM.
+///
+/// Handles stay plain pointers because the JVM reads fields at their layout offsets, so the
+/// JVM keeps the key of the slot (its index and generation) next to each handle and passes it
+/// to `xross_check_handle_key` before use. Exports, which only receive the pointer, find the
+/// slot by address; a released slot stays findable until more than `RELEASED_CAPACITY`
+/// slots were released after it.
+#[derive(Default)]
+struct Registry {
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+    by_addr: HashMap<usize, usize>,
+    /// Released slots, oldest first, with the generation they were released at.
+    released: VecDeque<(usize, u32)>,
+}
+
+/// Packs a slot index and generation into the key handed to the JVM; never 0.
+fn key(index: usize, generation: u32) -> u64 {
+    ((index as u64) << 32) | generation as u64
+}
+
+fn next_generation(generation: u32) -> u32 {
+    generation.checked_add(1).unwrap_or(1)
+}
+
+impl Registry {
+    fn get(&self, addr: usize) -> Option<&Slot> {
+        self.by_addr.get(&addr).map(|&index| &self.slots[index])
+    }
+
+    fn track<T: 'static>(&mut self, addr: usize) {
+        if let Some(&index) = self.by_addr.get(&addr) {
+            let slot = &mut self.slots[index];
+            if slot.handles == 0 {
+                // A new object at the address of a released one.
+                slot.generation = next_generation(slot.generation);
+                slot.type_id = TypeId::of::<T>();
+                slot.type_name = std::any::type_name::<T>();
+            }
+            slot.handles += 1;
+            return;
+        }
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    addr: 0,
+                    type_id: TypeId::of::<()>(),
+                    type_name: "",
+                    generation: 1,
+                    handles: 0,
+                });
+                self.slots.len() - 1
+            }
+        };
+        let slot = &mut self.slots[index];
+        slot.addr = addr;
+        slot.type_id = TypeId::of::<T>();
+        slot.type_name = std::any::type_name::<T>();
+        slot.handles = 1;
+        self.by_addr.insert(addr, index);
+    }
+
+    fn release(&mut self, index: usize) {
+        let slot = &mut self.slots[index];
+        slot.handles -= 1;
+        if slot.handles > 0 {
+            return;
+        }
+        self.released.push_back((index, slot.generation));
+        while self.released.len() > RELEASED_CAPACITY {
+            let (index, generation) = self.released.pop_front().unwrap();
+            self.forget(index, generation);
+        }
+    }
+
+    /// Frees the slot at `index` if it is still the released slot of `generation`.
+    /// Its generation is bumped right away, so that its key keeps failing.
+    fn forget(&mut self, index: usize, generation: u32) {
+        let slot = &mut self.slots[index];
+        if slot.handles == 0 && slot.generation == generation {
+            slot.generation = next_generation(slot.generation);
+            self.by_addr.remove(&slot.addr);
+            self.free.push(index);
+        }
+    }
+}
+
+static REGISTRY: LazyLock<Mutex<Registry>> = LazyLock::new(Default::default);
+
+fn invalid(ptr: *const c_void, what: &str, problem: std::fmt::Arguments) -> XrossError {
+    XrossError::new(
+        XrossErrorKind::InvalidHandle,
+        format!("{}: handle {:p} {}", what, ptr, problem),
+    )
+}
+
+fn stale(ptr: *const c_void, slot: &Slot, what: &str, use_: &str) -> XrossError {
+    invalid(ptr, what, format_args!("(generation {}) {}", slot.generation, use_))
+}
+
+/// Fails if the object at `slot` was handed out as another type than `T`.
+/// Handles passed as `c_void` are not checked.
+fn check_type<T: 'static>(ptr: *const T, slot: &Slot, what: &str) -> Result<(), XrossError> {
+    if TypeId::of::<T>() == TypeId::of::<c_void>() || slot.type_id == TypeId::of::<T>() {
+        return Ok(());
+    }
+    Err(invalid(
+        ptr.cast(),
+        what,
+        format_args!("is a `{}`, not a `{}`", slot.type_name, std::any::type_name::<T>()),
+    ))
+}
+
+/// Registers a handle handed to the JVM as a `T` and returns it as an untyped handle.
+#[doc(hidden)]
+pub fn xross_track_handle<T: 'static>(ptr: *mut T) -> *mut c_void {
+    if !ptr.is_null() {
+        REGISTRY.lock().unwrap().track::<T>(ptr as usize);
+    }
+    ptr.cast()
+}
+
+/// Fails if `ptr` refers to an object whose handles were all dropped or handed back to Rust,
+/// or to an object handed out as another type than `T`.
+///
+/// Null handles and objects not handed out by an export (e.g. borrowed fields) pass.
+#[doc(hidden)]
+pub fn xross_check_handle<T: 'static>(ptr: *const T, what: &str) -> Result<(), XrossError> {
+    match REGISTRY.lock().unwrap().get(ptr as usize) {
+        Some(slot) if slot.handles == 0 => {
+            Err(stale(ptr.cast(), slot, what, "was used after being dropped"))
+        }
+        Some(slot) => check_type(ptr, slot, what),
+        None => Ok(()),
+    }
+}
+
+/// Releases one handle to `ptr`, which is dropped or handed back to Rust by the caller.
+///
+/// Unlike `xross_check_handle`, a handle that was never handed out, or whose slot was
+/// reused since, is rejected: only the JVM's own handles may be consumed.
+#[doc(hidden)]
+pub fn xross_release_handle<T: 'static>(ptr: *const T, what: &str) -> Result<(), XrossError> {
+    if ptr.is_null() {
+        return Ok(());
+    }
+    let mut registry = REGISTRY.lock().unwrap();
+    let Some(&index) = registry.by_addr.get(&(ptr as usize)) else {
+        return Err(invalid(ptr.cast(), what, format_args!("is not an owned handle")));
+    };
+    let slot = &registry.slots[index];
+    if slot.handles == 0 {
+        return Err(stale(ptr.cast(), slot, what, "was already dropped"));
+    }
+    check_type(ptr, slot, what)?;
+    registry.release(index);
+    Ok(())
+}
+
+/// Forgets a stale slot at an address the JVM is given a borrowed reference to.
+#[doc(hidden)]
+pub fn xross_borrow_handle(ptr: *mut c_void) -> *mut c_void {
+    let mut registry = REGISTRY.lock().unwrap();
+    if let Some(&index) = registry.by_addr.get(&(ptr as usize))
+        && registry.slots[index].handles == 0
+    {
+        let generation = registry.slots[index].generation;
+        registry.forget(index, generation);
+    }
+    ptr
+}
+
+thread_local! {
+    /// Violation recorded by the last export on this thread that could not return it.
+    static HANDLE_ERROR: Cell<*mut XrossError> = const { Cell::new(std::ptr::null_mut()) };
+}
+
+/// Records a violation in an export that has no way to return it, for `xross_take_handle_error`,
+/// and returns the all-zero value the export returns instead of running
+/// (a null pointer, zero or an empty `XrossString`).
+///
+/// # Safety
+/// All-zero bytes must be a valid `T`.
+#[doc(hidden)]
+pub unsafe fn xross_reject_handle<T>(err: XrossError) -> T {
+    let previous = HANDLE_ERROR.replace(Box::into_raw(Box::new(err)));
+    unsafe {
+        crate::xross_free_error(previous);
+        std::mem::zeroed()
+    }
+}
+
+/// Takes the violation recorded on this thread by an export that could not return it, or null.
+///
+/// Such exports return an all-zero value without running, leaving any `out` buffer unwritten;
+/// bindings call this after them and release the error with `xross_free_error`.
+#[unsafe(no_mangle)]
+pub extern "C" fn xross_take_handle_error() -> *mut XrossError {
+    HANDLE_ERROR.replace(std::ptr::null_mut())
+}
+
+/// Returns the key of the slot of the live object at `ptr`, or 0 for an untracked address.
+///
+/// Bindings record it when wrapping a handle returned by an export and pass it to
+/// `xross_check_handle_key` with the handle before each use.
+#[unsafe(no_mangle)]
+pub extern "C" fn xross_handle_key(ptr: *const c_void) -> u64 {
+    let registry = REGISTRY.lock().unwrap();
+    match registry.by_addr.get(&(ptr as usize)) {
+        Some(&index) if registry.slots[index].handles > 0 => {
+            key(index, registry.slots[index].generation)
+        }
+        _ => 0,
+    }
+}
+
+/// Checks that `key`, returned by `xross_handle_key` for `ptr`, still refers to a live object
+/// at `ptr`. Returns null, or the `InvalidHandle` error to release with `xross_free_error`.
+///
+/// Unlike the checks of exports, which find slots by address, this also rejects a stale
+/// handle after its slot was reused, or after another object was placed at its address.
+/// A key of 0 (a handle that was not tracked) always passes.
+#[unsafe(no_mangle)]
+pub extern "C" fn xross_check_handle_key(ptr: *const c_void, key: u64) -> *mut XrossError {
+    if key == 0 {
+        return std::ptr::null_mut();
+    }
+    let (index, generation) = ((key >> 32) as usize, key as u32);
+    let registry = REGISTRY.lock().unwrap();
+    let live = registry.slots.get(index).is_some_and(|slot| {
+        slot.generation == generation && slot.handles > 0 && slot.addr == ptr as usize
+    });
+    if live {
+        return std::ptr::null_mut();
+    }
+    let err = invalid(
+        ptr,
+        "xross_check_handle_key",
+        format_args!("(slot {}, generation {}) was used after being dropped", index, generation),
+    );
+    Box::into_raw(Box::new(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_registry_detects_double_drop_and_reuse() {
+        // Leaked so that no other object is ever allocated at these addresses.
+        let ptr = Box::into_raw(Box::new(0u64));
+        assert_eq!(xross_handle_key(ptr.cast()), 0);
+        xross_track_handle(ptr);
+        let key = xross_handle_key(ptr.cast());
+        assert_ne!(key, 0);
+        assert!(xross_check_handle(ptr, "use").is_ok());
+        assert!(xross_check_handle_key(ptr.cast(), key).is_null());
+        assert!(xross_release_handle(ptr, "drop").is_ok());
+
+        let err = xross_release_handle(ptr, "drop").unwrap_err();
+        assert_eq!(err.kind, XrossErrorKind::InvalidHandle);
+        assert!(xross_check_handle(ptr, "use").is_err());
+        let err = xross_check_handle_key(ptr.cast(), key);
+        assert_eq!(unsafe { &*err }.kind, XrossErrorKind::InvalidHandle);
+        unsafe { crate::xross_free_error(err) };
+
+        // A new object at the same address gets a new key; the old one keeps failing.
+        xross_track_handle(ptr);
+        let renewed = xross_handle_key(ptr.cast());
+        assert_ne!(renewed, key);
+        assert!(xross_check_handle(ptr, "use").is_ok());
+        assert!(xross_check_handle_key(ptr.cast(), renewed).is_null());
+        let err = xross_check_handle_key(ptr.cast(), key);
+        assert!(!err.is_null());
+        unsafe { crate::xross_free_error(err) };
+        assert!(xross_release_handle(ptr, "drop").is_ok());
+
+        // Released slots are reused once enough others were released after them. The export
+        // checks no longer know the address, but the key still fails and release still refuses.
+        let others: Vec<u64> = vec![0; 1 << 16];
+        for other in &others {
+            let other = other as *const u64 as *mut u64;
+            xross_track_handle(other);
+            assert!(xross_release_handle(other, "drop").is_ok());
+        }
+        assert_eq!(xross_handle_key(ptr.cast()), 0);
+        let err = xross_check_handle_key(ptr.cast(), renewed);
+        assert!(!err.is_null());
+        unsafe { crate::xross_free_error(err) };
+        assert!(xross_release_handle(ptr, "drop").is_err());
+    }
+
+    #[test]
+    fn handle_registry_rejects_foreign_handles_and_other_types() {
+        let ptr = Box::into_raw(Box::new(0u32));
+        let err = xross_release_handle(ptr, "take").unwrap_err();
+        assert_eq!(err.kind, XrossErrorKind::InvalidHandle);
+        // Untracked objects may still be borrowed, like fields returned by reference.
+        assert!(xross_check_handle(ptr, "use").is_ok());
+
+        xross_track_handle(ptr);
+        let err = xross_check_handle(ptr.cast::<u8>(), "use").unwrap_err();
+        let message = unsafe { std::slice::from_raw_parts(err.message.ptr, err.message.len) };
+        let message = String::from_utf8_lossy(message);
+        assert!(message.contains("`u32`, not a `u8`"), "{}", message);
+        assert!(xross_check_handle(ptr.cast::<c_void>(), "use").is_ok());
+        assert!(xross_release_handle(ptr.cast::<u8>(), "drop").is_err());
+        assert!(xross_release_handle(ptr, "drop").is_ok());
+        drop(unsafe { Box::from_raw(ptr) });
+    }
+}
@@ -1,12 +1,47 @@
+use std::borrow::Cow;
 use std::ffi::c_void;
 
 pub use xross_macros::{
-    XrossClass, xross_class, xross_function, xross_function_dsl, xross_methods,
+    XrossClass, XrossError, xross_class, xross_function, xross_function_dsl, xross_methods,
+    xross_trait,
 };
 
 #[cfg(feature = "xross-alloc")]
 pub use xross_alloc::heap::xross_alloc_init;
 
+mod error;
+pub use error::*;
+mod shared;
+pub use shared::*;
+
+#[cfg(feature = "checked-handles")]
+mod handles;
+#[cfg(feature = "checked-handles")]
+pub use handles::*;
+#[cfg(feature = "live-objects")]
+mod live_objects;
+#[cfg(feature = "live-objects")]
+pub use live_objects::*;
+
+#[cfg(feature = "async")]
+mod executor;
+#[cfg(feature = "tokio")]
+mod runtime;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "async")]
+mod task;
+#[cfg(feature = "async")]
+pub use executor::*;
+#[cfg(feature = "async")]
+pub use futures::Stream;
+#[cfg(feature = "tokio")]
+pub use runtime::*;
+#[cfg(feature = "async")]
+pub use stream::*;
+#[cfg(feature = "async")]
+pub use task::*;
+
 #[cfg(feature = "xross-alloc")]
 #[global_allocator]
 static ALLOC: xross_alloc::XrossAlloc = xross_alloc::XrossAlloc;
@@ -20,6 +55,37 @@ pub struct XrossResult {
 unsafe impl Send for XrossResult {}
 unsafe impl Sync for XrossResult {}
 
+/// Tagged representation of an `Option` holding a primitive value.
+/// Unlike a nullable pointer, `Some(0)`, `Some(false)` and `Some(0.0)` stay distinguishable from `None`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct XrossOption<T> {
+    pub is_some: bool,
+    pub value: T,
+}
+
+impl<T: Copy + Default> From<Option<T>> for XrossOption<T> {
+    fn from(opt: Option<T>) -> Self {
+        match opt {
+            Some(value) => Self { is_some: true, value },
+            None => Self { is_some: false, value: T::default() },
+        }
+    }
+}
+
+impl<T> XrossOption<T> {
+    /// Converts the tagged value back into a Rust `Option`.
+    pub fn into_option(self) -> Option<T> {
+        if self.is_some { Some(self.value) } else { None }
+    }
+}
+
+impl<T> From<XrossOption<T>> for Option<T> {
+    fn from(opt: XrossOption<T>) -> Self {
+        opt.into_option()
+    }
+}
+
 /// Represent a Rust String (Vec<u8>) passed to the JVM.
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
@@ -39,6 +105,18 @@ impl From<String> for XrossString {
     }
 }
 
+impl From<&str> for XrossString {
+    fn from(s: &str) -> Self {
+        Self::from(s.to_owned())
+    }
+}
+
+impl From<Cow<'_, str>> for XrossString {
+    fn from(s: Cow<'_, str>) -> Self {
+        Self::from(s.into_owned())
+    }
+}
+
 impl XrossString {
     /// Converts the `XrossString` back into a Rust `String`.
     ///
@@ -54,6 +132,44 @@ impl XrossString {
     }
 }
 
+/// Represent a Rust `Vec<T>` passed to the JVM together with its length.
+/// Elements are laid out as in the original vector; `elem_size` and `elem_align`
+/// describe them so that the buffer can be released without knowing `T`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct XrossArray {
+    pub ptr: *mut c_void,
+    pub len: usize,
+    pub cap: usize,
+    pub elem_size: usize,
+    pub elem_align: usize,
+}
+
+impl<T> From<Vec<T>> for XrossArray {
+    fn from(v: Vec<T>) -> Self {
+        let mut v = std::mem::ManuallyDrop::new(v);
+        Self {
+            ptr: v.as_mut_ptr() as *mut c_void,
+            len: v.len(),
+            cap: v.capacity(),
+            elem_size: std::mem::size_of::<T>(),
+            elem_align: std::mem::align_of::<T>(),
+        }
+    }
+}
+
+impl XrossArray {
+    /// Converts the `XrossArray` back into a Rust `Vec<T>`.
+    ///
+    /// # Safety
+    ///
+    /// The array must have been created from a `Vec<T>` of the same `T`,
+    /// and this function must be called at most once for it.
+    pub unsafe fn into_vec<T>(self) -> Vec<T> {
+        unsafe { Vec::from_raw_parts(self.ptr as *mut T, self.len, self.cap) }
+    }
+}
+
 /// Represent a String view passed from the JVM to Rust.
 /// ptr points to the raw internal bytes of the JVM String.
 #[repr(C)]
@@ -66,18 +182,26 @@ pub struct XrossStringView {
 
 impl XrossStringView {
     pub fn to_string_lossy(&self) -> String {
+        self.as_cow().into_owned()
+    }
+
+    /// Decodes the view, borrowing the JVM bytes when no transcoding is needed.
+    ///
+    /// Pure-ASCII Latin1 payloads are valid UTF-8 and are returned as `Cow::Borrowed`;
+    /// everything else is decoded into an owned `String`.
+    pub fn as_cow(&self) -> Cow<'_, str> {
         if self.ptr.is_null() || self.len == 0 {
-            return String::new();
+            return Cow::Borrowed("");
         }
         match self.encoding {
             0 => {
                 // Latin1 (ISO-8859-1) - Direct map to chars
                 let slice = unsafe { std::slice::from_raw_parts(self.ptr, self.len) };
-                let mut out = String::with_capacity(slice.len());
-                for &byte in slice {
-                    out.push(byte as char);
+                if slice.is_ascii() {
+                    // SAFETY: ASCII bytes are valid UTF-8.
+                    return Cow::Borrowed(unsafe { std::str::from_utf8_unchecked(slice) });
                 }
-                out
+                Cow::Owned(slice.iter().map(|&byte| byte as char).collect())
             }
             1 => {
                 // UTF-16 input from JVM is passed as a raw byte sequence.
@@ -93,79 +217,99 @@ impl XrossStringView {
                 if !chunks.remainder().is_empty() {
                     decoded.push('\u{FFFD}');
                 }
-                decoded
+                Cow::Owned(decoded)
             }
-            _ => String::new(),
+            _ => Cow::Borrowed(""),
         }
     }
 }
 
+/// A JVM upcall stub handed to Rust as a closure.
+///
+/// `func` is an `extern "C"` function taking `ctx` followed by the callback arguments.
+/// `release` is called exactly once when Rust drops the callback.
 #[repr(C)]
-pub struct XrossTask {
-    pub task_ptr: *mut c_void,
-    pub poll_fn: unsafe extern "C" fn(*mut c_void) -> XrossResult,
-    pub drop_fn: unsafe extern "C" fn(*mut c_void),
+pub struct XrossCallback {
+    pub func: *const c_void,
+    pub ctx: *mut c_void,
+    pub release: Option<unsafe extern "C" fn(*mut c_void)>,
 }
 
-unsafe impl Send for XrossTask {}
-unsafe impl Sync for XrossTask {}
-
-#[cfg(feature = "tokio")]
-use std::sync::OnceLock;
-#[cfg(feature = "tokio")]
-static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+unsafe impl Send for XrossCallback {}
+unsafe impl Sync for XrossCallback {}
 
-#[cfg(feature = "tokio")]
-fn get_runtime() -> &'static tokio::runtime::Runtime {
-    RUNTIME.get_or_init(|| {
-        tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .expect("Failed to create Tokio runtime")
-    })
+impl XrossCallback {
+    /// Returns the context pointer to pass as the first argument of `func`.
+    pub fn ctx(&self) -> *mut c_void {
+        self.ctx
+    }
 }
 
-#[cfg(feature = "tokio")]
-pub fn xross_spawn_task<F, T>(future: F, mapper: fn(T) -> XrossResult) -> XrossTask
-where
-    F: Future<Output = T> + Send + 'static,
-    T: Send + 'static,
-{
-    let rt = get_runtime();
-    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-
-    rt.spawn(async move {
-        let res = future.await;
-        let _ = tx.send(mapper(res));
-    });
-
-    unsafe extern "C" fn poll_task(ptr: *mut c_void) -> XrossResult {
-        let rx = unsafe { &mut *(ptr as *mut tokio::sync::mpsc::UnboundedReceiver<XrossResult>) };
-        match rx.try_recv() {
-            Ok(res) => res,
-            Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
-                XrossResult { is_ok: true, ptr: std::ptr::null_mut() }
-            }
-            Err(_) => XrossResult { is_ok: false, ptr: std::ptr::null_mut() },
+impl Drop for XrossCallback {
+    fn drop(&mut self) {
+        if let Some(release) = self.release {
+            unsafe { release(self.ctx) };
         }
     }
+}
 
-    unsafe extern "C" fn drop_task(ptr: *mut c_void) {
-        let _ =
-            unsafe { Box::from_raw(ptr as *mut tokio::sync::mpsc::UnboundedReceiver<XrossResult>) };
-    }
+/// Lifecycle state of an `XrossTask`.
+/// The discriminants are mirrored by the `XrossAsync` constants of the generated Kotlin runtime.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XrossTaskStatus {
+    /// The task is still running.
+    Pending = 0,
+    /// The future finished; `is_ok` tells `Ok` from `Err`.
+    Completed = 1,
+    /// The future panicked; `ptr` holds an `XrossError`.
+    Panicked = 2,
+    /// The task was cancelled before it finished; `ptr` is null.
+    Cancelled = 3,
+}
 
-    XrossTask {
-        task_ptr: Box::into_raw(Box::new(rx)) as *mut c_void,
-        poll_fn: poll_task,
-        drop_fn: drop_task,
-    }
+/// Handle to an asynchronous computation running on the Rust side.
+///
+/// `status_fn` reports the lifecycle state. Once it is no longer `Pending`,
+/// `poll_fn` returns the result exactly once; while pending it returns `{ is_ok: true, ptr: null }`.
+/// `cancel_fn` aborts outstanding work and `drop_fn` aborts it before releasing the handle.
+#[repr(C)]
+pub struct XrossTask {
+    pub task_ptr: *mut c_void,
+    pub poll_fn: unsafe extern "C" fn(*mut c_void) -> XrossResult,
+    pub drop_fn: unsafe extern "C" fn(*mut c_void),
+    pub cancel_fn: unsafe extern "C" fn(*mut c_void),
+    pub status_fn: unsafe extern "C" fn(*mut c_void) -> XrossTaskStatus,
 }
 
+unsafe impl Send for XrossTask {}
+unsafe impl Sync for XrossTask {}
+
 pub trait XrossClass {
     fn xross_layout() -> String;
 }
 
+/// Description of an error whose type derives `XrossError`, written by `{type}_describe`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct XrossErrorInfo {
+    /// Index of the variant in the error metadata; 0 for structs.
+    pub variant: u32,
+    /// The `Display` output of the error.
+    pub message: XrossString,
+    /// The `Display` output of each `source()` in the chain, nearest first, as `XrossString`s.
+    pub sources: XrossArray,
+}
+
+impl XrossErrorInfo {
+    pub fn new(variant: u32, err: &dyn std::error::Error) -> Self {
+        let sources: Vec<XrossString> = std::iter::successors(err.source(), |e| e.source())
+            .map(|e| e.to_string().into())
+            .collect();
+        Self { variant, message: err.to_string().into(), sources: sources.into() }
+    }
+}
+
 /// Frees a string allocated by Rust that was passed to the JVM.
 ///
 /// # Safety
@@ -181,9 +325,60 @@ pub unsafe extern "C" fn xross_free_string(xs: XrossString) {
     }
 }
 
+/// Frees the box of an optional task or stream result. The value it holds is not released.
+///
+/// # Safety
+///
+/// The caller must ensure that `opt` was returned by an Xross task or stream and has not been
+/// freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xross_free_option(opt: *mut XrossOption<*mut c_void>) {
+    if !opt.is_null() {
+        drop(unsafe { Box::from_raw(opt) });
+    }
+}
+
+/// Frees the buffer of an array allocated by Rust that was passed to the JVM.
+/// The elements are not dropped; owned elements (strings, handles) must be
+/// released or taken over by the caller beforehand.
+///
+/// # Safety
+///
+/// The caller must ensure that the `XrossArray` was originally provided by the Xross bridge
+/// and has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xross_free_array(arr: XrossArray) {
+    if arr.ptr.is_null() || arr.cap == 0 || arr.elem_size == 0 {
+        return;
+    }
+    unsafe {
+        let layout =
+            std::alloc::Layout::from_size_align_unchecked(arr.cap * arr.elem_size, arr.elem_align);
+        std::alloc::dealloc(arr.ptr as *mut u8, layout);
+    }
+}
+
+/// Frees an array returned boxed (through an `XrossResult`, an `Option` or a task) and its box.
+/// As with `xross_free_array`, the elements are not dropped.
+///
+/// # Safety
+///
+/// The caller must ensure that `arr` points to a boxed `XrossArray` provided by the Xross bridge
+/// and has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xross_free_boxed_array(arr: *mut XrossArray) {
+    if !arr.is_null() {
+        let arr = unsafe { Box::from_raw(arr) };
+        unsafe { xross_free_array(*arr) };
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::XrossStringView;
+    use super::{
+        XrossArray, XrossOption, XrossStringView, xross_free_array, xross_free_boxed_array,
+    };
+    use std::borrow::Cow;
 
     #[test]
     fn decodes_latin1_losslessly() {
@@ -192,6 +387,17 @@ mod tests {
         assert_eq!(view.to_string_lossy(), "Aßÿ");
     }
 
+    #[test]
+    fn borrows_ascii_latin1_without_copying() {
+        let ascii = b"hello";
+        let view = XrossStringView { ptr: ascii.as_ptr(), len: ascii.len(), encoding: 0 };
+        assert!(matches!(view.as_cow(), Cow::Borrowed("hello")));
+
+        let latin1 = [0x63, 0x61, 0x66, 0xE9];
+        let view = XrossStringView { ptr: latin1.as_ptr(), len: latin1.len(), encoding: 0 };
+        assert!(matches!(view.as_cow(), Cow::Owned(ref s) if s == "café"));
+    }
+
     #[test]
     fn decodes_utf16_le_from_raw_bytes() {
         let bytes = [0x41, 0x00, 0x42, 0x00, 0x3D, 0xD8, 0x00, 0xDE]; // A, B, 😀
@@ -205,4 +411,67 @@ mod tests {
         let view = XrossStringView { ptr: bytes.as_ptr(), len: bytes.len(), encoding: 1 };
         assert_eq!(view.to_string_lossy(), "A�");
     }
+
+    #[test]
+    fn tagged_option_keeps_zero_values() {
+        let zero = XrossOption::from(Some(0i32));
+        assert!(zero.is_some);
+        assert_eq!(zero.into_option(), Some(0));
+        assert_eq!(XrossOption::from(Some(false)).into_option(), Some(false));
+        assert_eq!(XrossOption::<f64>::from(None).into_option(), None);
+    }
+
+    #[test]
+    fn array_round_trips_and_frees_without_type() {
+        let arr = XrossArray::from(vec![1u64, 2, 3]);
+        assert_eq!((arr.len, arr.elem_size, arr.elem_align), (3, 8, 8));
+        assert_eq!(unsafe { arr.into_vec::<u64>() }, vec![1, 2, 3]);
+
+        let mut v = Vec::with_capacity(16);
+        v.push(7u16);
+        unsafe { xross_free_array(XrossArray::from(v)) };
+        unsafe { xross_free_boxed_array(Box::into_raw(Box::new(XrossArray::from(vec![1u8; 5])))) };
+        unsafe { xross_free_boxed_array(std::ptr::null_mut()) };
+    }
+
+    #[test]
+    fn describes_err_values_by_their_strongest_trait() {
+        #[derive(Debug)]
+        struct Outer(std::io::Error);
+        impl std::fmt::Display for Outer {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "outer")
+            }
+        }
+        impl std::error::Error for Outer {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+        struct Opaque;
+
+        let outer = Outer(std::io::Error::other("inner"));
+        assert_eq!(crate::xross_describe_err!(&outer), ("outer".into(), vec!["inner".into()]));
+        assert_eq!(crate::xross_describe_err!(&"plain".to_string()), ("plain".into(), vec![]));
+        assert!(crate::xross_describe_err!(&Opaque).0.ends_with("Opaque"));
+
+        let err =
+            crate::XrossError::from_err("outer".into(), vec!["inner".into()], std::ptr::null_mut());
+        let res = err.into_result();
+        assert!(!res.is_ok);
+        unsafe { crate::xross_free_error(res.ptr as *mut crate::XrossError) };
+    }
+
+    #[test]
+    fn caught_panics_keep_their_message_across_resume() {
+        let panic = crate::xross_catch_panic(|| panic!("boom")).unwrap_err();
+        let resumed = crate::xross_catch_panic(move || panic.resume()).unwrap_err();
+        let err = resumed.into_error("Unknown panic");
+        assert_eq!(err.kind, crate::XrossErrorKind::Panic);
+        let res = err.into_result();
+        let err = unsafe { &*(res.ptr as *const crate::XrossError) };
+        let message = unsafe { std::slice::from_raw_parts(err.message.ptr, err.message.len) };
+        assert_eq!(message, b"boom");
+        unsafe { crate::xross_free_error(res.ptr as *mut crate::XrossError) };
+    }
 }
@@ -0,0 +1,128 @@
+use crate::{XrossArray, XrossString};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+/// A type handed to the JVM as an object handle, implemented by the bridging macros.
+pub trait XrossObject {
+    /// The signature of the type in the metadata, e.g. `graphics.Color`.
+    const SIGNATURE: &'static str;
+}
+
+/// Handles to one type handed to the JVM.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct XrossObjectCount {
+    /// Handles not yet dropped or handed back to Rust. Negative if more were released than
+    /// counted, e.g. for objects handed out without going through an export.
+    pub live: i64,
+    /// Handles handed out in total.
+    pub total: u64,
+    /// The highest `live` seen.
+    pub peak: u64,
+}
+
+/// One entry of the snapshot returned by `xross_live_objects`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct XrossLiveObjects {
+    pub signature: XrossString,
+    pub count: XrossObjectCount,
+}
+
+static COUNTS: LazyLock<Mutex<HashMap<&'static str, XrossObjectCount>>> =
+    LazyLock::new(Default::default);
+
+fn record(signature: &'static str, delta: i64) {
+    let mut counts = COUNTS.lock().unwrap();
+    let count = counts.entry(signature).or_default();
+    count.live += delta;
+    if delta > 0 {
+        count.total += delta as u64;
+        count.peak = count.peak.max(count.live.max(0) as u64);
+    }
+}
+
+/// Returns the counts of every type handed out so far, sorted by signature.
+pub fn xross_object_counts() -> Vec<(&'static str, XrossObjectCount)> {
+    let mut counts: Vec<_> = COUNTS.lock().unwrap().iter().map(|(s, c)| (*s, *c)).collect();
+    counts.sort_by_key(|(signature, _)| *signature);
+    counts
+}
+
+/// Returns the counts of the handles to `T`.
+pub fn xross_object_count<T: XrossObject>() -> XrossObjectCount {
+    COUNTS.lock().unwrap().get(T::SIGNATURE).copied().unwrap_or_default()
+}
+
+/// Wraps a pointer for `xross_count_object!`, which counts it only if it points to an `XrossObject`.
+#[doc(hidden)]
+pub struct XrossCounted<T: ?Sized>(pub *mut T);
+
+#[doc(hidden)]
+pub trait XrossCountObject {
+    fn xross_count(self, delta: i64);
+}
+
+#[doc(hidden)]
+pub trait XrossCountOther {
+    fn xross_count(self, delta: i64);
+}
+
+impl<T: XrossObject> XrossCountObject for &&XrossCounted<T> {
+    fn xross_count(self, delta: i64) {
+        if !self.0.is_null() {
+            record(T::SIGNATURE, delta);
+        }
+    }
+}
+
+impl<T: ?Sized> XrossCountOther for &XrossCounted<T> {
+    fn xross_count(self, _delta: i64) {}
+}
+
+/// Counts a handle handed to the JVM and evaluates to the pointer unchanged.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! xross_count_object {
+    ($ptr:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::{XrossCountObject as _, XrossCountOther as _};
+        let ptr = $ptr;
+        (&&$crate::XrossCounted(ptr)).xross_count(1);
+        ptr
+    }};
+}
+
+/// Uncounts a handle dropped or handed back to Rust by the JVM.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! xross_uncount_object {
+    ($ptr:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::{XrossCountObject as _, XrossCountOther as _};
+        (&&$crate::XrossCounted($ptr)).xross_count(-1);
+    }};
+}
+
+/// Returns an `XrossArray` of `XrossLiveObjects`, one per type handed out so far,
+/// sorted by signature. Released with `xross_free_live_objects`.
+#[unsafe(no_mangle)]
+pub extern "C" fn xross_live_objects() -> XrossArray {
+    let entries: Vec<XrossLiveObjects> = xross_object_counts()
+        .into_iter()
+        .map(|(signature, count)| XrossLiveObjects { signature: signature.into(), count })
+        .collect();
+    entries.into()
+}
+
+/// Frees a snapshot returned by `xross_live_objects`.
+///
+/// # Safety
+///
+/// `arr` must come from `xross_live_objects` and must not have been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xross_free_live_objects(arr: XrossArray) {
+    for entry in unsafe { arr.into_vec::<XrossLiveObjects>() } {
+        unsafe { crate::xross_free_string(entry.signature) };
+    }
+}
@@ -0,0 +1,245 @@
+use crate::XrossStringView;
+use std::sync::{Arc, Mutex};
+use tokio::runtime::{Handle, Runtime};
+
+/// Settings of the runtime driving async exports, as passed from the JVM.
+///
+/// Zero sizes and an empty thread name keep the tokio defaults.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct XrossRuntimeConfig {
+    pub worker_threads: usize,
+    pub max_blocking_threads: usize,
+    pub thread_stack_size: usize,
+    pub thread_name: XrossStringView,
+    pub current_thread: bool,
+}
+
+/// Builder for the runtime driving async exports.
+///
+/// The runtime is started lazily by the first async call; `install` must happen before that.
+#[derive(Debug, Clone, Default)]
+pub struct XrossRuntimeBuilder {
+    worker_threads: Option<usize>,
+    max_blocking_threads: Option<usize>,
+    thread_stack_size: Option<usize>,
+    thread_name: Option<String>,
+    current_thread: bool,
+}
+
+impl XrossRuntimeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn worker_threads(mut self, n: usize) -> Self {
+        self.worker_threads = Some(n);
+        self
+    }
+
+    pub fn max_blocking_threads(mut self, n: usize) -> Self {
+        self.max_blocking_threads = Some(n);
+        self
+    }
+
+    pub fn thread_stack_size(mut self, size: usize) -> Self {
+        self.thread_stack_size = Some(size);
+        self
+    }
+
+    pub fn thread_name(mut self, name: impl Into<String>) -> Self {
+        self.thread_name = Some(name.into());
+        self
+    }
+
+    /// Uses a single-threaded runtime driven by one dedicated thread.
+    pub fn current_thread(mut self, enabled: bool) -> Self {
+        self.current_thread = enabled;
+        self
+    }
+
+    /// Replaces the runtime settings. Returns false if the runtime is already running.
+    pub fn install(self) -> bool {
+        RUNTIME.lock().unwrap().install(self)
+    }
+
+    fn build(&self) -> std::io::Result<Runtime> {
+        let mut builder = if self.current_thread {
+            tokio::runtime::Builder::new_current_thread()
+        } else {
+            tokio::runtime::Builder::new_multi_thread()
+        };
+        builder.enable_all();
+        if let Some(n) = self.worker_threads {
+            builder.worker_threads(n);
+        }
+        if let Some(n) = self.max_blocking_threads {
+            builder.max_blocking_threads(n);
+        }
+        if let Some(size) = self.thread_stack_size {
+            builder.thread_stack_size(size);
+        }
+        if let Some(name) = &self.thread_name {
+            builder.thread_name(name);
+        }
+        builder.build()
+    }
+}
+
+impl From<&XrossRuntimeConfig> for XrossRuntimeBuilder {
+    fn from(config: &XrossRuntimeConfig) -> Self {
+        let non_zero = |n: usize| (n != 0).then_some(n);
+        let name = config.thread_name.to_string_lossy();
+        Self {
+            worker_threads: non_zero(config.worker_threads),
+            max_blocking_threads: non_zero(config.max_blocking_threads),
+            thread_stack_size: non_zero(config.thread_stack_size),
+            thread_name: (!name.is_empty()).then_some(name),
+            current_thread: config.current_thread,
+        }
+    }
+}
+
+struct Running {
+    runtime: Arc<Runtime>,
+    /// Thread blocking on a current-thread runtime, with the signal that releases it.
+    driver: Option<(std::thread::JoinHandle<()>, tokio::sync::oneshot::Sender<()>)>,
+}
+
+impl Running {
+    /// Stops the driver thread, then waits up to `timeout` for running tasks to yield.
+    fn shutdown(self, timeout: std::time::Duration) {
+        if let Some((thread, stop)) = self.driver {
+            let _ = stop.send(());
+            let _ = thread.join();
+        }
+        match Arc::try_unwrap(self.runtime) {
+            Ok(runtime) => runtime.shutdown_timeout(timeout),
+            Err(_) => unreachable!("runtime is only shared with its driver thread"),
+        }
+    }
+}
+
+/// Settings and, once started, the runtime itself.
+pub(crate) struct RuntimeSlot {
+    builder: XrossRuntimeBuilder,
+    running: Option<Running>,
+}
+
+impl RuntimeSlot {
+    pub(crate) const fn new() -> Self {
+        Self {
+            builder: XrossRuntimeBuilder {
+                worker_threads: None,
+                max_blocking_threads: None,
+                thread_stack_size: None,
+                thread_name: None,
+                current_thread: false,
+            },
+            running: None,
+        }
+    }
+
+    pub(crate) fn install(&mut self, builder: XrossRuntimeBuilder) -> bool {
+        if self.running.is_some() {
+            return false;
+        }
+        self.builder = builder;
+        true
+    }
+
+    /// Returns a handle to the runtime, starting it with the installed settings if needed.
+    pub(crate) fn handle(&mut self) -> Handle {
+        if let Some(running) = &self.running {
+            return running.runtime.handle().clone();
+        }
+
+        let runtime = Arc::new(self.builder.build().expect("Failed to create Tokio runtime"));
+        let driver = self.builder.current_thread.then(|| {
+            let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+            let rt = runtime.clone();
+            let name =
+                self.builder.thread_name.clone().unwrap_or_else(|| "xross-runtime".to_string());
+            let thread = std::thread::Builder::new()
+                .name(name)
+                .spawn(move || {
+                    let _ = rt.block_on(rx);
+                })
+                .expect("Failed to spawn runtime driver thread");
+            (thread, tx)
+        });
+        let handle = runtime.handle().clone();
+        self.running = Some(Running { runtime, driver });
+        handle
+    }
+
+    /// Stops the runtime of `slot`, without holding the lock while waiting for the tasks.
+    pub(crate) fn shutdown(slot: &Mutex<Self>, timeout: std::time::Duration) -> bool {
+        let Some(running) = slot.lock().unwrap().running.take() else { return false };
+        running.shutdown(timeout);
+        true
+    }
+}
+
+static RUNTIME: Mutex<RuntimeSlot> = Mutex::new(RuntimeSlot::new());
+
+/// Returns a handle to the runtime, starting it with the installed settings if needed.
+pub(crate) fn handle() -> Handle {
+    RUNTIME.lock().unwrap().handle()
+}
+
+/// Stops the runtime, waiting up to `timeout` for running tasks to yield.
+/// Returns false if the runtime was not running.
+///
+/// The settings are kept, so the next async call starts a fresh runtime.
+/// Must not be called from a thread of the runtime itself.
+pub fn xross_shutdown_runtime(timeout: std::time::Duration) -> bool {
+    RuntimeSlot::shutdown(&RUNTIME, timeout)
+}
+
+/// Configures the runtime before its first use.
+/// Returns false if `config` is null or the runtime is already running.
+///
+/// # Safety
+///
+/// `config` must be null or point to a valid `XrossRuntimeConfig`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn xross_runtime_configure(config: *const XrossRuntimeConfig) -> bool {
+    let Some(config) = (unsafe { config.as_ref() }) else { return false };
+    XrossRuntimeBuilder::from(config).install()
+}
+
+/// Shuts the runtime down, waiting at most `timeout_millis` for running tasks.
+/// Returns false if the runtime was not running.
+#[unsafe(no_mangle)]
+pub extern "C" fn xross_runtime_shutdown(timeout_millis: u64) -> bool {
+    xross_shutdown_runtime(std::time::Duration::from_millis(timeout_millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_uses_installed_settings_and_restarts_after_shutdown() {
+        use std::time::Duration;
+
+        // A slot of its own, so that async exports run by other tests cannot start it first.
+        let slot = std::sync::Mutex::new(RuntimeSlot::new());
+        let builder = XrossRuntimeBuilder::new().current_thread(true).thread_name("xross-test");
+        assert!(slot.lock().unwrap().install(builder));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        slot.lock().unwrap().handle().spawn(async move {
+            tx.send(std::thread::current().name() == Some("xross-test")).unwrap();
+        });
+        assert!(rx.recv().unwrap());
+
+        assert!(!slot.lock().unwrap().install(XrossRuntimeBuilder::new()));
+        assert!(RuntimeSlot::shutdown(&slot, Duration::from_secs(1)));
+        assert!(!RuntimeSlot::shutdown(&slot, Duration::from_secs(1)));
+        assert!(slot.lock().unwrap().install(XrossRuntimeBuilder::new()));
+
+        assert!(!unsafe { xross_runtime_configure(std::ptr::null()) });
+    }
+}
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+/// A type handed to the JVM as `Arc<Self>`, implemented for types marked `#[xross(shared)]`.
+///
+/// Each JVM handle holds one strong reference and is released from the JVM's Cleaner
+/// thread, so the type must be `Send + Sync`. Bridged signatures using `Arc<T>` require
+/// this trait, which guarantees that `T` exports `_arc_clone` and `_arc_drop`.
+pub trait XrossShared: Send + Sync {}
+
+/// Hands an `Arc<T>` to the JVM as a handle holding one strong reference.
+#[doc(hidden)]
+pub fn xross_arc_into_handle<T: XrossShared>(arc: Arc<T>) -> *mut T {
+    Arc::into_raw(arc).cast_mut()
+}
+
+/// Takes a new strong reference out of a handle that stays owned by the JVM.
+///
+/// # Safety
+///
+/// `ptr` must be a live handle created by `xross_arc_into_handle` or `_arc_clone`.
+#[doc(hidden)]
+pub unsafe fn xross_arc_from_handle<T: XrossShared>(ptr: *const T) -> Arc<T> {
+    unsafe {
+        Arc::increment_strong_count(ptr);
+        Arc::from_raw(ptr)
+    }
+}
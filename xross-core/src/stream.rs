@@ -0,0 +1,184 @@
+use crate::executor::executor;
+use crate::{XrossError, XrossResult};
+use futures::channel::mpsc::TryRecvError;
+use futures::future::AbortHandle;
+use futures::{FutureExt, SinkExt, Stream, StreamExt};
+use std::ffi::c_void;
+use std::sync::{Arc, Mutex};
+
+/// Number of items buffered ahead of the JVM consumer.
+const STREAM_BUFFER: usize = 16;
+
+/// State of an `XrossStream` reported by `poll_next_fn`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XrossStreamStatus {
+    /// No item is ready yet.
+    Pending = 0,
+    /// `value` holds the next item; `is_ok` is false for an `Err` item.
+    Item = 1,
+    /// The stream ended.
+    Done = 2,
+    /// The stream panicked; `value.ptr` holds an `XrossError`.
+    Panicked = 3,
+    /// The stream was cancelled before it ended.
+    Cancelled = 4,
+}
+
+/// Result of polling an `XrossStream`.
+#[repr(C)]
+pub struct XrossStreamPoll {
+    pub status: XrossStreamStatus,
+    pub value: XrossResult,
+}
+
+/// Handle to a stream of values produced on the Rust side.
+///
+/// The stream is driven by the executor and buffers a few items ahead.
+/// `cancel_fn` stops producing further items and `drop_fn` cancels before releasing the handle.
+#[repr(C)]
+pub struct XrossStream {
+    pub stream_ptr: *mut c_void,
+    pub poll_next_fn: unsafe extern "C" fn(*mut c_void) -> XrossStreamPoll,
+    pub cancel_fn: unsafe extern "C" fn(*mut c_void),
+    pub drop_fn: unsafe extern "C" fn(*mut c_void),
+}
+
+unsafe impl Send for XrossStream {}
+unsafe impl Sync for XrossStream {}
+
+/// Spawns a task draining `stream` into a buffer read through `XrossStream::poll_next_fn`.
+///
+/// Items are buffered as `T` and mapped when polled, so items never read are dropped with the
+/// stream instead of leaking.
+pub fn xross_spawn_stream<S, T>(stream: S, mapper: fn(T) -> XrossResult) -> XrossStream
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: Send + 'static,
+{
+    crate::xross_install_panic_hook();
+    let (mut tx, rx) = futures::channel::mpsc::channel(STREAM_BUFFER);
+    // Terminal state, set before the sender is dropped. `None` once closed means cancelled.
+    let end = Arc::new(Mutex::new(None));
+    let end_slot = end.clone();
+
+    let (driver, abort) = futures::future::abortable(async move {
+        let mut stream = Box::pin(stream);
+        loop {
+            match std::panic::AssertUnwindSafe(stream.next()).catch_unwind().await {
+                Ok(Some(val)) => {
+                    if tx.send(val).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => {
+                    let done = XrossResult { is_ok: true, ptr: std::ptr::null_mut() };
+                    *end_slot.lock().unwrap() =
+                        Some(XrossStreamPoll { status: XrossStreamStatus::Done, value: done });
+                    break;
+                }
+                Err(payload) => {
+                    let value = XrossError::from_panic(&*payload).into_result();
+                    *end_slot.lock().unwrap() =
+                        Some(XrossStreamPoll { status: XrossStreamStatus::Panicked, value });
+                    break;
+                }
+            }
+        }
+    });
+    executor().spawn(Box::pin(async move {
+        let _ = driver.await;
+    }));
+
+    let state = StreamState { rx, mapper, end, abort };
+    XrossStream {
+        stream_ptr: Box::into_raw(Box::new(state)) as *mut c_void,
+        poll_next_fn: poll_next::<T>,
+        cancel_fn: cancel_stream::<T>,
+        drop_fn: drop_stream::<T>,
+    }
+}
+
+struct StreamState<T> {
+    rx: futures::channel::mpsc::Receiver<T>,
+    mapper: fn(T) -> XrossResult,
+    end: Arc<Mutex<Option<XrossStreamPoll>>>,
+    abort: AbortHandle,
+}
+
+unsafe extern "C" fn poll_next<T>(ptr: *mut c_void) -> XrossStreamPoll {
+    let state = unsafe { &mut *(ptr as *mut StreamState<T>) };
+    match state.rx.try_recv() {
+        Ok(item) => {
+            XrossStreamPoll { status: XrossStreamStatus::Item, value: (state.mapper)(item) }
+        }
+        // The terminal state is sticky; its payload is handed out only once.
+        Err(TryRecvError::Closed) => match state.end.lock().unwrap().as_mut() {
+            Some(end) => {
+                let empty = XrossResult { is_ok: end.value.is_ok, ptr: std::ptr::null_mut() };
+                XrossStreamPoll {
+                    status: end.status,
+                    value: std::mem::replace(&mut end.value, empty),
+                }
+            }
+            None => XrossStreamPoll {
+                status: XrossStreamStatus::Cancelled,
+                value: XrossResult { is_ok: false, ptr: std::ptr::null_mut() },
+            },
+        },
+        Err(TryRecvError::Empty) => XrossStreamPoll {
+            status: XrossStreamStatus::Pending,
+            value: XrossResult { is_ok: true, ptr: std::ptr::null_mut() },
+        },
+    }
+}
+
+unsafe extern "C" fn cancel_stream<T>(ptr: *mut c_void) {
+    let state = unsafe { &*(ptr as *const StreamState<T>) };
+    state.abort.abort();
+}
+
+unsafe extern "C" fn drop_stream<T>(ptr: *mut c_void) {
+    let mut state = unsafe { Box::from_raw(ptr as *mut StreamState<T>) };
+    state.abort.abort();
+    // Items still buffered are dropped here rather than when the aborted driver goes away.
+    state.rx.close();
+    while let Ok(item) = state.rx.try_recv() {
+        drop(item);
+    }
+    // A panic payload the JVM never polled.
+    if let Some(end) = state.end.lock().unwrap().take()
+        && end.status == XrossStreamStatus::Panicked
+        && !end.value.ptr.is_null()
+    {
+        unsafe { crate::xross_free_error(end.value.ptr as *mut XrossError) };
+    }
+}
+
+#[cfg(all(test, any(feature = "tokio", feature = "thread-pool")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropping_a_stream_drops_its_unread_items() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+        struct Item;
+        impl Drop for Item {
+            fn drop(&mut self) {
+                DROPPED.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let stream = xross_spawn_stream(futures::stream::iter([Item, Item, Item]), |_| {
+            XrossResult { is_ok: true, ptr: std::ptr::null_mut() }
+        });
+        unsafe { (stream.drop_fn)(stream.stream_ptr) };
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while DROPPED.load(Ordering::SeqCst) < 3 {
+            assert!(std::time::Instant::now() < deadline, "unread stream items were leaked");
+            std::thread::yield_now();
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::executor::executor;
+use crate::{XrossCallback, XrossError, XrossResult, XrossTask, XrossTaskStatus};
+use futures::FutureExt;
+use futures::future::AbortHandle;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub fn xross_spawn_task<F, T>(future: F, mapper: fn(T) -> XrossResult) -> XrossTask
+where
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    spawn_task(future, mapper, None)
+}
+
+/// Spawns a task that pushes its result to `on_complete` instead of waiting to be polled.
+///
+/// `on_complete.func` is an `extern "C" fn(ctx, XrossResult, XrossTaskStatus)` and is invoked
+/// exactly once, whether the task completes, panics or is cancelled.
+/// The returned task is only used for cancelling and dropping. If `func` is null, the result
+/// is kept for `poll_fn` as with `xross_spawn_task`.
+pub fn xross_spawn_task_notify<F, T>(
+    future: F,
+    mapper: fn(T) -> XrossResult,
+    on_complete: XrossCallback,
+) -> XrossTask
+where
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    spawn_task(future, mapper, Some(on_complete))
+}
+
+/// Runs a synchronous function on the executor's blocking pool and resolves to its result.
+///
+/// Panics are re-raised when the future is polled, so the task reports them as usual.
+/// Cancelling the task does not interrupt `f` once it has started.
+pub async fn xross_run_blocking<F, T>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = futures::channel::oneshot::channel();
+    executor().spawn_blocking(Box::new(move || {
+        let _ = tx.send(crate::xross_catch_panic(f));
+    }));
+    match rx.await {
+        Ok(Ok(val)) => val,
+        Ok(Err(panic)) => panic.resume(),
+        Err(_) => panic!("Blocking call was dropped by the executor"),
+    }
+}
+
+/// `XrossTaskStatus` as stored in `TaskShared::status`, the values the JVM compares against.
+const PENDING: u8 = XrossTaskStatus::Pending as u8;
+const COMPLETED: u8 = XrossTaskStatus::Completed as u8;
+const PANICKED: u8 = XrossTaskStatus::Panicked as u8;
+const CANCELLED: u8 = XrossTaskStatus::Cancelled as u8;
+
+/// The outcome of a finished task. A value is only converted by the mapper when it is handed
+/// out, so a result that is never polled is dropped as its Rust type.
+enum TaskOutcome {
+    Value(Box<dyn FnOnce() -> XrossResult + Send>),
+    /// A panic (`ptr` is an `XrossError`) or a cancellation (`ptr` is null).
+    Failed(XrossResult),
+}
+
+impl TaskOutcome {
+    fn into_result(self) -> XrossResult {
+        match self {
+            TaskOutcome::Value(map) => map(),
+            TaskOutcome::Failed(res) => res,
+        }
+    }
+}
+
+/// State shared between a running task and its `XrossTask` handle.
+struct TaskShared {
+    status: AtomicU8,
+    result: Mutex<Option<TaskOutcome>>,
+}
+
+impl Drop for TaskShared {
+    fn drop(&mut self) {
+        // The task was dropped without polling its outcome, or finished after the handle was dropped.
+        if let Some(TaskOutcome::Failed(res)) =
+            self.result.get_mut().unwrap_or_else(|e| e.into_inner()).take()
+            && !res.ptr.is_null()
+        {
+            unsafe { crate::xross_free_error(res.ptr as *mut XrossError) };
+        }
+    }
+}
+
+/// Delivers the outcome of a task once. If the future is dropped before it
+/// produces a value, the task is reported as cancelled.
+struct Completion {
+    shared: Arc<TaskShared>,
+    callback: Option<XrossCallback>,
+    done: bool,
+}
+
+impl Completion {
+    fn finish(&mut self, status: XrossTaskStatus, outcome: TaskOutcome) {
+        if std::mem::replace(&mut self.done, true) {
+            return;
+        }
+        let callback = self.callback.take();
+        // A null pointer transmutes to `None`.
+        let func: Option<unsafe extern "C" fn(*mut c_void, XrossResult, XrossTaskStatus)> =
+            callback.as_ref().and_then(|c| unsafe { std::mem::transmute(c.func) });
+        match (callback, func) {
+            (Some(callback), Some(func)) => unsafe {
+                func(callback.ctx(), outcome.into_result(), status)
+            },
+            _ => *self.shared.result.lock().unwrap() = Some(outcome),
+        }
+        // Published after the result so that a non-pending status guarantees `poll_fn` sees it.
+        self.shared.status.store(status as u8, Ordering::Release);
+    }
+}
+
+impl Drop for Completion {
+    fn drop(&mut self) {
+        self.finish(
+            XrossTaskStatus::Cancelled,
+            TaskOutcome::Failed(XrossResult { is_ok: false, ptr: std::ptr::null_mut() }),
+        );
+    }
+}
+
+fn spawn_task<F, T>(
+    future: F,
+    mapper: fn(T) -> XrossResult,
+    callback: Option<XrossCallback>,
+) -> XrossTask
+where
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let shared = Arc::new(TaskShared { status: AtomicU8::new(PENDING), result: Mutex::new(None) });
+    let mut completion = Completion { shared: shared.clone(), callback, done: false };
+    crate::xross_install_panic_hook();
+    let (future, abort) =
+        futures::future::abortable(std::panic::AssertUnwindSafe(future).catch_unwind());
+    executor().spawn(Box::pin(async move {
+        match future.await {
+            Ok(Ok(val)) => completion.finish(
+                XrossTaskStatus::Completed,
+                TaskOutcome::Value(Box::new(move || mapper(val))),
+            ),
+            Ok(Err(payload)) => completion.finish(
+                XrossTaskStatus::Panicked,
+                TaskOutcome::Failed(XrossError::from_panic(&*payload).into_result()),
+            ),
+            // Aborted: dropping `completion` reports the cancellation.
+            Err(_) => {}
+        }
+    }));
+
+    struct TaskState {
+        shared: Arc<TaskShared>,
+        abort: AbortHandle,
+    }
+
+    unsafe extern "C" fn poll_task(ptr: *mut c_void) -> XrossResult {
+        let state = unsafe { &*(ptr as *const TaskState) };
+        if state.shared.status.load(Ordering::Acquire) == PENDING {
+            return XrossResult { is_ok: true, ptr: std::ptr::null_mut() };
+        }
+        // Already taken, or delivered through a completion upcall.
+        state
+            .shared
+            .result
+            .lock()
+            .unwrap()
+            .take()
+            .map(TaskOutcome::into_result)
+            .unwrap_or(XrossResult { is_ok: false, ptr: std::ptr::null_mut() })
+    }
+
+    unsafe extern "C" fn status_task(ptr: *mut c_void) -> XrossTaskStatus {
+        let state = unsafe { &*(ptr as *const TaskState) };
+        match state.shared.status.load(Ordering::Acquire) {
+            COMPLETED => XrossTaskStatus::Completed,
+            PANICKED => XrossTaskStatus::Panicked,
+            CANCELLED => XrossTaskStatus::Cancelled,
+            _ => XrossTaskStatus::Pending,
+        }
+    }
+
+    unsafe extern "C" fn cancel_task(ptr: *mut c_void) {
+        let state = unsafe { &*(ptr as *const TaskState) };
+        state.abort.abort();
+    }
+
+    /// Aborts the task and releases the handle. An outcome that was not polled is released
+    /// with the shared state, as soon as neither the handle nor the running task holds it.
+    unsafe extern "C" fn drop_task(ptr: *mut c_void) {
+        let state = unsafe { Box::from_raw(ptr as *mut TaskState) };
+        state.abort.abort();
+    }
+
+    let state = TaskState { shared, abort };
+    XrossTask {
+        task_ptr: Box::into_raw(Box::new(state)) as *mut c_void,
+        poll_fn: poll_task,
+        drop_fn: drop_task,
+        cancel_fn: cancel_task,
+        status_fn: status_task,
+    }
+}
+
+#[cfg(all(test, any(feature = "tokio", feature = "thread-pool")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropping_a_completed_task_drops_its_unpolled_value() {
+        use std::sync::atomic::AtomicUsize;
+        use std::time::{Duration, Instant};
+
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+        struct Value;
+        impl Drop for Value {
+            fn drop(&mut self) {
+                DROPPED.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let task =
+            xross_spawn_task(async { Value }, |_| unreachable!("an unpolled value is not mapped"));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while unsafe { (task.status_fn)(task.task_ptr) } == XrossTaskStatus::Pending {
+            assert!(Instant::now() < deadline, "the task did not complete");
+            std::thread::yield_now();
+        }
+        assert_eq!(unsafe { (task.status_fn)(task.task_ptr) }, XrossTaskStatus::Completed);
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 0);
+
+        unsafe { (task.drop_fn)(task.task_ptr) };
+        while DROPPED.load(Ordering::SeqCst) < 1 {
+            assert!(Instant::now() < deadline, "the unpolled value was leaked");
+            std::thread::yield_now();
+        }
+    }
+}
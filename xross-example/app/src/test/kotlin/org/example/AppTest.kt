@@ -108,6 +108,38 @@ class AppTest {
         assertEquals(20, GlobalMultiply.globalMultiply(4, 5))
     }
 
+    @Test
+    fun testReturnedCollections() {
+        val counts = WordCounts.wordCounts("a b a c a")
+        assertEquals(3, counts.size)
+        assertEquals(3, counts["a"])
+        assertNull(counts["z"])
+        assertTrue("b" in counts)
+        assertFalse(counts.containsKey("z"))
+        assertEquals(mapOf("a" to 3, "b" to 1, "c" to 1), counts)
+        (counts as AutoCloseable).close()
+        assertFailsWith<IllegalStateException> { counts.size }
+
+        val recent = RecentValues.recentValues(5, 3L)
+        assertEquals(listOf(3, 4, 5), recent.toList())
+        assertEquals(4, recent[1])
+        assertFailsWith<IndexOutOfBoundsException> { recent[3] }
+    }
+
+    @Test
+    fun testVecRoundTrips() {
+        assertEquals(listOf("日本語", "café", "a"), UnknownStruct.reversedNames(listOf("a", "café", "日本語")))
+        assertEquals(emptyList(), UnknownStruct.reversedNames(emptyList()))
+
+        val values = listOf(UnknownStruct(1, "one", 1.0f), UnknownStruct(2, "two", 2.0f))
+        val shifted = UnknownStruct.shifted(values, 10)
+        assertEquals(listOf(11, 12), shifted.map { it.i })
+        assertEquals(listOf("one", "two"), shifted.map { it.s })
+        // The arguments were moved into Rust
+        assertTrue(values.none { it.isValid })
+        shifted.forEach { it.close() }
+    }
+
     @Test
     fun testPanicHandling() {
         val service = MyService()
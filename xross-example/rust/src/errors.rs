@@ -0,0 +1,44 @@
+use std::fmt;
+use xross_core::{XrossError, xross_function};
+
+/// Failures of the example key-value store.
+#[derive(Debug, XrossError)]
+#[xross_package("errors")]
+pub enum StorageError {
+    /// No value is stored under the key.
+    #[error("key not found: {key}")]
+    NotFound { key: String },
+    /// The backing store could not be read.
+    #[error("storage unavailable")]
+    Unavailable {
+        #[source]
+        cause: std::io::Error,
+    },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::NotFound { key } => write!(f, "key not found: {}", key),
+            StorageError::Unavailable { .. } => write!(f, "storage unavailable"),
+        }
+    }
+}
+
+impl std::error::Error for StorageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StorageError::NotFound { .. } => None,
+            StorageError::Unavailable { cause } => Some(cause),
+        }
+    }
+}
+
+#[xross_function(package = "errors")]
+pub fn lookup(key: String) -> Result<i32, StorageError> {
+    match key.as_str() {
+        "answer" => Ok(42),
+        "disk" => Err(StorageError::Unavailable { cause: std::io::Error::other("disk offline") }),
+        _ => Err(StorageError::NotFound { key }),
+    }
+}
@@ -170,9 +170,9 @@ pub enum XrossFillRule {
     NonZero,
 }
 
-#[xross_methods]
+#[xross_methods(panicable)]
 impl Path2D {
-    #[xross_new(panicable)]
+    #[xross_new]
     pub fn new() -> Self {
         Self::default()
     }
@@ -198,7 +198,7 @@ impl Path2D {
         self.pen.is_gradient_enabled = enable_gradient;
     }
 
-    #[xross_method(panicable)]
+    #[xross_method]
     pub fn move_to(&mut self, x: f64, y: f64) {
         self.segments.push(SegmentData::default());
         let point = self.point(x, y);
@@ -217,7 +217,7 @@ impl Path2D {
         }
     }
 
-    #[xross_method(panicable)]
+    #[xross_method]
     pub fn line_to(&mut self, x: f64, y: f64) {
         let needs_new_segment = self.segments.last().map(|s| s.is_closed).unwrap_or(false);
 
@@ -275,7 +275,7 @@ impl Path2D {
         self.push_point(p);
     }
 
-    #[xross_method(panicable)]
+    #[xross_method]
     pub fn bezier_curve_to(&mut self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
         let last = self.last_point();
         let start_pos = last.map(|p| (p.x, p.y)).unwrap_or((0.0, 0.0));
@@ -300,7 +300,7 @@ impl Path2D {
         }
     }
 
-    #[xross_method(panicable)]
+    #[xross_method]
     pub fn quadratic_curve_to(&mut self, cpx: f64, cpy: f64, x: f64, y: f64) {
         let last = self.last_point();
         let start = last.map(|p| (p.x, p.y)).unwrap_or((0.0, 0.0));
@@ -319,7 +319,7 @@ impl Path2D {
         }
     }
 
-    #[xross_method(panicable)]
+    #[xross_method]
     pub fn arc(
         &mut self,
         x: f64,
@@ -379,7 +379,7 @@ impl Path2D {
         }
     }
 
-    #[xross_method(panicable)]
+    #[xross_method]
     pub fn arc_to(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, radius: f64) {
         let start = self.last_point().map(|p| (p.x, p.y)).unwrap_or((x1, y1));
 
@@ -415,7 +415,7 @@ impl Path2D {
     }
 
     #[allow(clippy::too_many_arguments)]
-    #[xross_method(panicable)]
+    #[xross_method]
     pub fn ellipse(
         &mut self,
         x: f64,
@@ -459,7 +459,7 @@ impl Path2D {
         }
     }
 
-    #[xross_method(panicable)]
+    #[xross_method]
     pub fn tessellate_fill(&mut self, rule: XrossFillRule) {
         self.buffer.clear();
 
@@ -499,7 +499,7 @@ impl Path2D {
         let _ = tessellator.tessellate_path(&path, &options, &mut output);
     }
 
-    #[xross_method(panicable)]
+    #[xross_method]
     pub fn tessellate_stroke(&mut self) {
         let mut output_buffer = Vec::new();
         let cap = self.pen.line_cap.into();
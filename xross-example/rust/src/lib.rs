@@ -4,6 +4,7 @@
 pub mod all_types;
 pub mod counters;
 pub mod enums;
+pub mod errors;
 pub mod fast;
 pub mod graphics;
 pub mod heavy;
@@ -14,6 +15,7 @@ pub mod standalone;
 pub use all_types::*;
 pub use counters::*;
 pub use enums::*;
+pub use errors::*;
 pub use fast::*;
 pub use graphics::*;
 pub use heavy::*;
@@ -24,8 +26,14 @@ pub use standalone::*;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::counters::{SERVICE_COUNT, SERVICE2_COUNT};
-    use std::sync::atomic::Ordering;
+    use std::sync::{Mutex, MutexGuard};
+    use xross_core::xross_object_count;
+
+    /// Serialises the tests that assert exact `live-objects` counts of the services.
+    fn lock_counts() -> MutexGuard<'static, ()> {
+        static COUNTS: Mutex<()> = Mutex::new(());
+        COUNTS.lock().unwrap_or_else(|e| e.into_inner())
+    }
 
     #[test]
     fn test_hello_enum_logic() {
@@ -54,9 +62,9 @@ mod tests {
 
     #[test]
     fn test_service_lifecycle_and_core_methods() {
-        let before = SERVICE_COUNT.load(Ordering::SeqCst);
+        let _counts = lock_counts();
+        let before = xross_object_count::<MyService>();
         let mut service = MyService::new();
-        assert!(SERVICE_COUNT.load(Ordering::SeqCst) >= before);
 
         assert_eq!(service.add_trivial(10, 15), 25);
         assert_eq!(service.add_critical_heap(7, 8), 15);
@@ -68,33 +76,454 @@ mod tests {
 
         let consumed_len = service.consume_self();
         assert_eq!(consumed_len, 1_000_000);
+        // Values that never cross the FFI boundary are not counted
+        assert_eq!(xross_object_count::<MyService>(), before);
 
-        // Other tests can run in parallel and mutate the same global counter,
-        // so we only assert it stays in a valid (non-negative) range here.
-        assert!(SERVICE_COUNT.load(Ordering::SeqCst) >= 0);
+        let handle = unsafe { xross_example_my_service_new() };
+        let during = xross_object_count::<MyService>();
+        assert_eq!(during.live, before.live + 1);
+        assert_eq!(during.total, before.total + 1);
+        unsafe { xross_example_my_service_drop(handle as *mut MyService) };
+        assert_eq!(xross_object_count::<MyService>().live, before.live);
     }
 
     #[test]
     fn test_async_and_service2_behaviour() {
-        let before = SERVICE2_COUNT.load(Ordering::SeqCst);
-
-        let service = MyService::new();
-        let value = RUNTIME.block_on(service.async_execute(21));
+        let _counts = lock_counts();
+        let before = (xross_object_count::<MyService>(), xross_object_count::<MyService2>());
+        let service = MyService::new_shared();
+        let value = RUNTIME.block_on(service.clone().async_execute(21));
+        assert_eq!(std::sync::Arc::strong_count(&service), 1);
         assert_eq!(value, 42);
 
         let s2 = MyService2::new(5);
-        assert!(SERVICE2_COUNT.load(Ordering::SeqCst) >= before);
-
         let cloned = s2.create_clone();
-        assert!(SERVICE2_COUNT.load(Ordering::SeqCst) >= before);
         assert_eq!(s2.get_self_ref().val, 5);
 
         let out = s2.execute();
         assert!((-5.0..=5.0).contains(&out));
 
-        drop(cloned);
-        drop(s2);
-        assert!(SERVICE2_COUNT.load(Ordering::SeqCst) >= 0);
+        assert_eq!(cloned.val, 5);
+        // Neither the shared service nor the clones were handed out through an export
+        assert_eq!((xross_object_count::<MyService>(), xross_object_count::<MyService2>()), before);
+    }
+
+    fn wait_task(task: xross_core::XrossTask) -> xross_core::XrossResult {
+        wait_task_status(task).1
+    }
+
+    fn wait_task_status(
+        task: xross_core::XrossTask,
+    ) -> (xross_core::XrossTaskStatus, xross_core::XrossResult) {
+        loop {
+            let status = unsafe { (task.status_fn)(task.task_ptr) };
+            if status != xross_core::XrossTaskStatus::Pending {
+                let res = unsafe { (task.poll_fn)(task.task_ptr) };
+                unsafe { (task.drop_fn)(task.task_ptr) };
+                return (status, res);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+    }
+
+    /// Reads and frees the `XrossError` of a failed result, returning its handle `value`.
+    fn take_error(
+        res: xross_core::XrossResult,
+    ) -> (xross_core::XrossErrorKind, String, Vec<String>, *mut std::ffi::c_void) {
+        assert!(!res.is_ok);
+        let err = res.ptr as *mut xross_core::XrossError;
+        let read = |s: &xross_core::XrossString| unsafe {
+            String::from_utf8_lossy(std::slice::from_raw_parts(s.ptr, s.len)).into_owned()
+        };
+        let err_ref = unsafe { &*err };
+        let causes = unsafe {
+            std::slice::from_raw_parts(
+                err_ref.causes.ptr as *const xross_core::XrossString,
+                err_ref.causes.len,
+            )
+        };
+        let (kind, message, value) = (err_ref.kind, read(&err_ref.message), err_ref.value);
+        let causes = causes.iter().map(read).collect();
+        unsafe { xross_core::xross_free_error(err) };
+        (kind, message, causes, value)
+    }
+
+    #[test]
+    fn test_async_result_propagates_err() {
+        let mut task = std::mem::MaybeUninit::<xross_core::XrossTask>::uninit();
+
+        unsafe { xross_example_standalone_async_checked_div(task.as_mut_ptr(), 84, 2) };
+        let ok = wait_task(unsafe { task.assume_init_read() });
+        assert!(ok.is_ok);
+        assert_eq!(ok.ptr as usize, 42);
+
+        unsafe { xross_example_standalone_async_checked_div(task.as_mut_ptr(), 1, 0) };
+        let err = wait_task(unsafe { task.assume_init_read() });
+        let (kind, msg, _, value) = take_error(err);
+        assert_eq!(kind, xross_core::XrossErrorKind::Err);
+        assert_eq!(msg, "division by zero");
+        assert!(value.is_null());
+    }
+
+    #[test]
+    fn test_async_option_none_is_not_pending() {
+        let take = |res: xross_core::XrossResult| {
+            assert!(res.is_ok && !res.ptr.is_null());
+            let opt = res.ptr as *mut xross_core::XrossOption<*mut std::ffi::c_void>;
+            let value = unsafe { (*opt).into_option() };
+            unsafe { xross_core::xross_free_option(opt) };
+            value.map(|ptr| {
+                let xs = unsafe { Box::from_raw(ptr as *mut xross_core::XrossString) };
+                unsafe { xs.into_string() }
+            })
+        };
+        let mut task = std::mem::MaybeUninit::<xross_core::XrossTask>::uninit();
+
+        unsafe { xross_example_standalone_async_lookup(task.as_mut_ptr(), 7) };
+        let (status, res) = wait_task_status(unsafe { task.assume_init_read() });
+        assert_eq!(status, xross_core::XrossTaskStatus::Completed);
+        assert_eq!(take(res).as_deref(), Some("user-7"));
+
+        unsafe { xross_example_standalone_async_lookup(task.as_mut_ptr(), 0) };
+        let (status, res) = wait_task_status(unsafe { task.assume_init_read() });
+        assert_eq!(status, xross_core::XrossTaskStatus::Completed);
+        assert_eq!(take(res), None);
+    }
+
+    #[test]
+    fn test_cancelled_task_reports_cancellation() {
+        let mut task = std::mem::MaybeUninit::<xross_core::XrossTask>::uninit();
+        unsafe { xross_example_standalone_async_add(task.as_mut_ptr(), 1, 2) };
+        let task = unsafe { task.assume_init() };
+
+        unsafe { (task.cancel_fn)(task.task_ptr) };
+        let (status, res) = wait_task_status(task);
+        assert_eq!(status, xross_core::XrossTaskStatus::Cancelled);
+        assert!(!res.is_ok);
+        assert!(res.ptr.is_null());
+    }
+
+    fn completion_channel() -> (xross_core::XrossCallback, std::sync::mpsc::Receiver<(bool, usize)>)
+    {
+        unsafe extern "C" fn on_complete(ctx: *mut std::ffi::c_void, res: xross_core::XrossResult) {
+            let tx = unsafe { &*(ctx as *const std::sync::mpsc::Sender<(bool, usize)>) };
+            tx.send((res.is_ok, res.ptr as usize)).unwrap();
+        }
+        unsafe extern "C" fn release(ctx: *mut std::ffi::c_void) {
+            drop(unsafe { Box::from_raw(ctx as *mut std::sync::mpsc::Sender<(bool, usize)>) });
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let callback = xross_core::XrossCallback {
+            func: on_complete as *const std::ffi::c_void,
+            ctx: Box::into_raw(Box::new(tx)) as *mut std::ffi::c_void,
+            release: Some(release),
+        };
+        (callback, rx)
+    }
+
+    #[test]
+    fn test_notify_task_invokes_completion_once() {
+        let mut task = std::mem::MaybeUninit::<xross_core::XrossTask>::uninit();
+
+        let (callback, rx) = completion_channel();
+        unsafe {
+            xross_example_standalone_async_checked_div_notify(task.as_mut_ptr(), callback, 84, 2)
+        };
+        assert_eq!(rx.recv().unwrap(), (true, 42));
+        // The sender is released right after the single invocation.
+        assert!(rx.recv().is_err());
+        let done = unsafe { task.assume_init_read() };
+        unsafe { (done.drop_fn)(done.task_ptr) };
+
+        let (callback, rx) = completion_channel();
+        unsafe { xross_example_standalone_async_add_notify(task.as_mut_ptr(), callback, 1, 2) };
+        let task = unsafe { task.assume_init() };
+        unsafe { (task.cancel_fn)(task.task_ptr) };
+        assert_eq!(rx.recv().unwrap(), (false, 0));
+        assert!(rx.recv().is_err());
+        unsafe { (task.drop_fn)(task.task_ptr) };
+    }
+
+    #[test]
+    fn test_async_panic_reports_message() {
+        let mut task = std::mem::MaybeUninit::<xross_core::XrossTask>::uninit();
+
+        unsafe { xross_example_standalone_async_sqrt(task.as_mut_ptr(), -1.0) };
+        let (status, res) = wait_task_status(unsafe { task.assume_init_read() });
+        assert_eq!(status, xross_core::XrossTaskStatus::Panicked);
+        let (kind, msg, _, _) = take_error(res);
+        assert_eq!(kind, xross_core::XrossErrorKind::Panic);
+        assert_eq!(msg, "negative input: -1");
+
+        unsafe { xross_example_standalone_async_checked_div(task.as_mut_ptr(), 1, 0) };
+        let (status, res) = wait_task_status(unsafe { task.assume_init_read() });
+        assert_eq!(status, xross_core::XrossTaskStatus::Completed);
+        assert_eq!(take_error(res).0, xross_core::XrossErrorKind::Err);
+    }
+
+    #[test]
+    fn test_blocking_function_runs_as_task() {
+        let mut task = std::mem::MaybeUninit::<xross_core::XrossTask>::uninit();
+        unsafe {
+            xross_example_standalone_blocking_prime_factorization(task.as_mut_ptr(), 3 << 10)
+        };
+        let (status, res) = wait_task_status(unsafe { task.assume_init() });
+        assert_eq!(status, xross_core::XrossTaskStatus::Completed);
+        assert_eq!(res.ptr as usize, 11);
+    }
+
+    #[test]
+    fn test_shared_receiver_keeps_object_alive_during_task() {
+        let service = MyService::new_shared();
+        let handle = std::sync::Arc::as_ptr(&service) as *mut std::ffi::c_void;
+
+        let mut task = std::mem::MaybeUninit::<xross_core::XrossTask>::uninit();
+        unsafe { xross_example_my_service_async_execute(task.as_mut_ptr(), handle, 21) };
+        assert_eq!(std::sync::Arc::strong_count(&service), 2);
+
+        let (status, res) = wait_task_status(unsafe { task.assume_init() });
+        assert_eq!(status, xross_core::XrossTaskStatus::Completed);
+        assert_eq!(res.ptr as usize, 42);
+        assert_eq!(std::sync::Arc::strong_count(&service), 1);
+    }
+
+    #[test]
+    fn test_stream_yields_items_then_done() {
+        let mut stream = std::mem::MaybeUninit::<xross_core::XrossStream>::uninit();
+        unsafe { xross_example_standalone_countdown(stream.as_mut_ptr(), 3) };
+        let stream = unsafe { stream.assume_init() };
+
+        let mut items = Vec::new();
+        loop {
+            let poll = unsafe { (stream.poll_next_fn)(stream.stream_ptr) };
+            match poll.status {
+                xross_core::XrossStreamStatus::Pending => std::thread::yield_now(),
+                xross_core::XrossStreamStatus::Item => items.push(poll.value.ptr as usize),
+                status => {
+                    assert_eq!(status, xross_core::XrossStreamStatus::Done);
+                    break;
+                }
+            }
+        }
+        unsafe { (stream.drop_fn)(stream.stream_ptr) };
+        assert_eq!(items, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_iterator_is_consumed_lazily() {
+        let iter = unsafe { xross_example_standalone_squares(5) };
+
+        let mut upper = xross_core::XrossOption { is_some: false, value: 0 };
+        assert_eq!(unsafe { xross_example_standalone_squares_ret_size_hint(iter, &mut upper) }, 5);
+        assert_eq!(upper.into_option(), Some(5));
+
+        let mut first = 0u64;
+        assert!(unsafe { xross_example_standalone_squares_ret_next(iter, &mut first) });
+        assert_eq!(first, 0);
+
+        let mut chunk = [0u64; 8];
+        let written = unsafe {
+            xross_example_standalone_squares_ret_next_chunk(iter, chunk.as_mut_ptr(), chunk.len())
+        };
+        assert_eq!(&chunk[..written], &[1, 4, 9, 16]);
+        assert!(!unsafe { xross_example_standalone_squares_ret_next(iter, &mut first) });
+        unsafe { xross_example_standalone_squares_ret_drop(iter) };
+    }
+
+    #[test]
+    fn test_collection_accessors_read_returned_map() {
+        let text = "a b a c a";
+        let map = unsafe { xross_example_standalone_word_counts(text.as_ptr(), text.len(), 0) };
+        assert_eq!(unsafe { xross_example_standalone_word_counts_ret_len(map) }, 3);
+
+        let mut count = 0i32;
+        let key = "a";
+        assert!(unsafe {
+            xross_example_standalone_word_counts_ret_get(
+                map,
+                key.as_ptr(),
+                key.len(),
+                0,
+                &mut count,
+            )
+        });
+        assert_eq!(count, 3);
+        let missing = "z";
+        assert!(!unsafe {
+            xross_example_standalone_word_counts_ret_contains(
+                map,
+                missing.as_ptr(),
+                missing.len(),
+                0,
+            )
+        });
+
+        // Keys are lent as XrossStrings and values are copied, in the same order.
+        let mut keys = std::mem::MaybeUninit::<[xross_core::XrossString; 3]>::uninit();
+        let mut values = [0i32; 3];
+        let written = unsafe {
+            xross_example_standalone_word_counts_ret_snapshot(map, keys.as_mut_ptr().cast(), 3)
+        };
+        assert_eq!(written, 3);
+        assert_eq!(
+            unsafe { xross_example_standalone_word_counts_ret_values(map, values.as_mut_ptr(), 3) },
+            3
+        );
+        let keys = unsafe { keys.assume_init() };
+        let mut entries: Vec<(String, i32)> = keys
+            .iter()
+            .zip(values)
+            .map(|(k, v)| {
+                let bytes = unsafe { std::slice::from_raw_parts(k.ptr, k.len) };
+                (String::from_utf8(bytes.to_vec()).unwrap(), v)
+            })
+            .collect();
+        entries.sort();
+        assert_eq!(entries, [("a".to_string(), 3), ("b".to_string(), 1), ("c".to_string(), 1)]);
+        unsafe { xross_example_standalone_word_counts_ret_drop(map) };
+    }
+
+    #[test]
+    fn test_collection_accessors_index_returned_deque() {
+        let deque = unsafe { xross_example_standalone_recent_values(5, 3) };
+        assert_eq!(unsafe { xross_example_standalone_recent_values_ret_len(deque) }, 3);
+
+        let mut value = 0i32;
+        assert!(unsafe { xross_example_standalone_recent_values_ret_get(deque, 0, &mut value) });
+        assert_eq!(value, 3);
+        assert!(!unsafe { xross_example_standalone_recent_values_ret_get(deque, 3, &mut value) });
+        unsafe { xross_example_standalone_recent_values_ret_drop(deque) };
+    }
+
+    #[test]
+    fn test_vec_of_strings_round_trips_as_views_and_strings() {
+        // Latin1 and UTF-16LE views, as the JVM passes them
+        let latin1 = [0x63, 0x61, 0x66, 0xE9];
+        let utf16 = [0x42, 0x00, 0x3D, 0xD8, 0x00, 0xDE];
+        let views = [
+            xross_core::XrossStringView { ptr: latin1.as_ptr(), len: latin1.len(), encoding: 0 },
+            xross_core::XrossStringView { ptr: utf16.as_ptr(), len: utf16.len(), encoding: 1 },
+        ];
+        let mut out = std::mem::MaybeUninit::<xross_core::XrossArray>::uninit();
+        unsafe { xross_example_unknown_struct_reversed_names(out.as_mut_ptr(), views.as_ptr(), 2) };
+        let array = unsafe { out.assume_init() };
+        assert_eq!(array.elem_size, std::mem::size_of::<xross_core::XrossString>());
+        let names: Vec<String> = unsafe { array.into_vec::<xross_core::XrossString>() }
+            .into_iter()
+            .map(|s| unsafe { s.into_string() })
+            .collect();
+        assert_eq!(names, ["B😀", "café"]);
+    }
+
+    #[test]
+    fn test_vec_of_objects_moves_handles_in_and_out() {
+        let handles: Vec<*mut std::ffi::c_void> = [1, 2]
+            .iter()
+            .map(|&i| unsafe { xross_example_unknown_struct_new(i, "x".as_ptr(), 1, 0, 0.5) })
+            .collect();
+        let mut out = std::mem::MaybeUninit::<xross_core::XrossArray>::uninit();
+        unsafe { xross_example_unknown_struct_shifted(out.as_mut_ptr(), handles.as_ptr(), 2, 10) };
+        let array = unsafe { out.assume_init() };
+
+        // The arguments were moved out of their handles; each element is a new owned handle.
+        let shifted = unsafe { array.into_vec::<*mut std::ffi::c_void>() };
+        let values: Vec<i32> = shifted
+            .into_iter()
+            .map(|handle| {
+                let value = unsafe { (*(handle as *const UnknownStruct)).i };
+                unsafe { xross_example_unknown_struct_drop(handle as *mut UnknownStruct) };
+                value
+            })
+            .collect();
+        assert_eq!(values, [11, 12]);
+    }
+
+    #[test]
+    fn test_error_handles_describe_variant_and_source_chain() {
+        let describe = |key: &str| {
+            let res = unsafe { xross_example_errors_lookup(key.as_ptr(), key.len(), 0) };
+            let (kind, _, causes, value) = take_error(res);
+            assert_eq!(kind, xross_core::XrossErrorKind::Err);
+            let mut info = std::mem::MaybeUninit::<xross_core::XrossErrorInfo>::uninit();
+            let err = value as *mut StorageError;
+            assert!(unsafe { xross_example_errors_storage_error_describe(info.as_mut_ptr(), err) });
+            unsafe { xross_example_errors_storage_error_drop(err) };
+            let info = unsafe { info.assume_init() };
+            let sources: Vec<String> =
+                unsafe { info.sources.into_vec::<xross_core::XrossString>() }
+                    .into_iter()
+                    .map(|s| unsafe { s.into_string() })
+                    .collect();
+            assert_eq!(sources, causes);
+            (info.variant, unsafe { info.message.into_string() }, sources)
+        };
+
+        let mut info = std::mem::MaybeUninit::<xross_core::XrossErrorInfo>::uninit();
+        assert!(!unsafe {
+            xross_example_errors_storage_error_describe(info.as_mut_ptr(), std::ptr::null())
+        });
+        assert_eq!(describe("nope"), (0, "key not found: nope".to_string(), vec![]));
+        assert_eq!(
+            describe("disk"),
+            (1, "storage unavailable".to_string(), vec!["disk offline".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_panicable_method_rejects_null_handle() {
+        let _counts = lock_counts();
+        let mut out = std::mem::MaybeUninit::<xross_core::XrossResult>::uninit();
+        unsafe { xross_example_my_service_cause_panic(out.as_mut_ptr(), std::ptr::null_mut(), 0) };
+        let (kind, msg, _, _) = take_error(unsafe { out.assume_init_read() });
+        assert_eq!(kind, xross_core::XrossErrorKind::NullHandle);
+        assert!(msg.contains("null handle"));
+
+        let service = unsafe { xross_example_my_service_new() };
+        unsafe { xross_example_my_service_cause_panic(out.as_mut_ptr(), service, 1) };
+        let (kind, msg, _, _) = take_error(unsafe { out.assume_init_read() });
+        assert_eq!(kind, xross_core::XrossErrorKind::Panic);
+        assert_eq!(msg, "Intentional panic from Rust!");
+        unsafe { xross_example_my_service_drop(service as *mut MyService) };
+    }
+
+    #[test]
+    fn test_live_objects_count_handles_until_dropped() {
+        let _counts = lock_counts();
+        let before = xross_object_count::<MyService2>();
+
+        let service = unsafe { xross_example_test_test2_my_service2_new(3) };
+        let clone = unsafe { xross_example_test_test2_my_service2_clone(service as *const _) };
+        let during = xross_object_count::<MyService2>();
+        assert_eq!(during.live, before.live + 2);
+        assert_eq!(during.total, before.total + 2);
+        assert!(during.peak >= 2);
+
+        let snapshot = xross_core::xross_live_objects();
+        let entries = unsafe {
+            std::slice::from_raw_parts(
+                snapshot.ptr as *const xross_core::XrossLiveObjects,
+                snapshot.len,
+            )
+        };
+        let signatures: Vec<String> = entries
+            .iter()
+            .map(|e| unsafe {
+                String::from_utf8_lossy(std::slice::from_raw_parts(
+                    e.signature.ptr,
+                    e.signature.len,
+                ))
+                .into_owned()
+            })
+            .collect();
+        assert!(signatures.contains(&"test.test2.MyService2".to_string()));
+        unsafe { xross_core::xross_free_live_objects(snapshot) };
+
+        unsafe { xross_example_test_test2_my_service2_drop(clone) };
+        unsafe { xross_example_test_test2_my_service2_drop(service as *mut MyService2) };
+        let after = xross_object_count::<MyService2>();
+        assert_eq!(after.live, before.live);
+        assert_eq!(after.total, before.total + 2);
     }
 
     #[test]
@@ -1,8 +1,7 @@
-use crate::counters::{SERVICE_COUNT, SERVICE2_COUNT, UNKNOWN_STRUCT_COUNT};
-use std::sync::atomic::Ordering;
-use xross_core::{XrossClass, xross_class, xross_methods};
+use crate::services::{MyService, MyService2};
+use xross_core::{XrossClass, xross_class, xross_methods, xross_object_count};
 
-#[derive(XrossClass)]
+#[derive(XrossClass, Clone)]
 #[xross(clonable)]
 #[repr(C)]
 pub struct UnknownStruct {
@@ -14,23 +13,8 @@ pub struct UnknownStruct {
     pub s: String,
 }
 
-// Clone時にもカウントを増やす
-impl Clone for UnknownStruct {
-    fn clone(&self) -> Self {
-        UNKNOWN_STRUCT_COUNT.fetch_add(1, Ordering::SeqCst);
-        Self { i: self.i, f: self.f, s: self.s.clone() }
-    }
-}
-
-impl Drop for UnknownStruct {
-    fn drop(&mut self) {
-        UNKNOWN_STRUCT_COUNT.fetch_sub(1, Ordering::SeqCst);
-    }
-}
-
 impl Default for UnknownStruct {
     fn default() -> Self {
-        UNKNOWN_STRUCT_COUNT.fetch_add(1, Ordering::SeqCst);
         Self { i: 32, f: 64.0, s: "Hello, World!".to_string() }
     }
 }
@@ -45,21 +29,24 @@ impl UnknownStruct {
 
     #[xross_new]
     pub fn new(i: i32, s: String, f: f32) -> Self {
-        UNKNOWN_STRUCT_COUNT.fetch_add(1, Ordering::SeqCst);
         Self { i, s, f }
     }
 
     #[xross_new]
     pub fn with_int(i: i32) -> Self {
-        UNKNOWN_STRUCT_COUNT.fetch_add(1, Ordering::SeqCst);
         Self { i, s: "From Int".to_string(), f: 0.0 }
     }
 
+    /// Reports the handles held by the JVM, as counted by `xross-core`'s `live-objects`.
+    ///
+    /// Unlike the `Drop` counters this used to read, values created and dropped within
+    /// Rust (temporaries, clones made inside a method) are not included, so the numbers
+    /// only return to zero once the JVM has dropped every handle it was given.
     #[xross_method]
     pub fn display_analysis() -> String {
-        let s1 = SERVICE_COUNT.load(Ordering::SeqCst);
-        let s2 = SERVICE2_COUNT.load(Ordering::SeqCst);
-        let u = UNKNOWN_STRUCT_COUNT.load(Ordering::SeqCst);
+        let s1 = xross_object_count::<MyService>().live;
+        let s2 = xross_object_count::<MyService2>().live;
+        let u = xross_object_count::<UnknownStruct>().live;
         format!(
             "--- Xross Native Analysis ---
 
@@ -78,6 +65,17 @@ impl UnknownStruct {
             s1 + s2 + u
         )
     }
+
+    #[xross_method]
+    pub fn reversed_names(names: Vec<String>) -> Vec<String> {
+        names.into_iter().rev().collect()
+    }
+
+    /// Takes the values over and returns them with `by` added to `i`.
+    #[xross_method]
+    pub fn shifted(values: Vec<UnknownStruct>, by: i32) -> Vec<UnknownStruct> {
+        values.into_iter().map(|v| Self { i: v.i + by, ..v }).collect()
+    }
 }
 
 pub struct ComplexStruct {
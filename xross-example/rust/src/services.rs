@@ -1,12 +1,11 @@
-use crate::counters::{SERVICE_COUNT, SERVICE2_COUNT};
 use crate::enums::{XrossSimpleEnum, XrossTestEnum};
 use crate::models::UnknownStruct;
 use std::cmp::{max, min};
-use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use xross_core::{XrossClass, xross_methods};
 
-#[derive(XrossClass)]
-#[xross(clonable)]
+#[derive(XrossClass, Clone)]
+#[xross(clonable, shared)]
 #[repr(C)]
 pub struct MyService {
     _boxes: Vec<i32>,
@@ -14,19 +13,6 @@ pub struct MyService {
     pub unknown_struct: Box<UnknownStruct>,
 }
 
-impl Clone for MyService {
-    fn clone(&self) -> Self {
-        SERVICE_COUNT.fetch_add(1, Ordering::SeqCst);
-        Self { _boxes: self._boxes.clone(), unknown_struct: self.unknown_struct.clone() }
-    }
-}
-
-impl Drop for MyService {
-    fn drop(&mut self) {
-        SERVICE_COUNT.fetch_sub(1, Ordering::SeqCst);
-    }
-}
-
 impl Default for MyService {
     fn default() -> Self {
         Self::new()
@@ -37,12 +23,17 @@ impl Default for MyService {
 impl MyService {
     #[xross_new]
     pub fn new() -> Self {
-        SERVICE_COUNT.fetch_add(1, Ordering::SeqCst);
         MyService { _boxes: vec![0; 1_000_000], unknown_struct: Box::new(UnknownStruct::default()) }
     }
 
+    /// Creates a shared service, needed to call async methods.
+    #[xross_method]
+    pub fn new_shared() -> Arc<Self> {
+        Arc::new(Self::new())
+    }
+
     #[xross_method]
-    pub async fn async_execute(&self, val: i32) -> i32 {
+    pub async fn async_execute(self: Arc<Self>, val: i32) -> i32 {
         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
         val * 2
     }
@@ -98,7 +89,7 @@ impl MyService {
     }
 }
 
-#[derive(XrossClass)]
+#[derive(XrossClass, Clone)]
 #[xross_package("test.test2")]
 #[xross(clonable)]
 #[repr(C)]
@@ -107,24 +98,10 @@ pub struct MyService2 {
     pub val: i32,
 }
 
-impl Clone for MyService2 {
-    fn clone(&self) -> Self {
-        SERVICE2_COUNT.fetch_add(1, Ordering::SeqCst);
-        Self { val: self.val }
-    }
-}
-
-impl Drop for MyService2 {
-    fn drop(&mut self) {
-        SERVICE2_COUNT.fetch_sub(1, Ordering::SeqCst);
-    }
-}
-
 #[xross_methods]
 impl MyService2 {
     #[xross_new]
     pub fn new(val: i32) -> Self {
-        SERVICE2_COUNT.fetch_add(1, Ordering::SeqCst);
         MyService2 { val }
     }
 
@@ -1,4 +1,5 @@
-use xross_core::{xross_function, xross_function_dsl};
+use std::collections::{HashMap, VecDeque};
+use xross_core::{Stream, xross_function, xross_function_dsl};
 
 #[xross_function(package = "standalone")]
 pub async fn async_add(a: i32, b: i32) -> i32 {
@@ -12,6 +13,64 @@ pub async fn async_greet(name: String) -> String {
     format!("Async Hello, {}!", name)
 }
 
+#[xross_function(package = "standalone")]
+pub async fn async_checked_div(a: i32, b: i32) -> Result<i32, String> {
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    if b == 0 { Err("division by zero".to_string()) } else { Ok(a / b) }
+}
+
+#[xross_function(package = "standalone")]
+pub async fn async_lookup(id: i32) -> Option<String> {
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    (id > 0).then(|| format!("user-{}", id))
+}
+
+#[xross_function(package = "standalone")]
+pub fn countdown(from: i32) -> impl Stream<Item = i32> + Send + 'static {
+    futures::stream::unfold(from, |n| async move {
+        if n <= 0 {
+            return None;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        Some((n, n - 1))
+    })
+}
+
+#[xross_function(package = "standalone")]
+pub fn squares(count: u64) -> impl Iterator<Item = u64> {
+    (0..count).map(|n| n * n)
+}
+
+#[xross_function(package = "standalone")]
+pub fn word_counts(text: String) -> HashMap<String, i32> {
+    let mut counts = HashMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[xross_function(package = "standalone")]
+pub fn recent_values(count: i32, keep: usize) -> VecDeque<i32> {
+    let mut recent = VecDeque::with_capacity(keep);
+    for value in 1..=count {
+        recent.push_back(value);
+        if recent.len() > keep {
+            recent.pop_front();
+        }
+    }
+    recent
+}
+
+#[xross_function(package = "standalone", panicable)]
+pub async fn async_sqrt(value: f64) -> f64 {
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    if value < 0.0 {
+        panic!("negative input: {}", value);
+    }
+    value.sqrt()
+}
+
 #[xross_function(package = "standalone", critical)]
 pub fn global_add(a: i32, b: i32) -> i32 {
     a + b
@@ -67,6 +126,11 @@ pub fn heavy_prime_factorization(mut n: u64) -> u32 {
     count
 }
 
+#[xross_function(package = "standalone", blocking)]
+pub fn blocking_prime_factorization(n: u64) -> u32 {
+    heavy_prime_factorization(n)
+}
+
 #[xross_function(package = "standalone")]
 pub fn heavy_matrix_multiplication(size: usize) -> f64 {
     let a = vec![1.1f64; size * size];
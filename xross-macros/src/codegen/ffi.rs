@@ -1,11 +1,19 @@
+pub mod callback;
+pub mod collection;
 pub mod common;
 pub mod conversion;
+pub mod handles;
+pub mod iterator;
 pub mod layout;
 pub mod methods;
 pub mod property;
 
+pub use callback::*;
+pub use collection::*;
 pub use common::*;
 pub use conversion::*;
+pub use handles::*;
+pub use iterator::*;
 pub use layout::*;
 pub use methods::*;
 pub use property::*;
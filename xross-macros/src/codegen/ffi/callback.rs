@@ -0,0 +1,95 @@
+use crate::codegen::ffi::primitive_rust_type;
+use crate::types::mapping::find_fn_bound;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Type;
+use xross_metadata::XrossType;
+
+/// Returns the FFI return type of a JVM upcall. Only `()` and primitives are supported.
+pub fn upcall_ret_type(ret: &XrossType) -> Option<TokenStream> {
+    match ret {
+        XrossType::Void => Some(quote! { () }),
+        _ => primitive_rust_type(ret),
+    }
+}
+
+/// Generates the FFI type and conversion of a value Rust passes to a JVM upcall.
+pub fn gen_upcall_arg(
+    ty: &XrossType,
+    rust_ty: &Type,
+    val: &syn::Ident,
+) -> (TokenStream, TokenStream) {
+    if let Some(prim) = primitive_rust_type(ty) {
+        return (prim, quote! { #val });
+    }
+    match ty {
+        // Strings are lent to the JVM for the duration of the call.
+        XrossType::String => (
+            quote! { xross_core::XrossString },
+            quote! {
+                {
+                    let s: &str = &*#val;
+                    xross_core::XrossString { ptr: s.as_ptr() as *mut u8, len: s.len(), cap: s.len() }
+                }
+            },
+        ),
+        XrossType::Object { .. } if matches!(rust_ty, Type::Reference(_)) => {
+            (quote! { *mut std::ffi::c_void }, quote! { #val as *const _ as *mut std::ffi::c_void })
+        }
+        XrossType::Object { .. } => (
+            quote! { *mut std::ffi::c_void },
+            crate::codegen::ffi::gen_track_handle(quote! { Box::into_raw(Box::new(#val)) }),
+        ),
+        _ => panic!("Unsupported callback argument type: {}", quote!(#rust_ty)),
+    }
+}
+
+/// Generates argument conversion for a closure parameter backed by an `XrossCallback`.
+/// The closure owns the callback, so the JVM side is released when Rust drops it.
+pub fn gen_callback_arg(
+    arg_ty: &Type,
+    arg_id: &syn::Ident,
+    args: &[XrossType],
+    ret: &XrossType,
+) -> (TokenStream, TokenStream, TokenStream) {
+    let bound = find_fn_bound(arg_ty)
+        .unwrap_or_else(|| panic!("Callback arg must be a closure type: {}", quote!(#arg_ty)));
+
+    let params: Vec<_> = (0..args.len()).map(|i| format_ident!("a{}", i)).collect();
+    let (ffi_tys, ffi_vals): (Vec<_>, Vec<_>) = args
+        .iter()
+        .zip(bound.inputs.iter())
+        .zip(&params)
+        .map(|((ty, rust_ty), param)| gen_upcall_arg(ty, rust_ty, param))
+        .unzip();
+    let rust_tys = bound.inputs.iter();
+
+    let ffi_ret = upcall_ret_type(ret)
+        .unwrap_or_else(|| panic!("Unsupported callback return type: {}", quote!(#arg_ty)));
+
+    // Panicable exports reject a null `func` before converting; elsewhere it is a panic, not UB.
+    let null_msg = format!("callback `{}` has a null function pointer", arg_id);
+    let closure = quote! {
+        {
+            let callback = #arg_id;
+            let func: Option<unsafe extern "C" fn(*mut std::ffi::c_void #(, #ffi_tys)*) -> #ffi_ret> =
+                unsafe { std::mem::transmute(callback.func) };
+            let func = func.expect(#null_msg);
+            move |#(#params: #rust_tys),*| -> #ffi_ret {
+                unsafe { func(callback.ctx() #(, #ffi_vals)*) }
+            }
+        }
+    };
+
+    let (conv, call_arg) = match arg_ty {
+        Type::Reference(r) if r.mutability.is_some() => {
+            (quote! { let mut #arg_id = #closure; }, quote! { &mut #arg_id })
+        }
+        Type::Reference(_) => (quote! { let #arg_id = #closure; }, quote! { &#arg_id }),
+        // `Box<dyn Fn(..)>`
+        Type::Path(_) => (quote! { let #arg_id = #closure; }, quote! { Box::new(#arg_id) }),
+        _ => (quote! { let #arg_id = #closure; }, quote! { #arg_id }),
+    };
+
+    (quote! { #arg_id: xross_core::XrossCallback }, conv, call_arg)
+}
@@ -0,0 +1,290 @@
+use crate::codegen::ffi::primitive_rust_type;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{ReturnType, Type};
+use xross_metadata::{XrossCollectionAccessors, XrossType};
+
+/// Returns the type arguments of the last path segment (e.g. `K` and `V` of `HashMap<K, V>`).
+pub fn generic_type_args(ty: &Type) -> Vec<&Type> {
+    let ty = if let Type::Reference(r) = ty { &*r.elem } else { ty };
+    let Type::Path(tp) = ty else { return Vec::new() };
+    let Some(last) = tp.path.segments.last() else { return Vec::new() };
+    let syn::PathArguments::AngleBracketed(args) = &last.arguments else {
+        return Vec::new();
+    };
+    args.args
+        .iter()
+        .filter_map(
+            |arg| if let syn::GenericArgument::Type(inner) = arg { Some(inner) } else { None },
+        )
+        .collect()
+}
+
+fn is_collection(ty: &XrossType) -> bool {
+    matches!(
+        ty,
+        XrossType::VecDeque(_)
+            | XrossType::LinkedList(_)
+            | XrossType::HashSet(_)
+            | XrossType::BTreeSet(_)
+            | XrossType::BinaryHeap(_)
+            | XrossType::HashMap { .. }
+            | XrossType::BTreeMap { .. }
+    )
+}
+
+/// Locates the collection carried by a return type, looking through `Option` and the `Ok` side of `Result`.
+fn find_collection(ty: &XrossType) -> Option<&XrossType> {
+    match ty {
+        _ if is_collection(ty) => Some(ty),
+        XrossType::Option(inner) | XrossType::Result { ok: inner, .. } => find_collection(inner),
+        _ => None,
+    }
+}
+
+/// Same as [`find_collection`], but also resolves the matching Rust type.
+fn find_collection_with_type<'a>(
+    ty: &'a XrossType,
+    rust_ty: &'a Type,
+) -> Option<(&'a XrossType, &'a Type)> {
+    match ty {
+        _ if is_collection(ty) => Some((ty, rust_ty)),
+        XrossType::Option(inner) | XrossType::Result { ok: inner, .. } => {
+            find_collection_with_type(inner, generic_type_args(rust_ty).first()?)
+        }
+        _ => None,
+    }
+}
+
+fn is_supported_key(ty: &XrossType) -> bool {
+    ty.is_primitive() || matches!(ty, XrossType::String | XrossType::Object { .. })
+}
+
+/// Returns the symbols of the accessor exports generated for a returned collection.
+pub fn collection_accessors(
+    symbol: &str,
+    ret_ty: &XrossType,
+) -> Option<Box<XrossCollectionAccessors>> {
+    let coll = find_collection(ret_ty)?;
+    let name = |suffix: &str| format!("{}_ret_{}", symbol, suffix);
+
+    let (get, contains, values) = match coll {
+        XrossType::HashMap { key, .. } | XrossType::BTreeMap { key, .. } => {
+            let keyed = is_supported_key(key);
+            (keyed.then(|| name("get")), keyed.then(|| name("contains")), Some(name("values")))
+        }
+        XrossType::HashSet(elem) | XrossType::BTreeSet(elem) => {
+            (None, is_supported_key(elem).then(|| name("contains")), None)
+        }
+        XrossType::VecDeque(_) | XrossType::LinkedList(_) => (Some(name("get")), None, None),
+        _ => (None, None, None),
+    };
+
+    Some(Box::new(XrossCollectionAccessors {
+        len: name("len"),
+        get,
+        contains,
+        snapshot: name("snapshot"),
+        values,
+        drop: name("drop"),
+    }))
+}
+
+/// FFI representation of an element borrowed from a collection.
+fn gen_element_repr(ty: &XrossType, val: TokenStream) -> (TokenStream, TokenStream) {
+    if let Some(prim) = primitive_rust_type(ty) {
+        return (prim, quote! { *#val });
+    }
+    match ty {
+        XrossType::Option(inner) if inner.is_primitive() => {
+            let prim = primitive_rust_type(inner).unwrap();
+            (
+                quote! { xross_core::XrossOption<#prim> },
+                quote! { xross_core::XrossOption::from(*#val) },
+            )
+        }
+        XrossType::String => (
+            quote! { xross_core::XrossString },
+            quote! {
+                xross_core::XrossString {
+                    ptr: #val.as_ptr() as *mut u8,
+                    len: #val.len(),
+                    cap: #val.capacity(),
+                }
+            },
+        ),
+        _ => {
+            (quote! { *mut std::ffi::c_void }, quote! { #val as *const _ as *mut std::ffi::c_void })
+        }
+    }
+}
+
+/// Generates the parameters and conversion binding `key` as a reference for a lookup key.
+fn gen_key_lookup(ty: &XrossType, key_ty: &Type) -> (Vec<TokenStream>, TokenStream) {
+    match ty {
+        XrossType::String => (
+            vec![quote! { key_ptr: *const u8 }, quote! { key_len: usize }, quote! { key_enc: u8 }],
+            quote! {
+                let key = &xross_core::XrossStringView {
+                    ptr: key_ptr,
+                    len: key_len,
+                    encoding: key_enc,
+                }.to_string_lossy();
+            },
+        ),
+        XrossType::Object { .. } => (
+            vec![quote! { key: *const std::ffi::c_void }],
+            quote! { let key = unsafe { &*(key as *const #key_ty) }; },
+        ),
+        _ => (vec![quote! { key: #key_ty }], quote! { let key = &key; }),
+    }
+}
+
+/// Generates the accessor exports for a collection returned by a method.
+pub fn generate_collection_accessors(
+    symbol: &str,
+    ret_ty: &XrossType,
+    sig_output: &ReturnType,
+    toks: &mut Vec<TokenStream>,
+) {
+    let ReturnType::Type(_, rust_ty) = sig_output else { return };
+    let Some(accessors) = collection_accessors(symbol, ret_ty) else { return };
+    let Some((coll, coll_ty)) = find_collection_with_type(ret_ty, rust_ty) else { return };
+    let type_args = generic_type_args(coll_ty);
+
+    let len_id = format_ident!("{}", accessors.len);
+    let snapshot_id = format_ident!("{}", accessors.snapshot);
+    let drop_id = format_ident!("{}", accessors.drop);
+
+    let (elem_ty, snapshot_iter) = match coll {
+        XrossType::HashMap { key, .. } | XrossType::BTreeMap { key, .. } => {
+            (key.as_ref(), quote! { coll.keys() })
+        }
+        XrossType::VecDeque(elem)
+        | XrossType::LinkedList(elem)
+        | XrossType::HashSet(elem)
+        | XrossType::BTreeSet(elem)
+        | XrossType::BinaryHeap(elem) => (elem.as_ref(), quote! { coll.iter() }),
+        _ => unreachable!(),
+    };
+    let (elem_repr, elem_conv) = gen_element_repr(elem_ty, quote! { v });
+
+    toks.push(quote! {
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #len_id(ptr: *const std::ffi::c_void) -> usize {
+            if ptr.is_null() { return 0; }
+            let coll = unsafe { &*(ptr as *const #coll_ty) };
+            coll.len()
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #snapshot_id(
+            ptr: *const std::ffi::c_void,
+            out: *mut #elem_repr,
+            cap: usize,
+        ) -> usize {
+            if ptr.is_null() || out.is_null() { return 0; }
+            let coll = unsafe { &*(ptr as *const #coll_ty) };
+            let mut written = 0;
+            for v in #snapshot_iter.take(cap) {
+                unsafe { out.add(written).write_unaligned(#elem_conv) };
+                written += 1;
+            }
+            written
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #drop_id(ptr: *mut std::ffi::c_void) {
+            if !ptr.is_null() {
+                drop(unsafe { Box::from_raw(ptr as *mut #coll_ty) });
+            }
+        }
+    });
+
+    if let (Some(values), XrossType::HashMap { value, .. } | XrossType::BTreeMap { value, .. }) =
+        (&accessors.values, coll)
+    {
+        let values_id = format_ident!("{}", values);
+        let (value_repr, value_conv) = gen_element_repr(value, quote! { v });
+        toks.push(quote! {
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn #values_id(
+                ptr: *const std::ffi::c_void,
+                out: *mut #value_repr,
+                cap: usize,
+            ) -> usize {
+                if ptr.is_null() || out.is_null() { return 0; }
+                let coll = unsafe { &*(ptr as *const #coll_ty) };
+                let mut written = 0;
+                for v in coll.values().take(cap) {
+                    unsafe { out.add(written).write_unaligned(#value_conv) };
+                    written += 1;
+                }
+                written
+            }
+        });
+    }
+
+    if let Some(get) = &accessors.get {
+        let get_id = format_ident!("{}", get);
+        let (params, lookup, value_ty) = match coll {
+            XrossType::HashMap { key, value } | XrossType::BTreeMap { key, value } => {
+                let (params, key_conv) = gen_key_lookup(key, type_args[0]);
+                (params, quote! { #key_conv coll.get(key) }, value.as_ref())
+            }
+            XrossType::VecDeque(elem) => {
+                (vec![quote! { index: usize }], quote! { coll.get(index) }, elem.as_ref())
+            }
+            XrossType::LinkedList(elem) => {
+                (vec![quote! { index: usize }], quote! { coll.iter().nth(index) }, elem.as_ref())
+            }
+            _ => unreachable!(),
+        };
+        let (value_repr, value_conv) = gen_element_repr(value_ty, quote! { v });
+        toks.push(quote! {
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn #get_id(
+                ptr: *const std::ffi::c_void,
+                #(#params,)*
+                out: *mut #value_repr,
+            ) -> bool {
+                if ptr.is_null() || out.is_null() { return false; }
+                let coll = unsafe { &*(ptr as *const #coll_ty) };
+                match { #lookup } {
+                    Some(v) => {
+                        unsafe { out.write_unaligned(#value_conv) };
+                        true
+                    }
+                    None => false,
+                }
+            }
+        });
+    }
+
+    if let Some(contains) = &accessors.contains {
+        let contains_id = format_ident!("{}", contains);
+        let (params, key_conv, call) = match coll {
+            XrossType::HashMap { key, .. } | XrossType::BTreeMap { key, .. } => {
+                let (params, key_conv) = gen_key_lookup(key, type_args[0]);
+                (params, key_conv, quote! { coll.contains_key(key) })
+            }
+            XrossType::HashSet(elem) | XrossType::BTreeSet(elem) => {
+                let (params, key_conv) = gen_key_lookup(elem, type_args[0]);
+                (params, key_conv, quote! { coll.contains(key) })
+            }
+            _ => unreachable!(),
+        };
+        toks.push(quote! {
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn #contains_id(
+                ptr: *const std::ffi::c_void,
+                #(#params),*
+            ) -> bool {
+                if ptr.is_null() { return false; }
+                let coll = unsafe { &*(ptr as *const #coll_ty) };
+                #key_conv
+                #call
+            }
+        });
+    }
+}
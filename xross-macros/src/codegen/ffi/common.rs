@@ -1,14 +1,17 @@
+use crate::codegen::ffi::{HandleUse, gen_handle_guard, gen_track_handle, gen_uncount_handle};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use xross_metadata::HandleMode;
 
-/// Generates common FFI functions (drop, clone, layout).
+/// Generates common FFI functions (drop, clone, layout, and arc clone/drop for shared types).
+#[allow(clippy::too_many_arguments)]
 pub fn generate_common_ffi(
     name: &syn::Ident,
     base: &str,
     layout_logic: TokenStream,
     toks: &mut Vec<TokenStream>,
     is_clonable: bool,
+    is_shared: bool,
     clone_mode: HandleMode,
     drop_mode: HandleMode,
 ) {
@@ -22,18 +25,33 @@ pub fn generate_common_ffi(
         impl #trait_name for #name { fn xross_layout() -> String { #layout_logic } }
     });
 
+    let self_ty = quote! { #name };
+    let uncount = gen_uncount_handle(quote! { ptr });
     let drop_inner = quote! {
-        if !ptr.is_null() { drop(unsafe { Box::from_raw(ptr) }); }
+        if !ptr.is_null() {
+            #uncount
+            drop(unsafe { Box::from_raw(ptr) });
+        }
     };
 
+    let report = quote! { return err.into_result(); };
+    let drop_symbol = drop_id.to_string();
     if drop_mode == HandleMode::Panicable {
         let error_arm = gen_panic_error_arm("drop");
+        let guard = gen_handle_guard(
+            quote! { ptr },
+            &self_ty,
+            HandleUse::Release,
+            &drop_symbol,
+            Some(&report),
+        );
         toks.push(quote! {
             #[unsafe(no_mangle)]
             pub unsafe extern "C" fn #drop_id(ptr: *mut #name) -> xross_core::XrossResult {
-                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+                #guard
+                let result = xross_core::xross_catch_panic(move || {
                     #drop_inner
-                }));
+                });
                 match result {
                     Ok(_) => xross_core::XrossResult { is_ok: true, ptr: std::ptr::null_mut() },
                     #error_arm
@@ -41,32 +59,45 @@ pub fn generate_common_ffi(
             }
         });
     } else {
+        let guard =
+            gen_handle_guard(quote! { ptr }, &self_ty, HandleUse::Release, &drop_symbol, None);
         toks.push(quote! {
             #[unsafe(no_mangle)]
             pub unsafe extern "C" fn #drop_id(ptr: *mut #name) {
+                #guard
                 #drop_inner
             }
         });
     }
 
     if is_clonable {
+        let clone_symbol = clone_id.to_string();
+        let cloned = gen_track_handle(quote! { Box::into_raw(Box::new(cloned_val)) });
         let clone_inner = quote! {
             if ptr.is_null() { return std::ptr::null_mut(); }
             let val_on_stack: #name = std::ptr::read_unaligned(ptr);
             let cloned_val = val_on_stack.clone();
             #[allow(clippy::forget_non_drop)]
             std::mem::forget(val_on_stack);
-            Box::into_raw(Box::new(cloned_val))
+            #cloned as *mut #name
         };
 
         if clone_mode == HandleMode::Panicable {
             let error_arm = gen_panic_error_arm("clone");
+            let guard = gen_handle_guard(
+                quote! { ptr },
+                &self_ty,
+                HandleUse::Borrow,
+                &clone_symbol,
+                Some(&report),
+            );
             toks.push(quote! {
                 #[unsafe(no_mangle)]
                 pub unsafe extern "C" fn #clone_id(ptr: *const #name) -> xross_core::XrossResult {
-                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+                    #guard
+                    let result = xross_core::xross_catch_panic(move || {
                         #clone_inner
-                    }));
+                    });
                     match result {
                         Ok(p) => xross_core::XrossResult { is_ok: true, ptr: p as *mut std::ffi::c_void },
                         #error_arm
@@ -74,15 +105,58 @@ pub fn generate_common_ffi(
                 }
             });
         } else {
+            let guard =
+                gen_handle_guard(quote! { ptr }, &self_ty, HandleUse::Borrow, &clone_symbol, None);
             toks.push(quote! {
                 #[unsafe(no_mangle)]
                 pub unsafe extern "C" fn #clone_id(ptr: *const #name) -> *mut #name {
+                    #guard
                     #clone_inner
                 }
             });
         }
     }
 
+    if is_shared {
+        // Handles of `Arc<T>` point at `T` and each hold one strong reference.
+        // Cloning hands out the same pointer with an extra strong reference.
+        let arc_clone_id = format_ident!("{}_arc_clone", base);
+        let arc_drop_id = format_ident!("{}_arc_drop", base);
+        let arc_clone_guard = gen_handle_guard(
+            quote! { ptr },
+            &self_ty,
+            HandleUse::Borrow,
+            &arc_clone_id.to_string(),
+            None,
+        );
+        let arc_drop_guard = gen_handle_guard(
+            quote! { ptr },
+            &self_ty,
+            HandleUse::Release,
+            &arc_drop_id.to_string(),
+            None,
+        );
+        let shared = gen_track_handle(quote! { ptr.cast_mut() });
+        let uncount_shared = gen_uncount_handle(quote! { ptr.cast_mut() });
+        toks.push(quote! {
+            impl xross_core::XrossShared for #name {}
+
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn #arc_clone_id(ptr: *const #name) -> *const #name {
+                #arc_clone_guard
+                if !ptr.is_null() { unsafe { std::sync::Arc::increment_strong_count(ptr) }; }
+                #shared as *const #name
+            }
+
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn #arc_drop_id(ptr: *const #name) {
+                #arc_drop_guard
+                #uncount_shared
+                if !ptr.is_null() { unsafe { std::sync::Arc::decrement_strong_count(ptr) }; }
+            }
+        });
+    }
+
     toks.push(quote! {
         #[unsafe(no_mangle)]
         pub unsafe extern "C" fn #layout_id(out: *mut xross_core::XrossString) {
@@ -99,17 +173,29 @@ pub fn generate_enum_aux_ffi(
     variant_name_arms: Vec<TokenStream>,
     toks: &mut Vec<TokenStream>,
 ) {
+    let self_ty = quote! { #type_ident };
     let tag_fn_id = format_ident!("{}_get_tag", symbol_base);
     let variant_name_fn_id = format_ident!("{}_get_variant_name", symbol_base);
+    let tag_guard =
+        gen_handle_guard(quote! { ptr }, &self_ty, HandleUse::Borrow, &tag_fn_id.to_string(), None);
+    let variant_name_guard = gen_handle_guard(
+        quote! { ptr },
+        &self_ty,
+        HandleUse::Borrow,
+        &variant_name_fn_id.to_string(),
+        None,
+    );
     toks.push(quote! {
         #[unsafe(no_mangle)]
         pub unsafe extern "C" fn #tag_fn_id(ptr: *const #type_ident) -> i32 {
+            #tag_guard
             if ptr.is_null() { return -1; }
             unsafe { *(ptr as *const i32) }
         }
 
         #[unsafe(no_mangle)]
         pub unsafe extern "C" fn #variant_name_fn_id(out: *mut xross_core::XrossString, ptr: *const #type_ident) {
+            #variant_name_guard
             if ptr.is_null() {
                 unsafe { std::ptr::write_unaligned(out, xross_core::XrossString::from(String::new())) };
                 return;
@@ -127,20 +213,6 @@ pub fn gen_panic_error_arm(context: &str) -> TokenStream {
     let default_msg = format!("Unknown panic{}", msg_prefix);
 
     quote! {
-        Err(panic_err) => {
-            let msg = if let Some(s) = panic_err.downcast_ref::<&str>() {
-                s.to_string()
-            } else if let Some(s) = panic_err.downcast_ref::<String>() {
-                s.clone()
-            } else {
-                #default_msg .to_string()
-            };
-
-            let xs = xross_core::XrossString::from(msg);
-            xross_core::XrossResult {
-                is_ok: false,
-                ptr: Box::into_raw(Box::new(xs)) as *mut std::ffi::c_void,
-            }
-        }
+        Err(panic) => panic.into_error(#default_msg).into_result()
     }
 }
@@ -1,3 +1,4 @@
+use crate::codegen::ffi::{gen_borrow_handle, gen_track_handle, gen_uncount_handle};
 use crate::types::resolver::resolve_type_with_attr;
 use crate::utils::{extract_base_type, extract_inner_type, is_primitive_type};
 use proc_macro2::TokenStream;
@@ -21,6 +22,65 @@ fn extract_result_types(ty: &Type) -> Option<(&Type, &Type)> {
     Some((iter.next()?, iter.next()?))
 }
 
+/// Returns true if the type is `String` rather than a borrowed `str` or `Cow<str>`.
+fn is_owned_string(ty: &Type) -> bool {
+    matches!(ty, Type::Path(tp) if tp.path.segments.last().is_some_and(|s| s.ident == "String"))
+}
+
+/// Returns the Rust type of a primitive `XrossType`, if it is one.
+pub fn primitive_rust_type(ty: &XrossType) -> Option<TokenStream> {
+    Some(match ty {
+        XrossType::I8 => quote!(i8),
+        XrossType::U8 => quote!(u8),
+        XrossType::I16 => quote!(i16),
+        XrossType::U16 => quote!(u16),
+        XrossType::I32 => quote!(i32),
+        XrossType::U32 => quote!(u32),
+        XrossType::I64 => quote!(i64),
+        XrossType::U64 => quote!(u64),
+        XrossType::ISize => quote!(isize),
+        XrossType::USize => quote!(usize),
+        XrossType::F32 => quote!(f32),
+        XrossType::F64 => quote!(f64),
+        XrossType::Bool => quote!(bool),
+        _ => return None,
+    })
+}
+
+/// Takes a new strong reference out of a handle that stays owned by the JVM.
+/// Requires `T: XrossShared`, so `Arc<T>` only crosses the boundary for `#[xross(shared)]` types.
+pub fn gen_shared_from_handle(base: &Type, handle: TokenStream) -> TokenStream {
+    quote! { unsafe { xross_core::xross_arc_from_handle(#handle as *const #base) } }
+}
+
+/// Hands an `Arc<T>` to the JVM as a handle holding one strong reference.
+pub fn gen_shared_into_handle(val: TokenStream) -> TokenStream {
+    gen_track_handle(quote! { xross_core::xross_arc_into_handle(#val) })
+}
+
+/// Returns the `#[xross_trait]` proxy type standing in for `dyn Trait` / `impl Trait`.
+fn trait_proxy_type(ty: &Type) -> Option<syn::Path> {
+    let bounds = match ty {
+        Type::Reference(r) => return trait_proxy_type(&r.elem),
+        Type::Path(_) => {
+            return crate::codegen::ffi::generic_type_args(ty)
+                .first()
+                .and_then(|inner| trait_proxy_type(inner));
+        }
+        Type::TraitObject(t) => &t.bounds,
+        Type::ImplTrait(t) => &t.bounds,
+        _ => return None,
+    };
+    bounds.iter().find_map(|bound| {
+        let syn::TypeParamBound::Trait(t) = bound else { return None };
+        let mut path = t.path.clone();
+        let last = path.segments.last_mut()?;
+        last.ident = format_ident!("Xross{}Proxy", last.ident);
+        last.arguments = syn::PathArguments::None;
+        Some(path)
+    })
+}
+
 /// Resolves the Xross return type and ownership.
 pub fn resolve_return_type(
     output: &ReturnType,
@@ -42,7 +102,10 @@ pub fn resolve_return_type(
                 }
                 _ => Ownership::Owned,
             };
-            if let XrossType::Object { ownership: o, .. } = &mut xty {
+            // `Arc<T>` keeps its shared ownership even when returned by reference.
+            if let XrossType::Object { ownership: o, .. } = &mut xty
+                && !o.is_shared()
+            {
                 *o = ownership;
             }
             xty
@@ -56,6 +119,24 @@ pub fn gen_receiver_logic(
     type_ident: &syn::Ident,
 ) -> (xross_metadata::XrossMethodType, TokenStream, TokenStream) {
     let arg_ident = format_ident!("_self");
+    let c_arg = quote! { #arg_ident: *mut std::ffi::c_void };
+
+    // `self: Arc<Self>` takes a new strong reference from a shared handle.
+    if receiver.colon_token.is_some()
+        && let Type::Path(tp) = &*receiver.ty
+        && let Some(last) = tp.path.segments.last()
+    {
+        match last.ident.to_string().as_str() {
+            "Arc" => {
+                let base: Type = syn::parse_quote!(#type_ident);
+                let call_arg = gen_shared_from_handle(&base, quote! { #arg_ident });
+                return (xross_metadata::XrossMethodType::SharedInstance, c_arg, call_arg);
+            }
+            "Rc" => panic!("{}", crate::types::mapping::RC_UNSUPPORTED),
+            _ => {}
+        }
+    }
+
     let method_type = if receiver.reference.is_none() {
         xross_metadata::XrossMethodType::OwnedInstance
     } else if receiver.mutability.is_some() {
@@ -64,11 +145,11 @@ pub fn gen_receiver_logic(
         xross_metadata::XrossMethodType::ConstInstance
     };
 
-    let c_arg = quote! { #arg_ident: *mut std::ffi::c_void };
     let call_arg = if receiver.reference.is_none() {
         // Use ptr::read to avoid taking ownership of the pointer itself.
         // This prevents double free if Kotlin side also thinks it owns the memory.
-        quote! { unsafe { std::ptr::read(#arg_ident as *const #type_ident) } }
+        let uncount = gen_uncount_handle(quote! { #arg_ident as *mut #type_ident });
+        quote! { { #uncount unsafe { std::ptr::read(#arg_ident as *const #type_ident) } } }
     } else if receiver.mutability.is_some() {
         quote! { &mut *(#arg_ident as *mut #type_ident) }
     } else {
@@ -78,6 +159,165 @@ pub fn gen_receiver_logic(
     (method_type, c_arg, call_arg)
 }
 
+/// Returns the element type of `Vec<T>`, `&[T]` or `[T]`.
+fn sequence_elem_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Reference(r) => sequence_elem_type(&r.elem),
+        Type::Slice(s) => Some(&s.elem),
+        _ => crate::codegen::ffi::generic_type_args(ty).into_iter().next(),
+    }
+}
+
+/// Generates the FFI element type and the conversion of one element `v` passed by the JVM
+/// inside a sequence or tuple. The boolean is true if elements are read out of JVM-owned handles.
+fn gen_incoming_element(elem: &XrossType, elem_ty: &Type) -> (TokenStream, TokenStream, bool) {
+    let read_handle = |ty: &Type, v: TokenStream| {
+        if let Type::Reference(r) = ty {
+            let base = extract_base_type(&r.elem);
+            (quote! { unsafe { &*(#v as *const #base) } }, false)
+        } else {
+            let uncount = gen_uncount_handle(quote! { #v as *mut #ty });
+            (quote! { { #uncount unsafe { std::ptr::read(#v as *const #ty) } } }, true)
+        }
+    };
+    match elem {
+        _ if elem.is_primitive() => (primitive_rust_type(elem).unwrap(), quote! { v }, false),
+        XrossType::Option(inner) if inner.is_primitive() => {
+            let prim = primitive_rust_type(inner).unwrap();
+            (quote! { xross_core::XrossOption<#prim> }, quote! { v.into_option() }, false)
+        }
+        XrossType::String => {
+            (quote! { xross_core::XrossStringView }, quote! { v.to_string_lossy() }, false)
+        }
+        XrossType::Option(inner) if **inner == XrossType::String => (
+            quote! { xross_core::XrossStringView },
+            quote! { if v.ptr.is_null() { None } else { Some(v.to_string_lossy()) } },
+            false,
+        ),
+        XrossType::Object { ownership, .. } if ownership.is_shared() => {
+            let conv = gen_shared_from_handle(extract_base_type(elem_ty), quote! { v });
+            (quote! { *mut std::ffi::c_void }, conv, false)
+        }
+        XrossType::Object { .. } => {
+            let (conv, owned) = read_handle(elem_ty, quote! { v });
+            (quote! { *mut std::ffi::c_void }, conv, owned)
+        }
+        XrossType::Option(inner) if matches!(**inner, XrossType::Object { .. }) => {
+            let (conv, owned) = read_handle(extract_inner_type(elem_ty), quote! { v });
+            (
+                quote! { *mut std::ffi::c_void },
+                quote! { if v.is_null() { None } else { Some(#conv) } },
+                owned,
+            )
+        }
+        _ => panic!("Unsupported element type: {}", quote!(#elem_ty)),
+    }
+}
+
+/// Generates argument conversion for `Vec<T>` / `&[T]` with non-primitive elements.
+/// The JVM passes a pointer to an array of element representations and its length.
+fn gen_sequence_arg(
+    arg_ty: &Type,
+    arg_id: &syn::Ident,
+    elem: &XrossType,
+    is_slice: bool,
+) -> (TokenStream, TokenStream, TokenStream) {
+    let ptr_id = format_ident!("{}_ptr", arg_id);
+    let len_id = format_ident!("{}_len", arg_id);
+    let elem_ty = sequence_elem_type(arg_ty)
+        .unwrap_or_else(|| panic!("Cannot determine element type of {}", quote!(#arg_ty)));
+    let (ffi_elem, conv, reads_handles) = gen_incoming_element(elem, elem_ty);
+
+    let collect = quote! {
+        let #arg_id: Vec<#elem_ty> = if #ptr_id.is_null() {
+            Vec::new()
+        } else {
+            unsafe { std::slice::from_raw_parts(#ptr_id, #len_id) }
+                .iter()
+                .map(|&v| #conv)
+                .collect()
+        };
+    };
+    // A borrowed slice must not drop bitwise copies of objects still owned by the JVM.
+    let (conv_logic, call_arg) = match (is_slice, reads_handles) {
+        (true, true) => (
+            quote! { #collect let #arg_id = std::mem::ManuallyDrop::new(#arg_id); },
+            quote! { &#arg_id },
+        ),
+        (true, false) => (collect, quote! { &#arg_id }),
+        (false, _) => (collect, quote! { #arg_id }),
+    };
+    (quote! { #ptr_id: *const #ffi_elem, #len_id: usize }, conv_logic, call_arg)
+}
+
+/// Builds a Rust tuple from a pointer to the `#[repr(C)]` struct of element representations
+/// written by the JVM.
+fn gen_tuple_from_ptr(elems: &[XrossType], tuple_ty: &Type, ptr: TokenStream) -> TokenStream {
+    let Type::Tuple(tuple) = tuple_ty else {
+        panic!("Tuple arg must be a tuple type: {}", quote!(#tuple_ty));
+    };
+    let (fields, convs): (Vec<_>, Vec<_>) = elems
+        .iter()
+        .zip(tuple.elems.iter())
+        .enumerate()
+        .map(|(i, (elem, elem_ty))| {
+            let (ffi_elem, conv, _) = gen_incoming_element(elem, elem_ty);
+            let index = syn::Index::from(i);
+            (ffi_elem, quote! { { let v = raw.#index; #conv } })
+        })
+        .unzip();
+    quote! {
+        {
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            struct XrossTupleArg(#(#fields),*);
+            let raw = unsafe { std::ptr::read_unaligned(#ptr as *const XrossTupleArg) };
+            (#(#convs,)*)
+        }
+    }
+}
+
+/// Converts an owned tuple into a `#[repr(C)]` struct of element representations.
+/// Non-primitive elements are owned by the JVM afterwards.
+pub fn gen_tuple_to_repr(elems: &[XrossType], val: TokenStream) -> TokenStream {
+    let names: Vec<_> = (0..elems.len()).map(|i| format_ident!("e{}", i)).collect();
+    let params: Vec<_> = (0..elems.len()).map(|i| format_ident!("T{}", i)).collect();
+    let convs = elems
+        .iter()
+        .zip(&names)
+        .map(|(elem, name)| gen_owned_element(elem, quote! { #name }).unwrap_or(quote! { #name }));
+    quote! {
+        {
+            #[repr(C)]
+            struct XrossTupleRepr<#(#params),*>(#(#params),*);
+            let (#(#names,)*) = #val;
+            XrossTupleRepr(#(#convs),*)
+        }
+    }
+}
+
+/// Converts an owned element into the representation stored in a returned `XrossArray`.
+fn gen_owned_element(ty: &XrossType, val: TokenStream) -> Option<TokenStream> {
+    match ty {
+        _ if ty.is_primitive() => None,
+        XrossType::Option(inner) if inner.is_primitive() => {
+            Some(quote! { xross_core::XrossOption::from(#val) })
+        }
+        XrossType::String => Some(quote! { xross_core::XrossString::from(#val) }),
+        _ => Some(gen_single_value_to_ptr(ty, val)),
+    }
+}
+
+/// Converts an owned `Vec<T>` into an `XrossArray` whose elements are owned by the JVM.
+pub fn gen_vec_to_array(inner: &XrossType, vec: TokenStream) -> TokenStream {
+    match gen_owned_element(inner, quote! { v }) {
+        None => quote! { xross_core::XrossArray::from(#vec) },
+        Some(conv) => quote! {
+            xross_core::XrossArray::from(#vec.into_iter().map(|v| #conv).collect::<Vec<_>>())
+        },
+    }
+}
+
 /// Helper to generate argument conversion logic.
 pub fn gen_arg_conversion(
     arg_ty: &Type,
@@ -89,37 +329,30 @@ pub fn gen_arg_conversion(
             let ptr_id = format_ident!("{}_ptr", arg_id);
             let len_id = format_ident!("{}_len", arg_id);
             let enc_id = format_ident!("{}_enc", arg_id);
-            (
-                quote! { #ptr_id: *const u8, #len_id: usize, #enc_id: u8 },
-                quote! {
-                    let #arg_id = xross_core::XrossStringView {
-                        ptr: #ptr_id,
-                        len: #len_id,
-                        encoding: #enc_id,
-                    }.to_string_lossy();
-                },
-                quote!(#arg_id),
-            )
+            let view = quote! {
+                xross_core::XrossStringView { ptr: #ptr_id, len: #len_id, encoding: #enc_id }
+            };
+            // `&str` and `Cow<str>` borrow the JVM bytes when they need no transcoding.
+            let (conv, call_arg) = match arg_ty {
+                Type::Reference(r) if !is_owned_string(&r.elem) => (
+                    quote! { let #arg_id = #view; let #arg_id = #arg_id.as_cow(); },
+                    quote! { &#arg_id },
+                ),
+                Type::Reference(_) => {
+                    (quote! { let #arg_id = #view.to_string_lossy(); }, quote! { &#arg_id })
+                }
+                _ if !is_owned_string(arg_ty) => (
+                    quote! { let #arg_id = #view; let #arg_id = #arg_id.as_cow(); },
+                    quote! { #arg_id },
+                ),
+                _ => (quote! { let #arg_id = #view.to_string_lossy(); }, quote! { #arg_id }),
+            };
+            (quote! { #ptr_id: *const u8, #len_id: usize, #enc_id: u8 }, conv, call_arg)
         }
-        XrossType::Slice(inner) => {
+        XrossType::Slice(inner) if inner.is_primitive() => {
             let ptr_id = format_ident!("{}_ptr", arg_id);
             let len_id = format_ident!("{}_len", arg_id);
-            let inner_rust_ty = match &**inner {
-                XrossType::I8 => quote!(i8),
-                XrossType::U8 => quote!(u8),
-                XrossType::I16 => quote!(i16),
-                XrossType::U16 => quote!(u16),
-                XrossType::I32 => quote!(i32),
-                XrossType::U32 => quote!(u32),
-                XrossType::I64 => quote!(i64),
-                XrossType::U64 => quote!(u64),
-                XrossType::ISize => quote!(isize),
-                XrossType::USize => quote!(usize),
-                XrossType::F32 => quote!(f32),
-                XrossType::F64 => quote!(f64),
-                XrossType::Bool => quote!(bool),
-                _ => quote!(std::ffi::c_void),
-            };
+            let inner_rust_ty = primitive_rust_type(inner).unwrap();
             (
                 quote! { #ptr_id: *const #inner_rust_ty, #len_id: usize },
                 quote! {
@@ -128,25 +361,10 @@ pub fn gen_arg_conversion(
                 quote!(#arg_id),
             )
         }
-        XrossType::Vec(inner) => {
+        XrossType::Vec(inner) if inner.is_primitive() => {
             let ptr_id = format_ident!("{}_ptr", arg_id);
             let len_id = format_ident!("{}_len", arg_id);
-            let inner_rust_ty = match &**inner {
-                XrossType::I8 => quote!(i8),
-                XrossType::U8 => quote!(u8),
-                XrossType::I16 => quote!(i16),
-                XrossType::U16 => quote!(u16),
-                XrossType::I32 => quote!(i32),
-                XrossType::U32 => quote!(u32),
-                XrossType::I64 => quote!(i64),
-                XrossType::U64 => quote!(u64),
-                XrossType::ISize => quote!(isize),
-                XrossType::USize => quote!(usize),
-                XrossType::F32 => quote!(f32),
-                XrossType::F64 => quote!(f64),
-                XrossType::Bool => quote!(bool),
-                _ => quote!(std::ffi::c_void),
-            };
+            let inner_rust_ty = primitive_rust_type(inner).unwrap();
             (
                 quote! { #ptr_id: *const #inner_rust_ty, #len_id: usize },
                 quote! {
@@ -155,6 +373,9 @@ pub fn gen_arg_conversion(
                 quote!(#arg_id),
             )
         }
+        XrossType::Slice(inner) | XrossType::Vec(inner) => {
+            gen_sequence_arg(arg_ty, arg_id, inner, matches!(x_ty, XrossType::Slice(_)))
+        }
         XrossType::Object { .. }
         | XrossType::VecDeque(_)
         | XrossType::LinkedList(_)
@@ -165,6 +386,23 @@ pub fn gen_arg_conversion(
         | XrossType::BTreeMap { .. } => (
             quote! { #arg_id: *mut std::ffi::c_void },
             match x_ty {
+                XrossType::Object { ownership, .. } if trait_proxy_type(arg_ty).is_some() => {
+                    let proxy = trait_proxy_type(arg_ty).unwrap();
+                    match ownership {
+                        Ownership::Ref => {
+                            quote! { let #arg_id = unsafe { &*(#arg_id as *const #proxy) }; }
+                        }
+                        Ownership::MutRef => {
+                            quote! { let #arg_id = unsafe { &mut *(#arg_id as *mut #proxy) }; }
+                        }
+                        Ownership::Boxed => {
+                            quote! { let #arg_id = unsafe { Box::from_raw(#arg_id as *mut #proxy) }; }
+                        }
+                        _ => {
+                            quote! { let #arg_id = *unsafe { Box::from_raw(#arg_id as *mut #proxy) }; }
+                        }
+                    }
+                }
                 XrossType::Object { ownership, .. } => match ownership {
                     Ownership::Ref => {
                         let base = extract_base_type(arg_ty);
@@ -176,12 +414,25 @@ pub fn gen_arg_conversion(
                     }
                     Ownership::Boxed => {
                         let inner = extract_inner_type(arg_ty);
-                        quote! { let #arg_id = unsafe { Box::from_raw(#arg_id as *mut #inner) }; }
+                        let uncount = gen_uncount_handle(quote! { #arg_id as *mut #inner });
+                        quote! {
+                            #uncount
+                            let #arg_id = unsafe { Box::from_raw(#arg_id as *mut #inner) };
+                        }
+                    }
+                    Ownership::Shared => {
+                        let conv =
+                            gen_shared_from_handle(extract_base_type(arg_ty), quote! { #arg_id });
+                        quote! { let #arg_id = #conv; }
                     }
                     Ownership::Owned => {
                         let base = extract_base_type(arg_ty);
+                        let uncount = gen_uncount_handle(quote! { #arg_id as *mut #base });
                         // Use ptr::read instead of Box::from_raw to avoid freeing memory that might be owned by Kotlin (e.g. Pure Enums).
-                        quote! { let #arg_id = unsafe { std::ptr::read(#arg_id as *const #base) }; }
+                        quote! {
+                            #uncount
+                            let #arg_id = unsafe { std::ptr::read(#arg_id as *const #base) };
+                        }
                     }
                 },
                 _ => {
@@ -194,40 +445,61 @@ pub fn gen_arg_conversion(
                     }
                 }
             },
-            quote! { #arg_id },
+            match (x_ty, arg_ty) {
+                // `&Arc<T>` borrows the strong reference taken for the call.
+                (XrossType::Object { ownership, .. }, Type::Reference(_))
+                    if ownership.is_shared() =>
+                {
+                    quote! { &#arg_id }
+                }
+                _ => quote! { #arg_id },
+            },
         ),
-        XrossType::Option(inner) => {
+        XrossType::Function { args, ret } => {
+            crate::codegen::ffi::gen_callback_arg(arg_ty, arg_id, args, ret)
+        }
+        XrossType::Tuple(elems) => {
+            let read = gen_tuple_from_ptr(elems, arg_ty, quote! { #arg_id });
+            (
+                quote! { #arg_id: *const std::ffi::c_void },
+                quote! { let #arg_id = #read; },
+                quote! { #arg_id },
+            )
+        }
+        XrossType::Option(inner) if inner.is_primitive() => {
             let inner_rust_ty = extract_inner_type(arg_ty);
+            (
+                quote! { #arg_id: xross_core::XrossOption<#inner_rust_ty> },
+                quote! { let #arg_id = #arg_id.into_option(); },
+                quote! { #arg_id },
+            )
+        }
+        XrossType::Option(inner) if matches!(&**inner, XrossType::Object { ownership, .. } if ownership.is_shared()) =>
+        {
+            let conv = gen_shared_from_handle(extract_base_type(arg_ty), quote! { #arg_id });
             (
                 quote! { #arg_id: *mut std::ffi::c_void },
-                match &**inner {
-                    XrossType::String
-                    | XrossType::Object { .. }
-                    | XrossType::Option(_)
-                    | XrossType::Result { .. }
-                    | XrossType::Vec(_)
-                    | XrossType::VecDeque(_)
-                    | XrossType::LinkedList(_)
-                    | XrossType::HashSet(_)
-                    | XrossType::BTreeSet(_)
-                    | XrossType::BinaryHeap(_)
-                    | XrossType::HashMap { .. }
-                    | XrossType::BTreeMap { .. } => quote! {
-                        let #arg_id = if #arg_id.is_null() { None }
-                        else { unsafe { Some(std::ptr::read(#arg_id as *const #inner_rust_ty)) } };
-                    },
-                    XrossType::F32 => quote! {
-                        let #arg_id = if #arg_id.is_null() { None }
-                        else { Some(f32::from_bits(#arg_id as u32)) };
-                    },
-                    XrossType::F64 => quote! {
-                        let #arg_id = if #arg_id.is_null() { None }
-                        else { Some(f64::from_bits(#arg_id as u64)) };
-                    },
-                    _ => quote! {
-                        let #arg_id = if #arg_id.is_null() { None }
-                        else { Some(#arg_id as usize as #inner_rust_ty) };
-                    },
+                quote! { let #arg_id = if #arg_id.is_null() { None } else { Some(#conv) }; },
+                quote! { #arg_id },
+            )
+        }
+        XrossType::Option(inner) if matches!(**inner, XrossType::Tuple(_)) => {
+            let XrossType::Tuple(elems) = &**inner else { unreachable!() };
+            let read = gen_tuple_from_ptr(elems, extract_inner_type(arg_ty), quote! { #arg_id });
+            (
+                quote! { #arg_id: *mut std::ffi::c_void },
+                quote! { let #arg_id = if #arg_id.is_null() { None } else { Some(#read) }; },
+                quote! { #arg_id },
+            )
+        }
+        XrossType::Option(_) => {
+            let inner_rust_ty = extract_inner_type(arg_ty);
+            let uncount = gen_uncount_handle(quote! { #arg_id as *mut #inner_rust_ty });
+            (
+                quote! { #arg_id: *mut std::ffi::c_void },
+                quote! {
+                    let #arg_id = if #arg_id.is_null() { None }
+                    else { #uncount unsafe { Some(std::ptr::read(#arg_id as *const #inner_rust_ty)) } };
                 },
                 quote! { #arg_id },
             )
@@ -236,6 +508,16 @@ pub fn gen_arg_conversion(
             let (ok_ty, err_ty) = extract_result_types(arg_ty)
                 .unwrap_or_else(|| panic!("Result arg must have concrete Ok/Err types"));
             let gen_read = |ty: &XrossType, ptr: TokenStream, rust_ty: &Type| match ty {
+                XrossType::Option(inner) if inner.is_primitive() => {
+                    let inner_rust_ty = extract_inner_type(rust_ty);
+                    quote! {
+                        unsafe { std::ptr::read(#ptr as *const xross_core::XrossOption<#inner_rust_ty>) }.into_option()
+                    }
+                }
+                XrossType::Tuple(elems) => gen_tuple_from_ptr(elems, rust_ty, ptr),
+                XrossType::Object { ownership, .. } if ownership.is_shared() => {
+                    gen_shared_from_handle(extract_base_type(rust_ty), ptr)
+                }
                 XrossType::String
                 | XrossType::Object { .. }
                 | XrossType::Option(_)
@@ -247,7 +529,9 @@ pub fn gen_arg_conversion(
                 | XrossType::BTreeSet(_)
                 | XrossType::BinaryHeap(_)
                 | XrossType::HashMap { .. }
-                | XrossType::BTreeMap { .. } => quote! { std::ptr::read(#ptr as *const #rust_ty) },
+                | XrossType::BTreeMap { .. } => {
+                    quote! { unsafe { std::ptr::read(#ptr as *const #rust_ty) } }
+                }
                 XrossType::F32 => quote! { f32::from_bits(#ptr as u32) },
                 XrossType::F64 => quote! { f64::from_bits(#ptr as u64) },
                 _ => quote! { #ptr as usize as #rust_ty },
@@ -258,9 +542,9 @@ pub fn gen_arg_conversion(
                 quote! { #arg_id: xross_core::XrossResult },
                 quote! {
                     let #arg_id = if #arg_id.is_ok {
-                        Ok(unsafe { #ok_read })
+                        Ok(#ok_read)
                     } else {
-                        Err(unsafe { #err_read })
+                        Err(#err_read)
                     };
                 },
                 quote! { #arg_id },
@@ -276,12 +560,33 @@ pub fn gen_single_value_to_ptr(ty: &XrossType, val_ident: TokenStream) -> TokenS
         XrossType::String => {
             quote! { Box::into_raw(Box::new(xross_core::XrossString::from(#val_ident))) as *mut std::ffi::c_void }
         }
+        XrossType::Object { ownership, .. } if ownership.is_shared() => {
+            gen_shared_into_handle(val_ident)
+        }
         XrossType::Object { .. } => {
-            quote! { Box::into_raw(Box::new(#val_ident)) as *mut std::ffi::c_void }
+            gen_track_handle(quote! { Box::into_raw(Box::new(#val_ident)) })
+        }
+        XrossType::Option(inner) if inner.is_primitive() => {
+            quote! { Box::into_raw(Box::new(xross_core::XrossOption::from(#val_ident))) as *mut std::ffi::c_void }
+        }
+        XrossType::Option(inner) => {
+            let some_ptr_logic = gen_single_value_to_ptr(inner, quote! { v });
+            quote! {
+                match #val_ident {
+                    Some(v) => #some_ptr_logic,
+                    None => std::ptr::null_mut(),
+                }
+            }
+        }
+        XrossType::Vec(inner) => {
+            let array = gen_vec_to_array(inner, val_ident);
+            quote! { Box::into_raw(Box::new(#array)) as *mut std::ffi::c_void }
         }
-        XrossType::Option(_)
-        | XrossType::Result { .. }
-        | XrossType::Vec(_)
+        XrossType::Tuple(elems) => {
+            let repr = gen_tuple_to_repr(elems, val_ident);
+            quote! { Box::into_raw(Box::new(#repr)) as *mut std::ffi::c_void }
+        }
+        XrossType::Result { .. }
         | XrossType::VecDeque(_)
         | XrossType::LinkedList(_)
         | XrossType::HashSet(_)
@@ -298,6 +603,21 @@ pub fn gen_single_value_to_ptr(ty: &XrossType, val_ident: TokenStream) -> TokenS
     }
 }
 
+/// Converts an `Err` value into a failed `XrossResult` carrying an `XrossError`.
+/// Errors of object type are also handed over as the error's `value` handle.
+pub fn gen_err_to_result(err: &XrossType, e: TokenStream) -> TokenStream {
+    let value = match err {
+        XrossType::Object { .. } => gen_single_value_to_ptr(err, e.clone()),
+        _ => quote! { std::ptr::null_mut() },
+    };
+    quote! {
+        {
+            let (message, causes) = xross_core::xross_describe_err!(&#e);
+            xross_core::XrossError::from_err(message, causes, #value).into_result()
+        }
+    }
+}
+
 /// Helper to generate return value wrapping logic.
 pub fn gen_ret_wrapping(
     ret_ty: &XrossType,
@@ -313,69 +633,61 @@ pub fn gen_ret_wrapping(
         XrossType::Object { ownership, .. } => match ownership {
             Ownership::Ref | Ownership::MutRef => (
                 quote! { *mut std::ffi::c_void },
-                quote! { #inner_call as *const _ as *mut std::ffi::c_void },
+                gen_borrow_handle(quote! { #inner_call as *const _ as *mut std::ffi::c_void }),
             ),
             Ownership::Owned => (
                 quote! { *mut std::ffi::c_void },
-                quote! { Box::into_raw(Box::new(#inner_call)) as *mut std::ffi::c_void },
+                gen_track_handle(quote! { Box::into_raw(Box::new(#inner_call)) }),
             ),
             Ownership::Boxed => (
                 quote! { *mut std::ffi::c_void },
-                quote! { Box::into_raw(#inner_call) as *mut std::ffi::c_void },
+                gen_track_handle(quote! { Box::into_raw(#inner_call) }),
             ),
+            Ownership::Shared => {
+                let is_ref = matches!(sig_output, ReturnType::Type(_, ty) if matches!(**ty, Type::Reference(_)));
+                let val = if is_ref {
+                    quote! { std::sync::Arc::clone(#inner_call) }
+                } else {
+                    inner_call
+                };
+                (quote! { *mut std::ffi::c_void }, gen_shared_into_handle(val))
+            }
         },
+        XrossType::Option(inner) if inner.is_primitive() => {
+            let inner_rust_ty = primitive_rust_type(inner).unwrap();
+            (
+                quote! { xross_core::XrossOption<#inner_rust_ty> },
+                quote! { xross_core::XrossOption::from(#inner_call) },
+            )
+        }
         XrossType::Option(inner) => {
             let some_ptr_logic = gen_single_value_to_ptr(inner, quote! { val });
-            match &**inner {
-                XrossType::String
-                | XrossType::Object { .. }
-                | XrossType::Option(_)
-                | XrossType::Result { .. }
-                | XrossType::Vec(_)
-                | XrossType::VecDeque(_)
-                | XrossType::LinkedList(_)
-                | XrossType::HashSet(_)
-                | XrossType::BTreeSet(_)
-                | XrossType::BinaryHeap(_)
-                | XrossType::HashMap { .. }
-                | XrossType::BTreeMap { .. } => (
-                    quote! { *mut std::ffi::c_void },
-                    quote! {
-                        match #inner_call {
-                            Some(val) => #some_ptr_logic,
-                            None => std::ptr::null_mut(),
-                        }
-                    },
-                ),
-                XrossType::F32 | XrossType::F64 => (
-                    quote! { *mut std::ffi::c_void },
-                    quote! {
-                        match #inner_call {
-                            Some(val) => val.to_bits() as usize as *mut std::ffi::c_void,
-                            None => std::ptr::null_mut(),
-                        }
-                    },
-                ),
-                _ => (
-                    quote! { *mut std::ffi::c_void },
-                    quote! {
-                        match #inner_call {
-                            Some(val) => val as usize as *mut std::ffi::c_void,
-                            None => std::ptr::null_mut(),
-                        }
-                    },
-                ),
-            }
+            (
+                quote! { *mut std::ffi::c_void },
+                quote! {
+                    match #inner_call {
+                        Some(val) => #some_ptr_logic,
+                        None => std::ptr::null_mut(),
+                    }
+                },
+            )
+        }
+        XrossType::Vec(inner) => {
+            (quote! { xross_core::XrossArray }, gen_vec_to_array(inner, inner_call))
+        }
+        // The repr struct is local to the wrapper, so it is written through an untyped out pointer.
+        XrossType::Tuple(elems) => {
+            (quote! { std::ffi::c_void }, gen_tuple_to_repr(elems, inner_call))
         }
         XrossType::Result { ok, err } => {
             let ok_ptr_logic = gen_single_value_to_ptr(ok, quote! { val });
-            let err_ptr_logic = gen_single_value_to_ptr(err, quote! { e });
+            let err_result = gen_err_to_result(err, quote! { e });
             (
                 quote! { xross_core::XrossResult },
                 quote! {
                     match #inner_call {
                         Ok(val) => xross_core::XrossResult { is_ok: true, ptr: #ok_ptr_logic },
-                        Err(e) => xross_core::XrossResult { is_ok: false, ptr: #err_ptr_logic },
+                        Err(e) => #err_result,
                     }
                 },
             )
@@ -386,11 +698,14 @@ pub fn gen_ret_wrapping(
         | XrossType::BTreeSet(_)
         | XrossType::BinaryHeap(_)
         | XrossType::HashMap { .. }
-        | XrossType::BTreeMap { .. }
-        | XrossType::Vec(_) => (
+        | XrossType::BTreeMap { .. } => (
             quote! { *mut std::ffi::c_void },
             quote! { Box::into_raw(Box::new(#inner_call)) as *mut std::ffi::c_void },
         ),
+        XrossType::Iterator(_) => (
+            quote! { *mut std::ffi::c_void },
+            super::gen_iterator_to_handle(sig_output, inner_call),
+        ),
         _ => {
             if let ReturnType::Type(_, ty) = sig_output {
                 let type_str = quote!(#ty).to_string();
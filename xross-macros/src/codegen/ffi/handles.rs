@@ -0,0 +1,146 @@
+use crate::codegen::ffi::MethodFfiData;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use xross_metadata::{Ownership, XrossMethodType, XrossType};
+
+/// Whether exports validate handles against the registry of `xross-core`'s `checked-handles`.
+pub fn checked_handles() -> bool {
+    cfg!(feature = "checked-handles")
+}
+
+/// Whether exports count the handles of each type with `xross-core`'s `live-objects`.
+pub fn live_objects() -> bool {
+    cfg!(feature = "live-objects")
+}
+
+/// Turns a typed `*mut T` handed to the JVM into an owned `*mut c_void` handle,
+/// counting and registering it when the features are enabled.
+pub fn gen_track_handle(ptr: TokenStream) -> TokenStream {
+    let ptr = if live_objects() {
+        quote! { xross_core::xross_count_object!(#ptr) }
+    } else {
+        ptr
+    };
+    if !checked_handles() {
+        return quote! { (#ptr as *mut std::ffi::c_void) };
+    }
+    quote! { xross_core::xross_track_handle(#ptr) }
+}
+
+/// Uncounts a typed `*mut T` handle dropped or handed back to Rust by the JVM.
+pub fn gen_uncount_handle(ptr: TokenStream) -> TokenStream {
+    if !live_objects() {
+        return quote! {};
+    }
+    quote! { xross_core::xross_uncount_object!(#ptr); }
+}
+
+/// Implements `XrossObject` for a bridged type, keying its counts by `signature`.
+pub fn gen_object_impl(name: &syn::Ident, signature: &str) -> TokenStream {
+    if !live_objects() {
+        return quote! {};
+    }
+    quote! {
+        impl xross_core::XrossObject for #name {
+            const SIGNATURE: &'static str = #signature;
+        }
+    }
+}
+
+/// Passes on a borrowed `*mut c_void` handle, forgetting a stale object at its address.
+pub fn gen_borrow_handle(ptr: TokenStream) -> TokenStream {
+    if !checked_handles() {
+        return ptr;
+    }
+    quote! { xross_core::xross_borrow_handle(#ptr) }
+}
+
+/// What an export does with a handle it receives.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HandleUse {
+    /// Reads through it or takes a new strong reference; the JVM keeps the handle.
+    Borrow,
+    /// Drops the object or takes it over; the JVM gives up the handle.
+    Release,
+}
+
+/// Validates `handle`, a pointer to a `ty`, on entry to export `symbol`.
+/// With `std::ffi::c_void` as `ty`, the type the object was handed out as is not checked.
+///
+/// `on_error` receives the `XrossError` as `err` and must diverge. Without it, for exports
+/// that cannot return an error, the export records it for `xross_take_handle_error` and
+/// returns an all-zero value instead of running.
+pub fn gen_handle_guard(
+    handle: TokenStream,
+    ty: &TokenStream,
+    handle_use: HandleUse,
+    symbol: &str,
+    on_error: Option<&TokenStream>,
+) -> TokenStream {
+    if !checked_handles() {
+        return quote! {};
+    }
+    let call = match handle_use {
+        HandleUse::Borrow => quote! { xross_core::xross_check_handle },
+        HandleUse::Release => quote! { xross_core::xross_release_handle },
+    };
+    let result = quote! { #call::<#ty>(#handle as *const #ty, #symbol) };
+    match on_error {
+        Some(on_error) => quote! { if let Err(err) = #result { #on_error } },
+        None => quote! {
+            if let Err(err) = #result {
+                return unsafe { xross_core::xross_reject_handle(err) };
+            }
+        },
+    }
+}
+
+/// Validates the receiver and object arguments of a method export. All handles are checked
+/// before any is released, so a rejected call leaves the JVM's handles as they were.
+pub fn gen_method_handle_guards(
+    ffi_data: &MethodFfiData,
+    on_error: Option<&TokenStream>,
+) -> Vec<TokenStream> {
+    if !checked_handles() {
+        return Vec::new();
+    }
+    let mut handles = Vec::new();
+    match ffi_data.method_type {
+        XrossMethodType::Static => {}
+        XrossMethodType::OwnedInstance => handles.push(("_self", HandleUse::Release)),
+        _ => handles.push(("_self", HandleUse::Borrow)),
+    }
+    for arg in &ffi_data.args_meta {
+        let ownership = match &arg.ty {
+            XrossType::Object { ownership, .. } => ownership,
+            XrossType::Option(inner) => match &**inner {
+                XrossType::Object { ownership, .. } => ownership,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let handle_use = match ownership {
+            Ownership::Owned | Ownership::Boxed => HandleUse::Release,
+            _ => HandleUse::Borrow,
+        };
+        handles.push((arg.name.as_str(), handle_use));
+    }
+
+    let symbol = &ffi_data.symbol_name;
+    let guard = |name: &str, handle_use| {
+        let handle = format_ident!("{}", name);
+        let c_void = quote! { std::ffi::c_void };
+        let ty =
+            ffi_data.handle_types.iter().find(|(n, _)| n == name).map_or(&c_void, |(_, ty)| ty);
+        gen_handle_guard(quote! { #handle }, ty, handle_use, symbol, on_error)
+    };
+    let mut guards: Vec<_> =
+        handles.iter().map(|(name, _)| guard(name, HandleUse::Borrow)).collect();
+    guards.extend(
+        handles
+            .iter()
+            .filter(|(_, u)| *u == HandleUse::Release)
+            .map(|(name, _)| guard(name, HandleUse::Release)),
+    );
+    guards
+}
@@ -0,0 +1,140 @@
+use crate::codegen::ffi::{gen_single_value_to_ptr, primitive_rust_type};
+use crate::types::mapping::iterator_item_type;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{ReturnType, Type};
+use xross_metadata::{XrossIteratorAccessors, XrossType};
+
+/// Returns the symbols of the accessor exports generated for a returned iterator.
+pub fn iterator_accessors(symbol: &str, ret_ty: &XrossType) -> Option<Box<XrossIteratorAccessors>> {
+    let XrossType::Iterator(item) = ret_ty else { return None };
+    let name = |suffix: &str| format!("{}_ret_{}", symbol, suffix);
+    Some(Box::new(XrossIteratorAccessors {
+        next: name("next"),
+        size_hint: name("size_hint"),
+        next_chunk: item.is_primitive().then(|| name("next_chunk")),
+        drop: name("drop"),
+    }))
+}
+
+/// Rust type of the handle an iterator is boxed into.
+///
+/// The handle outlives the call and is released from the JVM cleaner thread, so the iterator
+/// must not borrow the receiver and must be `Send`.
+fn boxed_iterator_type(item_ty: &Type) -> TokenStream {
+    quote! { Box<dyn Iterator<Item = #item_ty> + Send + 'static> }
+}
+
+/// Boxes the iterator returned by `inner_call` into an opaque handle.
+pub fn gen_iterator_to_handle(sig_output: &ReturnType, inner_call: TokenStream) -> TokenStream {
+    let ReturnType::Type(_, rust_ty) = sig_output else { unreachable!() };
+    let item_ty = iterator_item_type(rust_ty).expect("iterator item type");
+    let boxed = boxed_iterator_type(&item_ty);
+    // 借用したイテレータはここで境界エラーとなり、戻り値の型を指す
+    let iter = quote_spanned! {rust_ty.span()=>
+        {
+            fn xross_owned_iterator<I: Iterator + Send + 'static>(iter: I) -> I { iter }
+            xross_owned_iterator(#inner_call)
+        }
+    };
+    quote! {
+        Box::into_raw(Box::new(Box::new(#iter) as #boxed)) as *mut std::ffi::c_void
+    }
+}
+
+/// FFI representation of an item moved out of an iterator.
+fn gen_item_repr(ty: &XrossType, val: TokenStream) -> (TokenStream, TokenStream) {
+    if let Some(prim) = primitive_rust_type(ty) {
+        return (prim, val);
+    }
+    match ty {
+        XrossType::Option(inner) if inner.is_primitive() => {
+            let prim = primitive_rust_type(inner).unwrap();
+            (
+                quote! { xross_core::XrossOption<#prim> },
+                quote! { xross_core::XrossOption::from(#val) },
+            )
+        }
+        XrossType::String => {
+            (quote! { xross_core::XrossString }, quote! { xross_core::XrossString::from(#val) })
+        }
+        _ => (quote! { *mut std::ffi::c_void }, gen_single_value_to_ptr(ty, val)),
+    }
+}
+
+/// Generates the accessor exports for an iterator returned by a method.
+pub fn generate_iterator_accessors(
+    symbol: &str,
+    ret_ty: &XrossType,
+    sig_output: &ReturnType,
+    toks: &mut Vec<TokenStream>,
+) {
+    let ReturnType::Type(_, rust_ty) = sig_output else { return };
+    let Some(accessors) = iterator_accessors(symbol, ret_ty) else { return };
+    let XrossType::Iterator(item) = ret_ty else { return };
+    let item_ty = iterator_item_type(rust_ty).expect("iterator item type");
+    let iter_ty = boxed_iterator_type(&item_ty);
+
+    let next_id = format_ident!("{}", accessors.next);
+    let size_hint_id = format_ident!("{}", accessors.size_hint);
+    let drop_id = format_ident!("{}", accessors.drop);
+    let (item_repr, item_conv) = gen_item_repr(item, quote! { v });
+
+    toks.push(quote! {
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #next_id(ptr: *mut std::ffi::c_void, out: *mut #item_repr) -> bool {
+            if ptr.is_null() || out.is_null() { return false; }
+            let iter = unsafe { &mut *(ptr as *mut #iter_ty) };
+            match iter.next() {
+                Some(v) => {
+                    unsafe { out.write_unaligned(#item_conv) };
+                    true
+                }
+                None => false,
+            }
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #size_hint_id(
+            ptr: *const std::ffi::c_void,
+            upper: *mut xross_core::XrossOption<usize>,
+        ) -> usize {
+            if ptr.is_null() { return 0; }
+            let iter = unsafe { &*(ptr as *const #iter_ty) };
+            let (lower, hi) = iter.size_hint();
+            if !upper.is_null() {
+                unsafe { upper.write_unaligned(xross_core::XrossOption::from(hi)) };
+            }
+            lower
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #drop_id(ptr: *mut std::ffi::c_void) {
+            if !ptr.is_null() {
+                drop(unsafe { Box::from_raw(ptr as *mut #iter_ty) });
+            }
+        }
+    });
+
+    if let Some(next_chunk) = &accessors.next_chunk {
+        let next_chunk_id = format_ident!("{}", next_chunk);
+        toks.push(quote! {
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn #next_chunk_id(
+                ptr: *mut std::ffi::c_void,
+                out: *mut #item_repr,
+                cap: usize,
+            ) -> usize {
+                if ptr.is_null() || out.is_null() { return 0; }
+                let iter = unsafe { &mut *(ptr as *mut #iter_ty) };
+                let mut written = 0;
+                for v in iter.by_ref().take(cap) {
+                    unsafe { out.add(written).write_unaligned(v) };
+                    written += 1;
+                }
+                written
+            }
+        });
+    }
+}
@@ -17,21 +17,26 @@ pub fn gen_field_layout_spec(
     }
 }
 
+/// Returns the member used to access a named or positional field.
+pub fn field_member(field: &syn::Field, index: usize) -> syn::Member {
+    match &field.ident {
+        Some(ident) => syn::Member::Named(ident.clone()),
+        None => syn::Member::Unnamed(syn::Index::from(index)),
+    }
+}
+
 /// Generates the layout metadata logic for a struct.
 pub fn generate_struct_layout(s: &syn::ItemStruct) -> TokenStream {
     let name = &s.ident;
     let mut field_parts = Vec::new();
-    if let syn::Fields::Named(fields) = &s.fields {
-        for field in &fields.named {
-            let f_name = field.ident.as_ref().unwrap();
-            let f_ty = &field.ty;
-            field_parts.push(gen_field_layout_spec(
-                name,
-                quote! { #f_name },
-                &f_name.to_string(),
-                f_ty,
-            ));
-        }
+    for (i, field) in s.fields.iter().enumerate() {
+        let member = field_member(field, i);
+        field_parts.push(gen_field_layout_spec(
+            name,
+            quote! { #member },
+            &crate::utils::field_name(&member),
+            &field.ty,
+        ));
     }
     quote! {
         let mut parts = vec![format!("{}", std::mem::size_of::<#name>() as u64)];
@@ -52,11 +57,7 @@ pub fn generate_enum_layout(e: &syn::ItemEnum) -> TokenStream {
             let mut fields_info = Vec::new();
             for (i, field) in v.fields.iter().enumerate() {
                 let f_ty = &field.ty;
-                let f_display_name = field
-                    .ident
-                    .as_ref()
-                    .map(|id| id.to_string())
-                    .unwrap_or_else(|| crate::utils::ordinal_name(i));
+                let f_display_name = crate::utils::nth_field_name(field.ident.as_ref(), i);
                 let f_access = if let Some(ident) = &field.ident {
                     quote! { #v_name . #ident }
                 } else {
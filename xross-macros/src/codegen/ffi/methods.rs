@@ -1,11 +1,12 @@
 use crate::codegen::ffi::{
-    gen_arg_conversion, gen_panic_error_arm, gen_receiver_logic, gen_ret_wrapping,
+    gen_arg_conversion, gen_method_handle_guards, gen_panic_error_arm, gen_receiver_logic,
+    gen_ret_wrapping, generate_collection_accessors, generate_iterator_accessors,
 };
-use crate::utils::extract_safety_attr;
+use crate::utils::{extract_base_type, extract_safety_attr};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use syn::punctuated::Punctuated;
-use syn::{FnArg, Pat, ReturnType};
+use syn::{FnArg, Pat, ReturnType, Type};
 use xross_metadata::{
     HandleMode, Ownership, ThreadSafety, XrossField, XrossMethod, XrossMethodType, XrossType,
 };
@@ -20,6 +21,8 @@ pub struct MethodFfiData {
     pub c_args: Vec<TokenStream>,
     pub call_args: Vec<TokenStream>,
     pub conversion_logic: Vec<TokenStream>,
+    /// Pointee type of the receiver (`_self`) and object argument handles, by argument name.
+    pub handle_types: Vec<(String, TokenStream)>,
 }
 
 impl MethodFfiData {
@@ -35,10 +38,20 @@ impl MethodFfiData {
             c_args: Vec::new(),
             call_args: Vec::new(),
             conversion_logic: Vec::new(),
+            handle_types: Vec::new(),
         }
     }
 }
 
+/// Returns true if the method is exported as a task: an `async fn`, or a `blocking` method.
+pub fn is_task_export(sig: &syn::Signature, handle_mode: HandleMode) -> bool {
+    let is_async = sig.asyncness.is_some();
+    if is_async && handle_mode == HandleMode::Blocking {
+        panic!("`blocking` cannot be used on async fn `{}`", sig.ident);
+    }
+    is_async || handle_mode == HandleMode::Blocking
+}
+
 /// Builds a full type signature.
 pub fn build_signature(package: &str, name: &str) -> String {
     if package.is_empty() { name.to_string() } else { format!("{}.{}", package, name) }
@@ -53,8 +66,19 @@ pub fn write_ffi_function(
     handle_mode: HandleMode,
     toks: &mut Vec<TokenStream>,
 ) {
+    generate_collection_accessors(&ffi_data.symbol_name, ret_ty, sig_output, toks);
+    generate_iterator_accessors(&ffi_data.symbol_name, ret_ty, sig_output, toks);
+
+    if let XrossType::Stream(item) = ret_ty {
+        if ffi_data.is_async {
+            panic!("`{}` must return its stream from a non-async fn", ffi_data.symbol_name);
+        }
+        write_stream_ffi_function(ffi_data, item, inner_call, toks);
+        return;
+    }
+
     if ffi_data.is_async {
-        write_async_ffi_function(ffi_data, ret_ty, sig_output, inner_call, toks);
+        write_async_ffi_function(ffi_data, ret_ty, sig_output, inner_call, handle_mode, toks);
         return;
     }
 
@@ -63,7 +87,8 @@ pub fn write_ffi_function(
     let c_args = &ffi_data.c_args;
     let conv_logic = &ffi_data.conversion_logic;
 
-    let is_complex_ret = matches!(ret_ty, XrossType::String);
+    let is_complex_ret =
+        matches!(ret_ty, XrossType::String | XrossType::Vec(_) | XrossType::Tuple(_));
 
     if handle_mode == HandleMode::Panicable {
         let is_already_result = matches!(ret_ty, XrossType::Result { .. });
@@ -87,7 +112,10 @@ pub fn write_ffi_function(
                 | XrossType::Bool => {
                     quote! { val as usize as *mut std::ffi::c_void }
                 }
-                XrossType::String => {
+                XrossType::String | XrossType::Vec(_) | XrossType::Tuple(_) => {
+                    quote! { Box::into_raw(Box::new(val)) as *mut std::ffi::c_void }
+                }
+                XrossType::Option(inner) if inner.is_primitive() => {
                     quote! { Box::into_raw(Box::new(val)) as *mut std::ffi::c_void }
                 }
                 _ => quote! { val as *mut std::ffi::c_void },
@@ -98,10 +126,18 @@ pub fn write_ffi_function(
         };
 
         let error_arm = gen_panic_error_arm("");
+        let null_checks = gen_null_handle_checks(ffi_data);
+        let handle_guards = gen_method_handle_guards(
+            ffi_data,
+            Some(&quote! {
+                unsafe { std::ptr::write(out, err.into_result()) };
+                return;
+            }),
+        );
         let panic_handling = quote! {
-            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+            let result = xross_core::xross_catch_panic(move || {
                 #wrapper_body
-            }));
+            });
 
             match result {
                 Ok(val) => {
@@ -114,24 +150,30 @@ pub fn write_ffi_function(
         toks.push(quote! {
             #[unsafe(no_mangle)]
             pub unsafe extern "C" fn #export_ident(out: *mut xross_core::XrossResult, #(#c_args),*) {
+                #(#null_checks)*
+                #(#handle_guards)*
                 #(#conv_logic)*
                 let res = { #panic_handling };
                 unsafe { std::ptr::write(out, res) };
             }
         });
     } else if is_complex_ret {
+        let handle_guards = gen_method_handle_guards(ffi_data, None);
         toks.push(quote! {
             #[unsafe(no_mangle)]
             pub unsafe extern "C" fn #export_ident(out: *mut #c_ret_type, #(#c_args),*) {
+                #(#handle_guards)*
                 #(#conv_logic)*
                 let val = #wrapper_body;
-                unsafe { std::ptr::write_unaligned(out, val) };
+                unsafe { std::ptr::write_unaligned(out.cast(), val) };
             }
         });
     } else {
+        let handle_guards = gen_method_handle_guards(ffi_data, None);
         toks.push(quote! {
             #[unsafe(no_mangle)]
             pub unsafe extern "C" fn #export_ident(#(#c_args),*) -> #c_ret_type {
+                #(#handle_guards)*
                 #(#conv_logic)*
                 #wrapper_body
             }
@@ -139,6 +181,42 @@ pub fn write_ffi_function(
     }
 }
 
+/// Rejects null receiver and object handles before they are dereferenced,
+/// writing the error to `out` and returning early.
+fn gen_null_handle_checks(ffi_data: &MethodFfiData) -> Vec<TokenStream> {
+    let reject = |handle: TokenStream, kind: TokenStream, msg: String| {
+        quote! {
+            if #handle.is_null() {
+                let err = xross_core::XrossError::new(xross_core::XrossErrorKind::#kind, #msg);
+                unsafe { std::ptr::write(out, err.into_result()) };
+                return;
+            }
+        }
+    };
+    let mut checks = Vec::new();
+    if !matches!(ffi_data.method_type, XrossMethodType::Static) {
+        checks.push(reject(
+            quote! { _self },
+            quote! { NullHandle },
+            format!("`{}` was called on a null handle", ffi_data.symbol_name),
+        ));
+    }
+    for arg in &ffi_data.args_meta {
+        let arg_ident = format_ident!("{}", arg.name);
+        let handle = match arg.ty {
+            XrossType::Object { .. } => quote! { #arg_ident },
+            XrossType::Function { .. } => quote! { #arg_ident.func },
+            _ => continue,
+        };
+        checks.push(reject(
+            handle,
+            quote! { InvalidArgument },
+            format!("argument `{}` of `{}` is null", arg.name, ffi_data.symbol_name),
+        ));
+    }
+    checks
+}
+
 /// Processes a list of function arguments.
 pub fn process_method_args(
     inputs: &Punctuated<FnArg, syn::token::Comma>,
@@ -151,6 +229,7 @@ pub fn process_method_args(
             FnArg::Receiver(receiver) => {
                 let (m_ty, c_arg, call_arg) = gen_receiver_logic(receiver, type_name_ident);
                 ffi_data.method_type = m_ty;
+                ffi_data.handle_types.push(("_self".into(), quote! { #type_name_ident }));
                 ffi_data.c_args.push(c_arg);
                 ffi_data.call_args.push(call_arg);
             }
@@ -168,6 +247,21 @@ pub fn process_method_args(
                     Some(type_name_ident),
                 );
 
+                let is_handle = match &xross_ty {
+                    XrossType::Option(inner) => matches!(**inner, XrossType::Object { .. }),
+                    ty => matches!(ty, XrossType::Object { .. }),
+                };
+                if is_handle {
+                    let base = extract_base_type(&pat_type.ty);
+                    // Trait objects are passed as proxies of any type.
+                    let pointee = match base {
+                        Type::Path(tp) if tp.path.is_ident("Self") => quote! { #type_name_ident },
+                        Type::TraitObject(_) => quote! { std::ffi::c_void },
+                        _ => quote! { #base },
+                    };
+                    ffi_data.handle_types.push((arg_name.clone(), pointee));
+                }
+
                 ffi_data.args_meta.push(XrossField {
                     name: arg_name.clone(),
                     ty: xross_ty.clone(),
@@ -206,6 +300,9 @@ pub fn add_clone_method(
             signature: build_signature(package, name),
             ownership: Ownership::Owned,
         },
+        ret_accessors: None,
+        ret_iterator: None,
+        notify_symbol: None,
         safety: ThreadSafety::Lock,
         docs: vec!["Creates a clone of the native object.".to_string()],
     });
@@ -222,38 +319,177 @@ pub fn add_drop_method(methods: &mut Vec<XrossMethod>, symbol_base: &str, handle
         is_async: false,
         args: vec![],
         ret: XrossType::Void,
+        ret_accessors: None,
+        ret_iterator: None,
+        notify_symbol: None,
         safety: ThreadSafety::Lock,
         docs: vec!["Drops the native object.".to_string()],
     });
 }
 
+/// Name of the `{symbol}_notify` export generated next to an async method.
+pub fn notify_symbol(symbol: &str, is_async: bool) -> Option<String> {
+    is_async.then(|| format!("{}_notify", symbol))
+}
+
+/// Generates a `fn(T) -> XrossResult` mapper for values produced by tasks and streams.
+fn gen_result_mapper(ty: &XrossType) -> TokenStream {
+    match ty {
+        XrossType::Void => {
+            quote! { |_| xross_core::XrossResult { is_ok: true, ptr: std::ptr::null_mut() } }
+        }
+        // Same Ok/Err split as the synchronous wrapper.
+        XrossType::Result { ok, err } => {
+            let ok_ptr_logic = super::conversion::gen_single_value_to_ptr(ok, quote! { val });
+            let err_result = super::conversion::gen_err_to_result(err, quote! { e });
+            quote! {
+                |res| match res {
+                    Ok(val) => xross_core::XrossResult { is_ok: true, ptr: #ok_ptr_logic },
+                    Err(e) => #err_result,
+                }
+            }
+        }
+        // A null `ptr` means "pending" to pollers, so `None` is boxed as a tagged option
+        // instead of being returned as null. Freed with `xross_free_option`.
+        XrossType::Option(inner) if !inner.is_primitive() => {
+            let some_ptr_logic = super::conversion::gen_single_value_to_ptr(inner, quote! { v });
+            quote! {
+                |val| {
+                    let opt = match val {
+                        Some(v) => xross_core::XrossOption { is_some: true, value: #some_ptr_logic },
+                        None => xross_core::XrossOption { is_some: false, value: std::ptr::null_mut() },
+                    };
+                    xross_core::XrossResult {
+                        is_ok: true,
+                        ptr: Box::into_raw(Box::new(opt)) as *mut std::ffi::c_void,
+                    }
+                }
+            }
+        }
+        _ => {
+            let ptr_logic = super::conversion::gen_single_value_to_ptr(ty, quote! { val });
+            quote! { |val| xross_core::XrossResult { is_ok: true, ptr: #ptr_logic } }
+        }
+    }
+}
+
 pub fn write_async_ffi_function(
     ffi_data: &MethodFfiData,
     ret_ty: &XrossType,
     _sig_output: &ReturnType,
     inner_call: TokenStream,
+    handle_mode: HandleMode,
     toks: &mut Vec<TokenStream>,
 ) {
     let export_ident = &ffi_data.export_ident;
     let c_args = &ffi_data.c_args;
     let conv_logic = &ffi_data.conversion_logic;
 
-    let res_mapper = match ret_ty {
-        XrossType::Void => {
-            quote! { |_| xross_core::XrossResult { is_ok: true, ptr: std::ptr::null_mut() } }
-        }
-        _ => {
-            let ptr_logic = super::conversion::gen_single_value_to_ptr(ret_ty, quote! { val });
-            quote! { |val| xross_core::XrossResult { is_ok: true, ptr: #ptr_logic } }
-        }
+    // The task may outlive the call, so it must not borrow an object the JVM can drop meanwhile.
+    if matches!(ffi_data.method_type, XrossMethodType::ConstInstance | XrossMethodType::MutInstance)
+    {
+        panic!(
+            "`{}` runs as a task and cannot borrow self; take `self: Arc<Self>` or `self` instead",
+            ffi_data.symbol_name
+        );
+    }
+
+    // Panics inside the future are always reported by the task. For panicable methods the
+    // argument conversion and the call creating the future are guarded as well, and a panic
+    // there is re-raised inside the task so it is reported the same way.
+    let (prologue, future) = if handle_mode == HandleMode::Blocking {
+        // Arguments are converted on the calling thread; only the call itself is offloaded.
+        let call = match syn::parse2::<syn::ExprCall>(inner_call.clone()) {
+            Ok(call) => call,
+            Err(_) => {
+                let msg = format!(
+                    "`{}` cannot be `blocking`: only plain function and method calls can be offloaded",
+                    ffi_data.symbol_name
+                );
+                toks.push(syn::Error::new_spanned(inner_call, msg).to_compile_error());
+                return;
+            }
+        };
+        let func = &call.func;
+        let args = call.args.iter();
+        let binds: Vec<_> = (0..call.args.len()).map(|i| format_ident!("__arg{}", i)).collect();
+        (
+            quote! {
+                #(#conv_logic)*
+                #(let #binds = #args;)*
+            },
+            quote! { xross_core::xross_run_blocking(move || #func(#(#binds),*)) },
+        )
+    } else if handle_mode == HandleMode::Panicable {
+        (
+            quote! {},
+            quote! {
+                {
+                    let future = xross_core::xross_catch_panic(move || {
+                        #(#conv_logic)*
+                        #inner_call
+                    });
+                    async move {
+                        match future {
+                            Ok(future) => future.await,
+                            Err(panic) => panic.resume(),
+                        }
+                    }
+                }
+            },
+        )
+    } else {
+        (quote! { #(#conv_logic)* }, inner_call)
     };
 
+    let res_mapper = gen_result_mapper(ret_ty);
+    let handle_guards = gen_method_handle_guards(ffi_data, None);
+
+    // `{symbol}_notify` pushes the result to a completion upcall instead of being polled.
+    let notify_ident = format_ident!("{}_notify", export_ident);
     toks.push(quote! {
         #[unsafe(no_mangle)]
         pub unsafe extern "C" fn #export_ident(out: *mut xross_core::XrossTask, #(#c_args),*) {
-            #(#conv_logic)*
-            let task = xross_core::xross_spawn_task(#inner_call, #res_mapper);
+            #(#handle_guards)*
+            #prologue
+            let task = xross_core::xross_spawn_task(#future, #res_mapper);
             unsafe { std::ptr::write(out, task) };
         }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #notify_ident(
+            out: *mut xross_core::XrossTask,
+            on_complete: xross_core::XrossCallback,
+            #(#c_args),*
+        ) {
+            #(#handle_guards)*
+            #prologue
+            let task = xross_core::xross_spawn_task_notify(#future, #res_mapper, on_complete);
+            unsafe { std::ptr::write(out, task) };
+        }
+    });
+}
+
+/// Generates an export returning an `XrossStream` for a method returning `impl Stream<Item = T>`.
+pub fn write_stream_ffi_function(
+    ffi_data: &MethodFfiData,
+    item_ty: &XrossType,
+    inner_call: TokenStream,
+    toks: &mut Vec<TokenStream>,
+) {
+    let export_ident = &ffi_data.export_ident;
+    let c_args = &ffi_data.c_args;
+    let conv_logic = &ffi_data.conversion_logic;
+    let item_mapper = gen_result_mapper(item_ty);
+    let handle_guards = gen_method_handle_guards(ffi_data, None);
+
+    toks.push(quote! {
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #export_ident(out: *mut xross_core::XrossStream, #(#c_args),*) {
+            #(#handle_guards)*
+            #(#conv_logic)*
+            let stream = xross_core::xross_spawn_stream(#inner_call, #item_mapper);
+            unsafe { std::ptr::write(out, stream) };
+        }
     });
 }
@@ -1,3 +1,4 @@
+use crate::codegen::ffi::{HandleUse, gen_handle_guard, gen_track_handle};
 use crate::utils::is_primitive_type;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
@@ -5,7 +6,7 @@ use xross_metadata::{Ownership, XrossType};
 
 pub fn generate_property_accessors(
     struct_name: &syn::Ident,
-    field_ident: &syn::Ident,
+    field_ident: &syn::Member,
     field_ty: &syn::Type,
     xross_ty: &XrossType,
     symbol_base: &str,
@@ -18,8 +19,9 @@ pub fn generate_property_accessors(
         _ => "",
     };
 
-    let getter_name = format!("{}_property_{}{}_get", symbol_base, field_ident, suffix);
-    let setter_name = format!("{}_property_{}{}_set", symbol_base, field_ident, suffix);
+    let field_name = crate::utils::field_name(field_ident);
+    let getter_name = format!("{}_property_{}{}_get", symbol_base, field_name, suffix);
+    let setter_name = format!("{}_property_{}{}_set", symbol_base, field_name, suffix);
     let getter_ident = format_ident!("{}", getter_name);
     let setter_ident = format_ident!("{}", setter_name);
 
@@ -42,11 +44,23 @@ pub fn generate_property_accessors(
                 },
             )
         }
+        XrossType::Object { ownership, .. } if ownership.is_shared() => {
+            setter_args.push(quote! { _val: *mut std::ffi::c_void });
+            let base = crate::utils::extract_base_type(field_ty);
+            let conv = crate::codegen::ffi::gen_shared_from_handle(base, quote! { _val });
+            (
+                quote! { *mut std::ffi::c_void },
+                crate::codegen::ffi::gen_shared_into_handle(
+                    quote! { std::sync::Arc::clone(&_self.#field_ident) },
+                ),
+                quote! { _self.#field_ident = #conv; },
+            )
+        }
         XrossType::Object { ownership, .. } => {
             setter_args.push(quote! { _val: *mut std::ffi::c_void });
             (
                 quote! { *mut std::ffi::c_void },
-                quote! { Box::into_raw(Box::new(_self.#field_ident.clone())) as *mut std::ffi::c_void },
+                gen_track_handle(quote! { Box::into_raw(Box::new(_self.#field_ident.clone())) }),
                 if *ownership == Ownership::Owned {
                     quote! { _self.#field_ident = unsafe { std::ptr::read(_val as *const _) }; }
                 } else {
@@ -54,6 +68,15 @@ pub fn generate_property_accessors(
                 },
             )
         }
+        XrossType::Option(inner) if inner.is_primitive() => {
+            let inner_rust_ty = crate::codegen::ffi::primitive_rust_type(inner).unwrap();
+            setter_args.push(quote! { _val: xross_core::XrossOption<#inner_rust_ty> });
+            (
+                quote! { xross_core::XrossOption<#inner_rust_ty> },
+                quote! { xross_core::XrossOption::from(_self.#field_ident) },
+                quote! { _self.#field_ident = _val.into_option(); },
+            )
+        }
         XrossType::Option(inner) => {
             setter_args.push(quote! { _val: *mut std::ffi::c_void });
             let ok_ptr_logic =
@@ -75,18 +98,48 @@ pub fn generate_property_accessors(
                 },
             )
         }
+        XrossType::Vec(inner) => {
+            let (c_arg, conv, call_arg) =
+                crate::codegen::ffi::gen_arg_conversion(field_ty, &format_ident!("_val"), xross_ty);
+            setter_args.push(c_arg);
+            let array =
+                crate::codegen::ffi::gen_vec_to_array(inner, quote! { _self.#field_ident.clone() });
+            (
+                quote! { xross_core::XrossArray },
+                array,
+                quote! {
+                    #conv
+                    _self.#field_ident = #call_arg;
+                },
+            )
+        }
+        XrossType::Tuple(elems) => {
+            let (c_arg, conv, call_arg) =
+                crate::codegen::ffi::gen_arg_conversion(field_ty, &format_ident!("_val"), xross_ty);
+            setter_args.push(c_arg);
+            (
+                quote! { std::ffi::c_void },
+                crate::codegen::ffi::gen_tuple_to_repr(
+                    elems,
+                    quote! { _self.#field_ident.clone() },
+                ),
+                quote! {
+                    #conv
+                    _self.#field_ident = #call_arg;
+                },
+            )
+        }
         XrossType::Result { ok, err } => {
             setter_args.push(quote! { _val: xross_core::XrossResult });
             let ok_ptr_logic =
                 crate::codegen::ffi::gen_single_value_to_ptr(ok, quote! { val.clone() });
-            let err_ptr_logic =
-                crate::codegen::ffi::gen_single_value_to_ptr(err, quote! { e.clone() });
+            let err_result = crate::codegen::ffi::gen_err_to_result(err, quote! { e.clone() });
             (
                 quote! { xross_core::XrossResult },
                 quote! {
                     match &_self.#field_ident {
                         Ok(val) => xross_core::XrossResult { is_ok: true, ptr: #ok_ptr_logic },
-                        Err(e) => xross_core::XrossResult { is_ok: false, ptr: #err_ptr_logic },
+                        Err(e) => #err_result,
                     }
                 },
                 quote! { /* Result setter is complex, simplified for now */ },
@@ -106,7 +159,9 @@ pub fn generate_property_accessors(
             if !is_primitive && !is_ptr_or_ref {
                 (
                     quote! { *mut std::ffi::c_void },
-                    quote! { Box::into_raw(Box::new(_self.#field_ident.clone())) as *mut std::ffi::c_void },
+                    gen_track_handle(
+                        quote! { Box::into_raw(Box::new(_self.#field_ident.clone())) },
+                    ),
                     quote! { _self.#field_ident = unsafe { std::ptr::read(_val as *const _) }; },
                 )
             } else {
@@ -119,17 +174,37 @@ pub fn generate_property_accessors(
         }
     };
 
-    extra_functions.push(quote! {
-        #[unsafe(no_mangle)]
-        pub unsafe extern "C" fn #getter_ident(ptr: *mut std::ffi::c_void) -> #ret_type {
-            let _self = unsafe { &*(ptr as *mut #struct_name) };
-            #get_body
-        }
-    });
+    let self_ty = quote! { #struct_name };
+    let getter_guard =
+        gen_handle_guard(quote! { ptr }, &self_ty, HandleUse::Borrow, &getter_name, None);
+    let setter_guard =
+        gen_handle_guard(quote! { ptr }, &self_ty, HandleUse::Borrow, &setter_name, None);
+
+    if matches!(xross_ty, XrossType::Tuple(_)) {
+        // The tuple repr has no nameable type, so it is written to a caller-provided buffer.
+        extra_functions.push(quote! {
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn #getter_ident(ptr: *mut std::ffi::c_void, out: *mut #ret_type) {
+                #getter_guard
+                let _self = unsafe { &*(ptr as *mut #struct_name) };
+                unsafe { std::ptr::write_unaligned(out.cast(), #get_body) };
+            }
+        });
+    } else {
+        extra_functions.push(quote! {
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn #getter_ident(ptr: *mut std::ffi::c_void) -> #ret_type {
+                #getter_guard
+                let _self = unsafe { &*(ptr as *mut #struct_name) };
+                #get_body
+            }
+        });
+    }
 
     extra_functions.push(quote! {
         #[unsafe(no_mangle)]
         pub unsafe extern "C" fn #setter_ident(#(#setter_args),*) {
+            #setter_guard
             let _self = unsafe { &mut *(ptr as *mut #struct_name) };
             #set_body
         }
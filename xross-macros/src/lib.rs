@@ -15,6 +15,15 @@ pub fn xross_class_derive(input: TokenStream) -> TokenStream {
     macros::derive::impl_xross_class_derive(input).into()
 }
 
+/// Derive macro for `XrossError`.
+/// Bridges an error struct or enum so that the JVM can throw one exception class per variant.
+/// The type must implement `std::error::Error`.
+#[proc_macro_derive(XrossError, attributes(xross_package, xross, error, source, from))]
+pub fn xross_error_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as Item);
+    macros::derive::impl_xross_error_derive(input).into()
+}
+
 /// Macro to define a class with its fields and methods in a DSL.
 #[proc_macro]
 pub fn xross_class(input: TokenStream) -> TokenStream {
@@ -28,6 +37,13 @@ pub fn xross_methods(attr: TokenStream, item: TokenStream) -> TokenStream {
     macros::attribute::impl_xross_class_attribute(attr.into(), input_impl).into()
 }
 
+/// Attribute macro for traits implemented on the JVM through a vtable.
+#[proc_macro_attribute]
+pub fn xross_trait(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input_trait = parse_macro_input!(item as syn::ItemTrait);
+    macros::attribute::impl_xross_trait_attribute(attr.into(), input_trait).into()
+}
+
 /// Attribute macro for standalone functions.
 #[proc_macro_attribute]
 pub fn xross_function(attr: TokenStream, item: TokenStream) -> TokenStream {
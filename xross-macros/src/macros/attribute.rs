@@ -1,5 +1,7 @@
 pub mod class;
 pub mod function;
+pub mod traits;
 
 pub use class::impl_xross_class_attribute;
 pub use function::impl_xross_function_attribute;
+pub use traits::impl_xross_trait_attribute;
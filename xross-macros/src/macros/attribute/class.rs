@@ -1,42 +1,110 @@
 use crate::codegen::ffi::{
-    MethodFfiData, build_signature, process_method_args, resolve_return_type, write_ffi_function,
+    MethodFfiData, build_signature, collection_accessors, is_task_export, iterator_accessors,
+    notify_symbol, process_method_args, resolve_return_type, write_ffi_function,
 };
-use crate::metadata::{load_definition, save_definition};
+use crate::metadata::{load_definition, load_instantiations, save_definition};
+use crate::types::generics::{GenericSubst, Instantiation};
 use crate::utils::*;
 use proc_macro2::TokenStream;
 use quote::quote;
+use syn::parse::Parser;
+use syn::visit_mut::VisitMut;
 use syn::{ImplItem, ItemImpl, Type};
-use xross_metadata::{Ownership, ThreadSafety, XrossDefinition, XrossMethod};
+use xross_metadata::{
+    HandleMode, Ownership, ThreadSafety, XrossDefaults, XrossDefinition, XrossMethod,
+};
 
-pub fn impl_xross_class_attribute(_attr: TokenStream, mut input_impl: ItemImpl) -> TokenStream {
+pub fn impl_xross_class_attribute(attr: TokenStream, mut input_impl: ItemImpl) -> TokenStream {
+    let package = parse_impl_package(attr.clone());
+    let impl_defaults = parse_impl_defaults(attr);
     let type_name_ident = if let Type::Path(tp) = &*input_impl.self_ty {
-        &tp.path.segments.last().unwrap().ident
+        tp.path.segments.last().unwrap().ident.clone()
     } else {
         panic!("xross_methods must be used on a direct type implementation");
     };
 
-    let mut definition = load_definition(type_name_ident).expect(
+    let mut extra_functions = Vec::new();
+
+    if input_impl.generics.type_params().next().is_some() {
+        // `impl<T> Buffer<T>` is expanded once per instantiation declared on the type.
+        let definitions = load_instantiations(&type_name_ident, package.as_deref());
+        if definitions.is_empty() {
+            panic!(
+                "No instantiations found for generic type `{}`. Add #[xross(instantiate(...))] to its definition.",
+                type_name_ident
+            );
+        }
+        let instantiations: Vec<Instantiation> = definitions
+            .iter()
+            .map(|def| {
+                let inst = def.instance_of().unwrap();
+                let args = inst.args.iter().map(|a| syn::parse_str::<Type>(a).unwrap());
+                Instantiation {
+                    concrete: syn::parse_quote!(#type_name_ident<#(#args),*>),
+                    alias: syn::Ident::new(def.name(), proc_macro2::Span::call_site()),
+                }
+            })
+            .collect();
+        for (mut definition, inst) in definitions.into_iter().zip(&instantiations) {
+            let mut mono = input_impl.clone();
+            GenericSubst::new(&input_impl.self_ty, &inst.type_args(), &instantiations)
+                .visit_item_impl_mut(&mut mono);
+            expand_methods(
+                &mut definition,
+                &inst.alias,
+                impl_defaults,
+                &mut mono,
+                &mut extra_functions,
+            );
+            save_definition(&definition);
+        }
+        // Only strips the xross attributes from the generic impl.
+        let mut discarded = Vec::new();
+        strip_method_attrs(
+            &mut input_impl,
+            (HandleMode::Normal, ThreadSafety::Lock),
+            &mut discarded,
+        );
+        return quote! { #(#extra_functions)* #input_impl };
+    }
+
+    let mut definition = load_definition(&type_name_ident).expect(
         "XrossClass definition not found. Apply #[derive(XrossClass)] or xross_class! first.",
     );
+    expand_methods(
+        &mut definition,
+        &type_name_ident,
+        impl_defaults,
+        &mut input_impl,
+        &mut extra_functions,
+    );
+    save_definition(&definition);
+    quote! { #(#extra_functions)* #input_impl }
+}
 
-    let (package_name, symbol_base) = match &definition {
-        XrossDefinition::Struct(s) => (s.package_name.clone(), s.symbol_prefix.clone()),
-        XrossDefinition::Enum(e) => (e.package_name.clone(), e.symbol_prefix.clone()),
-        XrossDefinition::Opaque(o) => (o.package_name.clone(), o.symbol_prefix.clone()),
-        XrossDefinition::Function(f) => (f.package_name.clone(), f.symbol.clone()),
-    };
-
-    let mut extra_functions = Vec::new();
-    let mut methods_meta = Vec::new();
+/// A method marked with `#[xross_new]`, `#[xross_default]` or `#[xross_method]`.
+struct MarkedMethod {
+    index: usize,
+    handle_mode: HandleMode,
+    safety: ThreadSafety,
+    is_new: bool,
+    is_default: bool,
+}
 
-    for item in &mut input_impl.items {
+/// Removes the xross method attributes, collecting the methods that carried one
+/// along with their effective handle mode and safety level.
+fn strip_method_attrs(
+    input_impl: &mut ItemImpl,
+    (default_mode, default_safety): (HandleMode, ThreadSafety),
+    marked: &mut Vec<MarkedMethod>,
+) {
+    for (index, item) in input_impl.items.iter_mut().enumerate() {
         if let ImplItem::Fn(method) = item {
+            let handle_mode = extract_handle_mode(&method.attrs, default_mode);
+            let safety = extract_safety_attr(&method.attrs, default_safety);
             let mut is_new = false;
             let mut is_default = false;
             let mut is_method = false;
-
-            let handle_mode = extract_handle_mode(&method.attrs);
-
             method.attrs.retain(|attr| {
                 if attr.path().is_ident("xross_new") {
                     is_new = true;
@@ -52,13 +120,41 @@ pub fn impl_xross_class_attribute(_attr: TokenStream, mut input_impl: ItemImpl)
                     true
                 }
             });
-
-            if !is_new && !is_method {
-                continue;
+            if is_new || is_method {
+                marked.push(MarkedMethod { index, handle_mode, safety, is_new, is_default });
             }
+        }
+    }
+}
 
+/// Generates the FFI exports for the marked methods of `input_impl` and records them in `definition`.
+fn expand_methods(
+    definition: &mut XrossDefinition,
+    type_name_ident: &syn::Ident,
+    impl_defaults: XrossDefaults,
+    input_impl: &mut ItemImpl,
+    extra_functions: &mut Vec<TokenStream>,
+) {
+    let (package_name, symbol_base) = match &*definition {
+        XrossDefinition::Struct(s) => (s.package_name.clone(), s.symbol_prefix.clone()),
+        XrossDefinition::Enum(e) => (e.package_name.clone(), e.symbol_prefix.clone()),
+        XrossDefinition::Opaque(o) => (o.package_name.clone(), o.symbol_prefix.clone()),
+        XrossDefinition::Function(f) => (f.package_name.clone(), f.symbol.clone()),
+        XrossDefinition::Trait(_) => panic!("xross_methods cannot be used on a trait definition."),
+        XrossDefinition::Error(_) => panic!("xross_methods cannot be used on an error definition."),
+    };
+
+    let mut methods_meta = Vec::new();
+    // A method's own attributes win over the impl block, then the type, then the crate.
+    let type_defaults = definition.defaults().copied().unwrap_or_default();
+    let defaults = effective_defaults(impl_defaults.or(type_defaults));
+    let mut marked = Vec::new();
+    strip_method_attrs(input_impl, defaults, &mut marked);
+
+    for MarkedMethod { index, handle_mode, safety, is_new, is_default } in marked {
+        if let ImplItem::Fn(method) = &input_impl.items[index] {
             let rust_fn_name = &method.sig.ident;
-            let is_async = method.sig.asyncness.is_some();
+            let is_async = is_task_export(&method.sig, handle_mode);
             let mut ffi_data = MethodFfiData::new(&symbol_base, rust_fn_name);
             ffi_data.is_async = is_async;
 
@@ -83,12 +179,15 @@ pub fn impl_xross_class_attribute(_attr: TokenStream, mut input_impl: ItemImpl)
                 symbol: ffi_data.symbol_name.clone(),
                 method_type: ffi_data.method_type,
                 handle_mode,
-                safety: extract_safety_attr(&method.attrs, ThreadSafety::Lock),
+                safety,
                 is_constructor: is_new,
                 is_default,
                 is_async,
                 args: ffi_data.args_meta.clone(),
                 ret: ret_ty.clone(),
+                ret_accessors: collection_accessors(&ffi_data.symbol_name, &ret_ty),
+                ret_iterator: iterator_accessors(&ffi_data.symbol_name, &ret_ty),
+                notify_symbol: notify_symbol(&ffi_data.symbol_name, is_async),
                 docs: extract_docs(&method.attrs),
             });
 
@@ -100,22 +199,48 @@ pub fn impl_xross_class_attribute(_attr: TokenStream, mut input_impl: ItemImpl)
                 &method.sig.output,
                 inner_call,
                 handle_mode,
-                &mut extra_functions,
+                extra_functions,
             );
         }
     }
 
-    match &mut definition {
+    match definition {
         XrossDefinition::Struct(s) => s.methods.extend(methods_meta),
         XrossDefinition::Enum(e) => e.methods.extend(methods_meta),
         XrossDefinition::Opaque(o) => o.methods.extend(methods_meta),
+        XrossDefinition::Trait(_) | XrossDefinition::Error(_) => unreachable!(),
         XrossDefinition::Function(_f) => {
             if !methods_meta.is_empty() {
                 panic!("Cannot add methods to a standalone function definition.");
             }
         }
     }
+}
 
-    save_definition(&definition);
-    quote! { #(#extra_functions)* #input_impl }
+/// Reads `package = "..."` from `#[xross_methods(...)]`, which selects the generic type
+/// when types with the same name are instantiated in different packages.
+fn parse_impl_package(attr: TokenStream) -> Option<String> {
+    let mut package = None;
+    let res = syn::meta::parser(|meta| {
+        if meta.path.is_ident("package") {
+            let value = meta.value()?;
+            if let Ok(lit) = value.parse::<syn::LitStr>() {
+                package = Some(lit.value());
+            } else {
+                package = Some(value.parse::<syn::Ident>()?.to_string());
+            }
+        } else if meta.input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in meta.input);
+            content.parse::<TokenStream>()?;
+        } else if meta.input.peek(syn::Token![=]) {
+            meta.value()?.parse::<syn::Expr>()?;
+        }
+        Ok(())
+    })
+    .parse2(attr);
+    if let Err(e) = res {
+        panic!("Failed to parse xross_methods attributes: {}", e);
+    }
+    package
 }
@@ -1,11 +1,11 @@
 use crate::codegen::ffi::{
-    MethodFfiData, process_method_args, resolve_return_type, write_ffi_function,
+    MethodFfiData, is_task_export, process_method_args, resolve_return_type, write_ffi_function,
 };
 use crate::utils::*;
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::parse::Parser;
-use xross_metadata::ThreadSafety;
+use xross_metadata::{ThreadSafety, XrossDefaults};
 
 pub fn impl_xross_function_attribute(attr: TokenStream, input_fn: syn::ItemFn) -> TokenStream {
     let mut package_name = String::new();
@@ -26,6 +26,8 @@ pub fn impl_xross_function_attribute(attr: TokenStream, input_fn: syn::ItemFn) -
                 handle_mode = Some(xross_metadata::HandleMode::Critical { allow_heap_access });
             } else if meta.path.is_ident("panicable") {
                 handle_mode = Some(xross_metadata::HandleMode::Panicable);
+            } else if meta.path.is_ident("blocking") {
+                handle_mode = Some(xross_metadata::HandleMode::Blocking);
             } else if meta.path.is_ident("safety") {
                 let value = meta.value()?.parse::<syn::Ident>()?;
                 safety = match value.to_string().as_str() {
@@ -46,7 +48,10 @@ pub fn impl_xross_function_attribute(attr: TokenStream, input_fn: syn::ItemFn) -
 
     let rust_fn_name = &input_fn.sig.ident;
     let name_str = rust_fn_name.to_string();
-    let is_async = input_fn.sig.asyncness.is_some();
+    let (default_mode, default_safety) = effective_defaults(XrossDefaults::default());
+    let handle_mode =
+        handle_mode.unwrap_or_else(|| extract_handle_mode(&input_fn.attrs, default_mode));
+    let is_async = is_task_export(&input_fn.sig, handle_mode);
 
     let symbol_prefix = crate::utils::get_symbol_prefix(&package_name);
 
@@ -59,8 +64,7 @@ pub fn impl_xross_function_attribute(attr: TokenStream, input_fn: syn::ItemFn) -
     let ret_ty =
         resolve_return_type(&input_fn.sig.output, &input_fn.attrs, &package_name, &dummy_ident);
 
-    let handle_mode = handle_mode.unwrap_or_else(|| extract_handle_mode(&input_fn.attrs));
-    let safety = safety.unwrap_or_else(|| extract_safety_attr(&input_fn.attrs, ThreadSafety::Lock));
+    let safety = safety.unwrap_or_else(|| extract_safety_attr(&input_fn.attrs, default_safety));
     let docs = extract_docs(&input_fn.attrs);
 
     crate::utils::register_xross_function(
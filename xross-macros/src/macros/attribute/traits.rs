@@ -0,0 +1,195 @@
+use crate::codegen::ffi::{
+    HandleUse, build_signature, gen_handle_guard, gen_track_handle, gen_upcall_arg,
+    resolve_return_type, upcall_ret_type,
+};
+use crate::metadata::save_definition;
+use crate::types::resolver::resolve_type_with_attr;
+use crate::utils::*;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::Parser;
+use syn::{FnArg, Pat, TraitItem};
+use xross_metadata::{
+    HandleMode, ThreadSafety, XrossDefinition, XrossField, XrossMethod, XrossMethodType, XrossTrait,
+};
+
+pub fn impl_xross_trait_attribute(attr: TokenStream, input_trait: syn::ItemTrait) -> TokenStream {
+    let mut package_name = String::new();
+    if !attr.is_empty() {
+        let res = syn::meta::parser(|meta| {
+            if meta.path.is_ident("package") {
+                let value = meta.value()?;
+                if let Ok(lit) = value.parse::<syn::LitStr>() {
+                    package_name = lit.value();
+                } else if let Ok(id) = value.parse::<syn::Ident>() {
+                    package_name = id.to_string();
+                }
+            }
+            Ok(())
+        })
+        .parse2(attr);
+        if let Err(e) = res {
+            panic!("Failed to parse xross_trait attributes: {}", e);
+        }
+    }
+
+    let trait_ident = &input_trait.ident;
+    let name_str = trait_ident.to_string();
+    let vis = &input_trait.vis;
+    let crate_name = std::env::var("CARGO_PKG_NAME")
+        .unwrap_or_else(|_| "unknown_crate".to_string())
+        .replace("-", "_");
+    let symbol_base = build_symbol_base(&crate_name, &package_name, &name_str);
+
+    let vtable_ident = format_ident!("Xross{}VTable", trait_ident);
+    let proxy_ident = format_ident!("Xross{}Proxy", trait_ident);
+    let new_ident = format_ident!("{}_new", symbol_base);
+    let drop_ident = format_ident!("{}_drop", symbol_base);
+
+    let mut methods = Vec::new();
+    let mut slots = Vec::new();
+    let mut impls = Vec::new();
+
+    for item in &input_trait.items {
+        let TraitItem::Fn(method) = item else { continue };
+        let sig = &method.sig;
+        let fn_ident = &sig.ident;
+
+        let receiver = match sig.inputs.first() {
+            Some(FnArg::Receiver(r)) if r.reference.is_some() => r,
+            _ => panic!("xross_trait method `{}` must take &self or &mut self", fn_ident),
+        };
+        let method_type = if receiver.mutability.is_some() {
+            XrossMethodType::MutInstance
+        } else {
+            XrossMethodType::ConstInstance
+        };
+
+        let mut args_meta = Vec::new();
+        let mut params = Vec::new();
+        let mut ffi_tys = Vec::new();
+        let mut ffi_vals = Vec::new();
+        for (i, input) in sig.inputs.iter().skip(1).enumerate() {
+            let FnArg::Typed(pat_type) = input else { unreachable!() };
+            let param = format_ident!("a{}", i);
+            let name = match &*pat_type.pat {
+                Pat::Ident(id) => id.ident.to_string(),
+                _ => param.to_string(),
+            };
+            let ty = &pat_type.ty;
+            let xross_ty =
+                resolve_type_with_attr(ty, &pat_type.attrs, &package_name, Some(trait_ident));
+            let (ffi_ty, ffi_val) = gen_upcall_arg(&xross_ty, ty, &param);
+
+            args_meta.push(XrossField {
+                name,
+                ty: xross_ty,
+                safety: extract_safety_attr(&pat_type.attrs, ThreadSafety::Lock),
+                docs: vec![],
+            });
+            params.push(quote! { #param: #ty });
+            ffi_tys.push(ffi_ty);
+            ffi_vals.push(ffi_val);
+        }
+
+        let ret_ty = resolve_return_type(&sig.output, &method.attrs, &package_name, trait_ident);
+        let ffi_ret = upcall_ret_type(&ret_ty).unwrap_or_else(|| {
+            panic!("Unsupported return type for xross_trait method `{}`", fn_ident)
+        });
+        let output = &sig.output;
+
+        slots.push(quote! {
+            pub #fn_ident: unsafe extern "C" fn(*mut std::ffi::c_void #(, #ffi_tys)*) -> #ffi_ret
+        });
+        impls.push(quote! {
+            fn #fn_ident(#receiver, #(#params),*) #output {
+                unsafe { (self.vtable.#fn_ident)(self.vtable.ctx #(, #ffi_vals)*) }
+            }
+        });
+        methods.push(XrossMethod {
+            name: fn_ident.to_string(),
+            symbol: fn_ident.to_string(),
+            method_type,
+            handle_mode: HandleMode::Normal,
+            safety: extract_safety_attr(&method.attrs, ThreadSafety::Lock),
+            is_constructor: false,
+            is_default: false,
+            is_async: false,
+            args: args_meta,
+            ret: ret_ty,
+            ret_accessors: None,
+            ret_iterator: None,
+            notify_symbol: None,
+            docs: extract_docs(&method.attrs),
+        });
+    }
+
+    // Proxies are owned handles, consumed by `Box<dyn Trait>` arguments or dropped by the JVM.
+    let proxy_handle =
+        gen_track_handle(quote! { Box::into_raw(Box::new(#proxy_ident { vtable })) });
+    let drop_guard = gen_handle_guard(
+        quote! { ptr },
+        &quote! { #proxy_ident },
+        HandleUse::Release,
+        &drop_ident.to_string(),
+        None,
+    );
+
+    save_definition(&XrossDefinition::Trait(XrossTrait {
+        signature: build_signature(&package_name, &name_str),
+        symbol_prefix: symbol_base,
+        package_name,
+        name: name_str,
+        methods,
+        docs: extract_docs(&input_trait.attrs),
+    }));
+
+    quote! {
+        #input_trait
+
+        /// Function table filled by the JVM to implement the trait.
+        #[repr(C)]
+        #vis struct #vtable_ident {
+            pub ctx: *mut std::ffi::c_void,
+            pub release: Option<unsafe extern "C" fn(*mut std::ffi::c_void)>,
+            #(#slots,)*
+        }
+
+        /// Implements the trait by calling through a JVM-provided vtable.
+        #vis struct #proxy_ident {
+            vtable: #vtable_ident,
+        }
+
+        unsafe impl Send for #proxy_ident {}
+        unsafe impl Sync for #proxy_ident {}
+
+        impl Drop for #proxy_ident {
+            fn drop(&mut self) {
+                if let Some(release) = self.vtable.release {
+                    unsafe { release(self.vtable.ctx) };
+                }
+            }
+        }
+
+        impl #trait_ident for #proxy_ident {
+            #(#impls)*
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #new_ident(vtable: *const #vtable_ident) -> *mut std::ffi::c_void {
+            if vtable.is_null() {
+                return std::ptr::null_mut();
+            }
+            let vtable = unsafe { std::ptr::read(vtable) };
+            #proxy_handle
+        }
+
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #drop_ident(ptr: *mut std::ffi::c_void) {
+            #drop_guard
+            if !ptr.is_null() {
+                drop(unsafe { Box::from_raw(ptr as *mut #proxy_ident) });
+            }
+        }
+    }
+}
@@ -1,8 +1,13 @@
 pub mod enum_impl;
+pub mod error_impl;
 pub mod struct_impl;
 
+use crate::types::generics::{GenericSubst, Instantiation, extract_instantiations};
 use proc_macro2::TokenStream;
+use quote::quote;
 use syn::Item;
+use syn::visit_mut::VisitMut;
+use xross_metadata::XrossInstantiation;
 
 pub fn impl_xross_class_derive(input: Item) -> TokenStream {
     let crate_name = std::env::var("CARGO_PKG_NAME")
@@ -12,8 +17,51 @@ pub fn impl_xross_class_derive(input: Item) -> TokenStream {
     let mut extra_functions = Vec::new();
 
     let derive_toks = match input {
-        Item::Struct(s) => struct_impl::impl_struct_derive(&s, &crate_name, &mut extra_functions),
-        Item::Enum(e) => enum_impl::impl_enum_derive(&e, &crate_name, &mut extra_functions),
+        Item::Struct(s) if s.generics.type_params().next().is_some() => {
+            let instantiations = require_instantiations(&s.ident, &s.attrs);
+            let mut toks = Vec::new();
+            for inst in &instantiations {
+                let mut mono = s.clone();
+                GenericSubst::for_declaration(&s.ident, &s.generics, inst, &instantiations)
+                    .visit_fields_mut(&mut mono.fields);
+                mono.ident = inst.alias.clone();
+                mono.generics = Default::default();
+                toks.push(alias_decl(&s.vis, inst));
+                toks.push(struct_impl::impl_struct_derive(
+                    &mono,
+                    &crate_name,
+                    Some(instance_of(&s.ident, inst)),
+                    &mut extra_functions,
+                ));
+            }
+            quote! { #(#toks)* }
+        }
+        Item::Enum(e) if e.generics.type_params().next().is_some() => {
+            let instantiations = require_instantiations(&e.ident, &e.attrs);
+            let mut toks = Vec::new();
+            for inst in &instantiations {
+                let mut mono = e.clone();
+                let mut subst =
+                    GenericSubst::for_declaration(&e.ident, &e.generics, inst, &instantiations);
+                for variant in &mut mono.variants {
+                    subst.visit_fields_mut(&mut variant.fields);
+                }
+                mono.ident = inst.alias.clone();
+                mono.generics = Default::default();
+                toks.push(alias_decl(&e.vis, inst));
+                toks.push(enum_impl::impl_enum_derive(
+                    &mono,
+                    &crate_name,
+                    Some(instance_of(&e.ident, inst)),
+                    &mut extra_functions,
+                ));
+            }
+            quote! { #(#toks)* }
+        }
+        Item::Struct(s) => {
+            struct_impl::impl_struct_derive(&s, &crate_name, None, &mut extra_functions)
+        }
+        Item::Enum(e) => enum_impl::impl_enum_derive(&e, &crate_name, None, &mut extra_functions),
         _ => panic!("#[derive(XrossClass)] only supports Struct and Enum"),
     };
 
@@ -22,3 +70,35 @@ pub fn impl_xross_class_derive(input: Item) -> TokenStream {
         #(#extra_functions)*
     }
 }
+
+pub fn impl_xross_error_derive(input: Item) -> TokenStream {
+    let crate_name = std::env::var("CARGO_PKG_NAME")
+        .unwrap_or_else(|_| "unknown_crate".to_string())
+        .replace("-", "_");
+    error_impl::impl_error_derive(&input, &crate_name)
+}
+
+/// Generic types are bridged once per `#[xross(instantiate(...))]` entry.
+fn require_instantiations(ident: &syn::Ident, attrs: &[syn::Attribute]) -> Vec<Instantiation> {
+    let instantiations = extract_instantiations(attrs);
+    if instantiations.is_empty() {
+        panic!(
+            "Generic type `{}` requires #[xross(instantiate({}<..> as Alias, ...))]",
+            ident, ident
+        );
+    }
+    instantiations
+}
+
+/// Declares the alias that names the instantiation in generated code.
+fn alias_decl(vis: &syn::Visibility, inst: &Instantiation) -> TokenStream {
+    let Instantiation { concrete, alias } = inst;
+    quote! { #vis type #alias = #concrete; }
+}
+
+fn instance_of(ident: &syn::Ident, inst: &Instantiation) -> XrossInstantiation {
+    XrossInstantiation {
+        generic: ident.to_string(),
+        args: inst.type_args().iter().map(|ty| quote!(#ty).to_string()).collect(),
+    }
+}
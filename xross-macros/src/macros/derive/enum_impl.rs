@@ -1,17 +1,18 @@
 use crate::codegen::ffi::{
-    add_clone_method, add_drop_method, generate_common_ffi, generate_enum_aux_ffi,
-    generate_enum_layout,
+    add_clone_method, add_drop_method, build_signature, gen_object_impl, generate_common_ffi,
+    generate_enum_aux_ffi, generate_enum_layout,
 };
 use crate::metadata::save_definition;
 use crate::types::resolver::resolve_type_with_attr;
 use crate::utils::*;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use xross_metadata::{ThreadSafety, XrossDefinition, XrossEnum, XrossField, XrossVariant};
+use xross_metadata::{XrossDefinition, XrossEnum, XrossField, XrossInstantiation, XrossVariant};
 
 pub fn impl_enum_derive(
     e: &syn::ItemEnum,
     crate_name: &str,
+    instance_of: Option<XrossInstantiation>,
     extra_functions: &mut Vec<TokenStream>,
 ) -> TokenStream {
     let name = &e.ident;
@@ -21,7 +22,10 @@ pub fn impl_enum_derive(
 
     let layout_logic = generate_enum_layout(e);
     let is_clonable = extract_is_clonable(&e.attrs);
+    let is_shared = extract_is_shared(&e.attrs);
     let (clone_mode, drop_mode) = extract_special_modes(&e.attrs);
+    let defaults = extract_type_defaults(&e.attrs);
+    let (_, default_safety) = effective_defaults(defaults.unwrap_or_default());
 
     let mut variants = Vec::new();
     let mut methods = Vec::new();
@@ -43,14 +47,13 @@ pub fn impl_enum_derive(
         let mut call_args = Vec::new();
 
         for (i, field) in v.fields.iter().enumerate() {
-            let field_name =
-                field.ident.as_ref().map(|id| id.to_string()).unwrap_or_else(|| ordinal_name(i));
+            let field_name = nth_field_name(field.ident.as_ref(), i);
             let ty = resolve_type_with_attr(&field.ty, &field.attrs, &package, Some(name));
 
             v_fields.push(XrossField {
                 name: field_name,
                 ty: ty.clone(),
-                safety: ThreadSafety::Lock,
+                safety: default_safety,
                 docs: extract_docs(&field.attrs),
             });
 
@@ -78,23 +81,23 @@ pub fn impl_enum_derive(
             quote! { #name::#v_ident(#(#call_args),*) }
         };
 
+        let value = crate::codegen::ffi::gen_track_handle(
+            quote! { Box::into_raw(Box::new(#enum_construct)) },
+        );
         extra_functions.push(quote! {
             #[unsafe(no_mangle)]
             pub unsafe extern "C" fn #constructor_name(#(#c_param_defs),*) -> *mut #name {
                 #(#internal_conversions)*
-                Box::into_raw(Box::new(#enum_construct))
+                #value as *mut #name
             }
         });
 
         variants.push(XrossVariant { name: v_str, fields: v_fields, docs: extract_docs(&v.attrs) });
     }
 
+    let signature = build_signature(&package, &name_str);
     save_definition(&XrossDefinition::Enum(XrossEnum {
-        signature: if package.is_empty() {
-            name_str.clone()
-        } else {
-            format!("{}.{}", package, name_str)
-        },
+        signature: signature.clone(),
         symbol_prefix: symbol_base.clone(),
         package_name: package,
         name: name_str,
@@ -102,6 +105,9 @@ pub fn impl_enum_derive(
         methods,
         docs: extract_docs(&e.attrs),
         is_copy: extract_is_copy(&e.attrs),
+        is_shared,
+        instance_of,
+        defaults,
     }));
 
     let mut toks = Vec::new();
@@ -111,9 +117,11 @@ pub fn impl_enum_derive(
         layout_logic,
         &mut toks,
         is_clonable,
+        is_shared,
         clone_mode,
         drop_mode,
     );
+    toks.push(gen_object_impl(name, &signature));
 
     generate_enum_aux_ffi(name, &symbol_base, variant_name_arms, &mut toks);
     quote!(#(#toks)*)
@@ -0,0 +1,141 @@
+use crate::codegen::ffi::{
+    build_signature, gen_object_impl, generate_common_ffi, generate_enum_layout,
+    generate_struct_layout,
+};
+use crate::metadata::save_definition;
+use crate::types::resolver::resolve_type_with_attr;
+use crate::utils::*;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Fields, Item};
+use xross_metadata::{ThreadSafety, XrossDefinition, XrossErrorDef, XrossErrorVariant, XrossField};
+
+pub fn impl_error_derive(input: &Item, crate_name: &str) -> TokenStream {
+    let (name, attrs, generics) = match input {
+        Item::Struct(s) => (&s.ident, &s.attrs, &s.generics),
+        Item::Enum(e) => (&e.ident, &e.attrs, &e.generics),
+        _ => panic!("#[derive(XrossError)] only supports Struct and Enum"),
+    };
+    if generics.type_params().next().is_some() {
+        panic!("#[derive(XrossError)] does not support generic type `{}`", name);
+    }
+    let name_str = name.to_string();
+    let package = extract_package(attrs);
+    let symbol_base = build_symbol_base(crate_name, &package, &name_str);
+    let is_clonable = extract_is_clonable(attrs);
+    let (clone_mode, drop_mode) = extract_special_modes(attrs);
+
+    let (layout_logic, variants, variant_arms) = match input {
+        Item::Struct(s) => {
+            let variant = error_variant(&name_str, attrs, &s.fields, &package, name);
+            (generate_struct_layout(s), vec![variant], quote! { _ => 0 })
+        }
+        Item::Enum(e) => {
+            let arms = e.variants.iter().enumerate().map(|(i, v)| {
+                let v_ident = &v.ident;
+                let index = i as u32;
+                quote! { #name::#v_ident { .. } => #index }
+            });
+            let variants = e
+                .variants
+                .iter()
+                .map(|v| error_variant(&v.ident.to_string(), &v.attrs, &v.fields, &package, name))
+                .collect();
+            (generate_enum_layout(e), variants, quote! { #(#arms),* })
+        }
+        _ => unreachable!(),
+    };
+
+    let signature = build_signature(&package, &name_str);
+    save_definition(&XrossDefinition::Error(XrossErrorDef {
+        signature: signature.clone(),
+        symbol_prefix: symbol_base.clone(),
+        package_name: package,
+        name: name_str,
+        variants,
+        docs: extract_docs(attrs),
+    }));
+
+    let mut toks = Vec::new();
+    generate_common_ffi(
+        name,
+        &symbol_base,
+        layout_logic,
+        &mut toks,
+        is_clonable,
+        false,
+        clone_mode,
+        drop_mode,
+    );
+    toks.push(gen_object_impl(name, &signature));
+
+    // The `&dyn Error` coercion also requires the type to implement `std::error::Error`.
+    // Returns false without writing `out` when either pointer is null.
+    let describe_id = format_ident!("{}_describe", symbol_base);
+    let guard = crate::codegen::ffi::gen_handle_guard(
+        quote! { ptr },
+        &quote! { #name },
+        crate::codegen::ffi::HandleUse::Borrow,
+        &describe_id.to_string(),
+        None,
+    );
+    toks.push(quote! {
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn #describe_id(out: *mut xross_core::XrossErrorInfo, ptr: *const #name) -> bool {
+            if out.is_null() || ptr.is_null() { return false; }
+            #guard
+            let err = unsafe { &*ptr };
+            let variant = match err { #variant_arms };
+            let info = xross_core::XrossErrorInfo::new(variant, err);
+            unsafe { std::ptr::write_unaligned(out, info) };
+            true
+        }
+    });
+    quote!(#(#toks)*)
+}
+
+fn error_variant(
+    name: &str,
+    attrs: &[Attribute],
+    fields: &Fields,
+    package: &str,
+    type_ident: &syn::Ident,
+) -> XrossErrorVariant {
+    let mut source = None;
+    let mut xross_fields = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let field_name = nth_field_name(field.ident.as_ref(), i);
+        // The underlying error is reported through the `source()` chain instead of as a field.
+        let is_source = field_name == "source"
+            || field.attrs.iter().any(|a| a.path().is_ident("source") || a.path().is_ident("from"));
+        if is_source {
+            source = Some(field_name);
+            continue;
+        }
+        xross_fields.push(XrossField {
+            name: field_name,
+            ty: resolve_type_with_attr(&field.ty, &field.attrs, package, Some(type_ident)),
+            safety: ThreadSafety::Lock,
+            docs: extract_docs(&field.attrs),
+        });
+    }
+    XrossErrorVariant {
+        name: name.to_string(),
+        message: extract_error_message(attrs),
+        fields: xross_fields,
+        source,
+        docs: extract_docs(attrs),
+    }
+}
+
+/// Reads the message template of `#[error("...")]`; `#[error(transparent)]` has none.
+fn extract_error_message(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().filter(|a| a.path().is_ident("error")).find_map(|a| {
+        a.parse_args_with(|input: syn::parse::ParseStream| {
+            let lit: syn::LitStr = input.parse()?;
+            let _: TokenStream = input.parse()?;
+            Ok(lit.value())
+        })
+        .ok()
+    })
+}
@@ -1,16 +1,17 @@
 use crate::codegen::ffi::{
-    add_clone_method, add_drop_method, generate_common_ffi, generate_property_accessors,
-    generate_struct_layout,
+    add_clone_method, add_drop_method, build_signature, field_member, gen_object_impl,
+    generate_common_ffi, generate_property_accessors, generate_struct_layout,
 };
 use crate::metadata::save_definition;
 use crate::types::resolver::resolve_type_with_attr;
 use crate::utils::*;
 use proc_macro2::TokenStream;
-use xross_metadata::{ThreadSafety, XrossDefinition, XrossField, XrossStruct};
+use xross_metadata::{XrossDefinition, XrossField, XrossInstantiation, XrossStruct};
 
 pub fn impl_struct_derive(
     s: &syn::ItemStruct,
     crate_name: &str,
+    instance_of: Option<XrossInstantiation>,
     extra_functions: &mut Vec<TokenStream>,
 ) -> TokenStream {
     let name = &s.ident;
@@ -20,7 +21,10 @@ pub fn impl_struct_derive(
 
     let layout_logic = generate_struct_layout(s);
     let is_clonable = extract_is_clonable(&s.attrs);
+    let is_shared = extract_is_shared(&s.attrs);
     let (clone_mode, drop_mode) = extract_special_modes(&s.attrs);
+    let defaults = extract_type_defaults(&s.attrs);
+    let (_, default_safety) = effective_defaults(defaults.unwrap_or_default());
 
     let mut fields = Vec::new();
     let mut methods = Vec::new();
@@ -30,42 +34,36 @@ pub fn impl_struct_derive(
     }
     add_drop_method(&mut methods, &symbol_base, drop_mode);
 
-    if let syn::Fields::Named(f) = &s.fields {
-        for field in &f.named {
-            let has_attr = field.attrs.iter().any(|a| {
-                a.path().is_ident("xross_field")
-                    || a.path().segments.last().map(|s| s.ident == "xross_field").unwrap_or(false)
-            });
+    // Positional fields of tuple structs are named by their ordinal, as in enum variants.
+    for (i, field) in s.fields.iter().enumerate() {
+        let has_attr = field.attrs.iter().any(|a| {
+            a.path().is_ident("xross_field")
+                || a.path().segments.last().map(|s| s.ident == "xross_field").unwrap_or(false)
+        });
 
-            if has_attr {
-                let field_ident = field.ident.as_ref().unwrap();
-                let field_name = field_ident.to_string();
-                let xross_ty =
-                    resolve_type_with_attr(&field.ty, &field.attrs, &package, Some(name));
-                fields.push(XrossField {
-                    name: field_name.clone(),
-                    ty: xross_ty.clone(),
-                    safety: extract_safety_attr(&field.attrs, ThreadSafety::Lock),
-                    docs: extract_docs(&field.attrs),
-                });
+        if has_attr {
+            let member = field_member(field, i);
+            let xross_ty = resolve_type_with_attr(&field.ty, &field.attrs, &package, Some(name));
+            fields.push(XrossField {
+                name: field_name(&member),
+                ty: xross_ty.clone(),
+                safety: extract_safety_attr(&field.attrs, default_safety),
+                docs: extract_docs(&field.attrs),
+            });
 
-                generate_property_accessors(
-                    name,
-                    field_ident,
-                    &field.ty,
-                    &xross_ty,
-                    &symbol_base,
-                    extra_functions,
-                );
-            }
+            generate_property_accessors(
+                name,
+                &member,
+                &field.ty,
+                &xross_ty,
+                &symbol_base,
+                extra_functions,
+            );
         }
     }
+    let signature = build_signature(&package, &name_str);
     save_definition(&XrossDefinition::Struct(XrossStruct {
-        signature: if package.is_empty() {
-            name_str.clone()
-        } else {
-            format!("{}.{}", package, name_str)
-        },
+        signature: signature.clone(),
         symbol_prefix: symbol_base.clone(),
         package_name: package,
         name: name_str,
@@ -73,6 +71,9 @@ pub fn impl_struct_derive(
         methods,
         docs: extract_docs(&s.attrs),
         is_copy: extract_is_copy(&s.attrs),
+        is_shared,
+        instance_of,
+        defaults,
     }));
 
     let mut toks = Vec::new();
@@ -82,8 +83,10 @@ pub fn impl_struct_derive(
         layout_logic,
         &mut toks,
         is_clonable,
+        is_shared,
         clone_mode,
         drop_mode,
     );
+    toks.push(gen_object_impl(name, &signature));
     quote::quote!(#(#toks)*)
 }
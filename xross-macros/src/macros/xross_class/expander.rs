@@ -1,7 +1,8 @@
 use crate::codegen::ffi::{
-    MethodFfiData, add_clone_method, add_drop_method, build_signature, gen_field_layout_spec,
-    generate_common_ffi, generate_enum_aux_ffi, generate_property_accessors, process_method_args,
-    resolve_return_type, write_ffi_function,
+    MethodFfiData, add_clone_method, add_drop_method, build_signature, collection_accessors,
+    gen_field_layout_spec, gen_object_impl, generate_common_ffi, generate_enum_aux_ffi,
+    generate_property_accessors, is_task_export, iterator_accessors, notify_symbol,
+    process_method_args, resolve_return_type, write_ffi_function,
 };
 use crate::macros::xross_class::parser::{VariantFieldInfo, XrossClassInput, XrossClassItem};
 use crate::metadata::save_definition;
@@ -10,7 +11,7 @@ use crate::utils::*;
 use quote::{format_ident, quote};
 use syn::{ReturnType, Type};
 use xross_metadata::{
-    HandleMode, ThreadSafety, XrossDefinition, XrossEnum, XrossField, XrossMethod, XrossStruct,
+    HandleMode, XrossDefaults, XrossDefinition, XrossEnum, XrossField, XrossMethod, XrossStruct,
     XrossVariant,
 };
 
@@ -22,6 +23,7 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
     let mut clone_mode = HandleMode::Normal;
     let mut drop_mode = HandleMode::Normal;
     let mut is_copy = false;
+    let mut is_shared = false;
     let mut fields_raw = Vec::new();
     let mut methods_raw = Vec::new();
     let mut variants_raw = Vec::new();
@@ -42,6 +44,7 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
                 clone_mode = m;
             }
             XrossClassItem::IsCopy(v) => is_copy = v,
+            XrossClassItem::IsShared(v) => is_shared = v,
             XrossClassItem::Field { name, ty } => fields_raw.push((name, ty)),
             XrossClassItem::Method(sig, type_override, mode) => {
                 methods_raw.push((sig, type_override, mode))
@@ -62,15 +65,19 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
     let mut extra_functions = Vec::new();
     let mut methods_meta = Vec::new();
 
+    let (default_mode, default_safety) = effective_defaults(XrossDefaults::default());
+
     if is_clonable {
         add_clone_method(&mut methods_meta, &symbol_base, &package, &name, clone_mode);
     }
     add_drop_method(&mut methods_meta, &symbol_base, drop_mode);
 
     for (sig, type_override, handle_mode) in methods_raw {
+        let handle_mode = handle_mode.unwrap_or(default_mode);
         let rust_fn_name = &sig.ident;
-        let is_async = sig.asyncness.is_some();
+        let is_async = is_task_export(&sig, handle_mode);
         let mut ffi_data = MethodFfiData::new(&symbol_base, rust_fn_name);
+        ffi_data.is_async = is_async;
         process_method_args(&sig.inputs, &package, &type_ident, &mut ffi_data);
         let ret_ty = resolve_return_type(&sig.output, &[], &package, &type_ident);
         let is_constructor = if let ReturnType::Type(_, ty) = &sig.output {
@@ -87,12 +94,15 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
             symbol: ffi_data.symbol_name.clone(),
             method_type: ffi_data.method_type,
             handle_mode,
-            safety: ThreadSafety::Lock,
+            safety: default_safety,
             is_constructor,
             is_default: false,
             is_async,
             args: ffi_data.args_meta.clone(),
             ret: ret_ty.clone(),
+            ret_accessors: collection_accessors(&ffi_data.symbol_name, &ret_ty),
+            ret_iterator: iterator_accessors(&ffi_data.symbol_name, &ret_ty),
+            notify_symbol: notify_symbol(&ffi_data.symbol_name, is_async),
             docs: vec![],
         });
 
@@ -133,7 +143,10 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
 
             match &v.fields {
                 VariantFieldInfo::Unit => {
-                    extra_functions.push(quote! { #[unsafe(no_mangle)] pub unsafe extern "C" fn #constructor_name() -> *mut #type_ident { Box::into_raw(Box::new(#type_ident::#v_ident)) } });
+                    let value = crate::codegen::ffi::gen_track_handle(
+                        quote! { Box::into_raw(Box::new(#type_ident::#v_ident)) },
+                    );
+                    extra_functions.push(quote! { #[unsafe(no_mangle)] pub unsafe extern "C" fn #constructor_name() -> *mut #type_ident { #value as *mut #type_ident } });
                     variant_specs.push(quote! { #v_name_str .to_string() });
                     variant_name_arms.push(quote! { #type_ident::#v_ident => #v_name_str });
                 }
@@ -149,11 +162,11 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
 
                     for (i, (f_name, f_ty)) in fields_iter.into_iter().enumerate() {
                         let ty = resolve_type_with_attr(f_ty, &[], &package, Some(&type_ident));
-                        let f_name_str = f_name.clone().unwrap_or_else(|| ordinal_name(i));
+                        let f_name_str = f_name.clone().unwrap_or_else(|| positional_name(i));
                         v_fields_meta.push(XrossField {
                             name: f_name_str.clone(),
                             ty: ty.clone(),
-                            safety: ThreadSafety::Lock,
+                            safety: default_safety,
                             docs: vec![],
                         });
                         let arg_id = f_name
@@ -198,11 +211,14 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
                         )
                     };
 
+                    let value = crate::codegen::ffi::gen_track_handle(
+                        quote! { Box::into_raw(Box::new(#construction)) },
+                    );
                     extra_functions.push(quote! {
                         #[unsafe(no_mangle)]
                         pub unsafe extern "C" fn #constructor_name(#(#c_param_defs),*) -> *mut #type_ident {
                             #(#internal_conversions)*
-                            Box::into_raw(Box::new(#construction))
+                            #value as *mut #type_ident
                         }
                     });
                     variant_name_arms.push(quote! { #pattern => #v_name_str });
@@ -218,7 +234,7 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
             });
         }
         save_definition(&XrossDefinition::Enum(XrossEnum {
-            signature,
+            signature: signature.clone(),
             symbol_prefix: symbol_base.clone(),
             package_name: package,
             name: name.clone(),
@@ -226,6 +242,9 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
             methods: methods_meta,
             docs: vec![],
             is_copy,
+            is_shared,
+            instance_of: None,
+            defaults: None,
         }));
         layout_logic = quote! { let mut parts = vec![format!("{}", std::mem::size_of::<#type_ident>() as u64)]; let variants: Vec<String> = vec![#(#variant_specs),*]; parts.push(variants.join(";")); parts.join(";") };
         generate_enum_aux_ffi(&type_ident, &symbol_base, variant_name_arms, &mut extra_functions);
@@ -237,7 +256,7 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
             fields_meta.push(XrossField {
                 name: f_name.clone(),
                 ty: xross_ty.clone(),
-                safety: ThreadSafety::Lock,
+                safety: default_safety,
                 docs: vec![],
             });
             let field_ident = format_ident!("{}", f_name);
@@ -249,7 +268,7 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
             ));
             generate_property_accessors(
                 &type_ident,
-                &field_ident,
+                &syn::Member::Named(field_ident),
                 &f_ty,
                 &xross_ty,
                 &symbol_base,
@@ -257,7 +276,7 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
             );
         }
         save_definition(&XrossDefinition::Struct(XrossStruct {
-            signature,
+            signature: signature.clone(),
             symbol_prefix: symbol_base.clone(),
             package_name: package,
             name,
@@ -265,6 +284,9 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
             methods: methods_meta,
             docs: vec![],
             is_copy,
+            is_shared,
+            instance_of: None,
+            defaults: None,
         }));
         layout_logic = quote! { let mut parts = vec![format!("{}", std::mem::size_of::<#type_ident>() as u64)]; #(parts.push(#field_specs);)* parts.join(";") };
     }
@@ -274,8 +296,10 @@ pub fn impl_xross_class(input: XrossClassInput) -> proc_macro::TokenStream {
         layout_logic,
         &mut extra_functions,
         is_clonable,
+        is_shared,
         clone_mode,
         drop_mode,
     );
+    extra_functions.push(gen_object_impl(&type_ident, &signature));
     quote! { #(#extra_functions)* }.into()
 }
@@ -10,11 +10,13 @@ syn::custom_keyword!(clonable);
 syn::custom_keyword!(is_clonable);
 syn::custom_keyword!(iscopy);
 syn::custom_keyword!(is_copy);
+syn::custom_keyword!(shared);
 syn::custom_keyword!(field);
 syn::custom_keyword!(method);
 syn::custom_keyword!(drop);
 syn::custom_keyword!(critical);
 syn::custom_keyword!(panicable);
+syn::custom_keyword!(blocking);
 syn::custom_keyword!(heap_access);
 
 pub enum VariantFieldInfo {
@@ -34,8 +36,9 @@ pub enum XrossClassItem {
     Enum(String),
     IsClonable(bool, xross_metadata::HandleMode),
     IsCopy(bool),
+    IsShared(bool),
     Field { name: String, ty: Type },
-    Method(Signature, Option<String>, xross_metadata::HandleMode),
+    Method(Signature, Option<String>, Option<xross_metadata::HandleMode>),
     Variants(Vec<VariantInfo>),
     DropMode(xross_metadata::HandleMode),
 }
@@ -147,6 +150,11 @@ impl Parse for XrossClassInput {
                 let val: syn::LitBool = input.parse()?;
                 input.parse::<Token![;]>()?;
                 items.push(XrossClassItem::IsCopy(val.value));
+            } else if input.peek(shared) {
+                input.parse::<shared>()?;
+                let val: syn::LitBool = input.parse()?;
+                input.parse::<Token![;]>()?;
+                items.push(XrossClassItem::IsShared(val.value));
             } else if input.peek(field) {
                 input.parse::<field>()?;
                 let name = input.parse::<syn::Ident>()?.to_string();
@@ -198,7 +206,7 @@ impl Parse for XrossClassInput {
                     inputs.push(arg);
                 }
                 let output = input.parse::<ReturnType>()?;
-                let mut mode = xross_metadata::HandleMode::Normal;
+                let mut mode = None;
                 if input.peek(syn::token::Paren) {
                     let m_content;
                     parenthesized!(m_content in input);
@@ -213,10 +221,13 @@ impl Parse for XrossClassInput {
                                 allow_heap_access = true;
                             }
                         }
-                        mode = xross_metadata::HandleMode::Critical { allow_heap_access };
+                        mode = Some(xross_metadata::HandleMode::Critical { allow_heap_access });
                     } else if m_content.peek(panicable) {
                         m_content.parse::<panicable>()?;
-                        mode = xross_metadata::HandleMode::Panicable;
+                        mode = Some(xross_metadata::HandleMode::Panicable);
+                    } else if m_content.peek(blocking) {
+                        m_content.parse::<blocking>()?;
+                        mode = Some(xross_metadata::HandleMode::Blocking);
                     }
                 }
                 if input.peek(Token![;]) {
@@ -240,7 +251,7 @@ impl Parse for XrossClassInput {
                     mode,
                 ));
             } else {
-                return Err(input.error("expected one of: package, class, enum, variants, clonable, iscopy, field, method, drop"));
+                return Err(input.error("expected one of: package, class, enum, variants, clonable, iscopy, shared, field, method, drop"));
             }
         }
         Ok(XrossClassInput { items })
@@ -1,15 +1,16 @@
 use crate::codegen::ffi::{
-    MethodFfiData, process_method_args, resolve_return_type, write_ffi_function,
+    MethodFfiData, is_task_export, process_method_args, resolve_return_type, write_ffi_function,
 };
 use quote::quote;
 use syn::parse::{Parse, ParseStream};
 use syn::{Signature, Token};
-use xross_metadata::{HandleMode, ThreadSafety};
+use xross_metadata::{HandleMode, ThreadSafety, XrossDefaults};
 
 mod kw {
     syn::custom_keyword!(package);
     syn::custom_keyword!(critical);
     syn::custom_keyword!(panicable);
+    syn::custom_keyword!(blocking);
     syn::custom_keyword!(safety);
     syn::custom_keyword!(heap_access);
 }
@@ -24,8 +25,8 @@ pub struct XrossFunctionInput {
 impl Parse for XrossFunctionInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut package_name = String::new();
-        let mut handle_mode = HandleMode::Normal;
-        let mut safety_level = ThreadSafety::Lock;
+        let (mut handle_mode, mut safety_level) =
+            crate::utils::effective_defaults(XrossDefaults::default());
 
         while !input.peek(Token![fn]) && !input.is_empty() {
             if input.peek(kw::package) {
@@ -53,6 +54,12 @@ impl Parse for XrossFunctionInput {
                 if input.peek(Token![;]) {
                     input.parse::<Token![;]>()?;
                 }
+            } else if input.peek(kw::blocking) {
+                input.parse::<kw::blocking>()?;
+                handle_mode = HandleMode::Blocking;
+                if input.peek(Token![;]) {
+                    input.parse::<Token![;]>()?;
+                }
             } else if input.peek(kw::safety) {
                 input.parse::<kw::safety>()?;
                 let id = input.parse::<syn::Ident>()?;
@@ -86,7 +93,7 @@ pub fn impl_xross_function(input: proc_macro::TokenStream) -> proc_macro::TokenS
     let package_name = input.package_name;
     let rust_fn_name = &input.signature.ident;
     let name_str = rust_fn_name.to_string();
-    let is_async = input.signature.asyncness.is_some();
+    let is_async = is_task_export(&input.signature, input.handle_mode);
 
     let symbol_prefix = crate::utils::get_symbol_prefix(&package_name);
 
@@ -62,7 +62,7 @@ pub fn save_definition(def: &XrossDefinition) {
         XrossDefinition::Struct(s) => deduplicate_methods(&mut s.methods),
         XrossDefinition::Enum(e) => deduplicate_methods(&mut e.methods),
         XrossDefinition::Opaque(o) => deduplicate_methods(&mut o.methods),
-        XrossDefinition::Function(_) => {}
+        XrossDefinition::Function(_) | XrossDefinition::Trait(_) | XrossDefinition::Error(_) => {}
     }
 
     if path.exists()
@@ -114,6 +114,14 @@ fn is_structurally_compatible(a: &XrossDefinition, b: &XrossDefinition) -> bool
         (XrossDefinition::Function(fa), XrossDefinition::Function(fb)) => {
             fa.package_name == fb.package_name && fa.name == fb.name
         }
+        (XrossDefinition::Trait(ta), XrossDefinition::Trait(tb)) => {
+            ta.package_name == tb.package_name && ta.name == tb.name
+        }
+        (XrossDefinition::Error(ea), XrossDefinition::Error(eb)) => {
+            ea.package_name == eb.package_name
+                && ea.name == eb.name
+                && ea.variants.len() == eb.variants.len()
+        }
         _ => false,
     }
 }
@@ -138,6 +146,46 @@ pub fn load_definition(ident: &syn::Ident) -> Option<XrossDefinition> {
     None
 }
 
+/// Loads every definition that instantiates the given generic type, restricted to `package` if given.
+/// Panics if generic types with the same name are instantiated in different packages and no
+/// package was given.
+pub fn load_instantiations(generic: &syn::Ident, package: Option<&str>) -> Vec<XrossDefinition> {
+    let Ok(entries) = fs::read_dir(get_xross_dir()) else { return Vec::new() };
+    let mut defs: Vec<XrossDefinition> = entries
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str::<XrossDefinition>(&content).ok())
+        .filter(|def| def.instance_of().is_some_and(|inst| *generic == inst.generic))
+        .filter(|def| package.is_none_or(|package| def.package_name() == package))
+        .collect();
+    defs.sort_by(|a, b| a.signature().cmp(b.signature()));
+
+    let mut packages: Vec<&str> = defs.iter().map(|def| def.package_name()).collect();
+    packages.sort();
+    packages.dedup();
+    if packages.len() > 1 {
+        panic!(
+            "\n[Xross Error] Ambiguous generic type: '{}'\n\
+             Instantiations were found in different packages:\n\
+             {}\n\
+             Select one with #[xross_methods(package = \"...\")].\n",
+            generic,
+            packages.iter().map(|p| format!("  - {}", p)).collect::<Vec<_>>().join("\n")
+        );
+    }
+    defs
+}
+
+/// Returns true if a trait named `name` was declared with `#[xross_trait]`.
+pub fn is_xross_trait(name: &str) -> bool {
+    let Ok(entries) = fs::read_dir(get_xross_dir()) else { return false };
+    entries
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str::<XrossDefinition>(&content).ok())
+        .any(|def| matches!(def, XrossDefinition::Trait(_)) && def.name() == name)
+}
+
 /// Discovers the signature of a type by its name.
 /// Panics if multiple types with the same name are found in different packages.
 pub fn discover_signature(type_name: &str) -> Option<String> {
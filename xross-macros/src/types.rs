@@ -1,2 +1,3 @@
+pub mod generics;
 pub mod mapping;
 pub mod resolver;
@@ -0,0 +1,115 @@
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::visit_mut::{self, VisitMut};
+use syn::{Attribute, PathArguments, Token, Type};
+
+/// A concrete instantiation declared with `#[xross(instantiate(Buffer<f32> as FloatBuffer))]`.
+#[derive(Clone)]
+pub struct Instantiation {
+    pub concrete: Type,
+    pub alias: syn::Ident,
+}
+
+impl Parse for Instantiation {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let concrete = input.parse()?;
+        input.parse::<Token![as]>()?;
+        Ok(Self { concrete, alias: input.parse()? })
+    }
+}
+
+impl Instantiation {
+    /// Returns the type arguments of the concrete type, e.g. `f32` for `Buffer<f32>`.
+    pub fn type_args(&self) -> Vec<Type> {
+        crate::codegen::ffi::generic_type_args(&self.concrete).into_iter().cloned().collect()
+    }
+}
+
+/// Extracts the instantiation list from `#[xross(instantiate(...))]`.
+pub fn extract_instantiations(attrs: &[Attribute]) -> Vec<Instantiation> {
+    let mut result = Vec::new();
+    for attr in attrs {
+        if attr.path().is_ident("xross") {
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("instantiate") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    result.extend(Punctuated::<Instantiation, Token![,]>::parse_terminated(
+                        &content,
+                    )?);
+                }
+                Ok(())
+            });
+        }
+    }
+    result
+}
+
+/// Replaces generic parameters with concrete types, and known instantiations with their aliases.
+pub struct GenericSubst {
+    params: Vec<(syn::Ident, Type)>,
+    aliases: Vec<(String, syn::Ident)>,
+}
+
+impl GenericSubst {
+    /// Maps the type parameters used as arguments of `generic_ty` (e.g. `T` in `Buffer<T>`)
+    /// onto `args`.
+    pub fn new(generic_ty: &Type, args: &[Type], instantiations: &[Instantiation]) -> Self {
+        let params = crate::codegen::ffi::generic_type_args(generic_ty)
+            .into_iter()
+            .zip(args)
+            .filter_map(|(param, arg)| match param {
+                Type::Path(tp) => Some((tp.path.get_ident()?.clone(), arg.clone())),
+                _ => None,
+            })
+            .collect();
+        let aliases = instantiations
+            .iter()
+            .map(|inst| {
+                let concrete = &inst.concrete;
+                (quote!(#concrete).to_string(), inst.alias.clone())
+            })
+            .collect();
+        Self { params, aliases }
+    }
+
+    /// Builds the substitution for a generic type declaration such as `struct Buffer<T>`.
+    pub fn for_declaration(
+        ident: &syn::Ident,
+        generics: &syn::Generics,
+        inst: &Instantiation,
+        instantiations: &[Instantiation],
+    ) -> Self {
+        let params: Vec<_> = generics.type_params().map(|p| &p.ident).collect();
+        let generic_ty: Type = syn::parse_quote!(#ident<#(#params),*>);
+        Self::new(&generic_ty, &inst.type_args(), instantiations)
+    }
+}
+
+impl VisitMut for GenericSubst {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        visit_mut::visit_type_mut(self, ty);
+        let Type::Path(tp) = ty else { return };
+        if tp.qself.is_some() {
+            return;
+        }
+        if let Some(ident) = tp.path.get_ident()
+            && let Some((_, concrete)) = self.params.iter().find(|(p, _)| p == ident)
+        {
+            *ty = concrete.clone();
+            return;
+        }
+        let is_generic = tp
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| matches!(s.arguments, PathArguments::AngleBracketed(_)));
+        if is_generic {
+            let key = quote!(#tp).to_string();
+            if let Some((_, alias)) = self.aliases.iter().find(|(k, _)| *k == key) {
+                *ty = syn::parse_quote!(#alias);
+            }
+        }
+    }
+}
@@ -1,12 +1,201 @@
-use syn::{GenericArgument, PathArguments, Type, TypePath};
+use syn::punctuated::Punctuated;
+use syn::{GenericArgument, ParenthesizedGenericArguments, PathArguments, Type, TypePath};
+use syn::{TypeParamBound, token::Plus};
 use xross_metadata::{Ownership, XrossType};
 
+/// Finds the `Fn(..) -> R` / `FnMut` / `FnOnce` bound of `impl Fn(..)`, `dyn Fn(..)`,
+/// `Box<dyn Fn(..)>` or a reference to one of them.
+pub fn find_fn_bound(ty: &Type) -> Option<&ParenthesizedGenericArguments> {
+    fn from_bounds(
+        bounds: &Punctuated<TypeParamBound, Plus>,
+    ) -> Option<&ParenthesizedGenericArguments> {
+        bounds.iter().find_map(|bound| match bound {
+            TypeParamBound::Trait(t) => {
+                let last = t.path.segments.last()?;
+                match &last.arguments {
+                    PathArguments::Parenthesized(args)
+                        if matches!(last.ident.to_string().as_str(), "Fn" | "FnMut" | "FnOnce") =>
+                    {
+                        Some(args)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+    }
+    match ty {
+        Type::Reference(r) => find_fn_bound(&r.elem),
+        Type::Paren(p) => find_fn_bound(&p.elem),
+        Type::ImplTrait(t) => from_bounds(&t.bounds),
+        Type::TraitObject(t) => from_bounds(&t.bounds),
+        Type::Path(tp) => {
+            let last = tp.path.segments.last()?;
+            if last.ident != "Box" {
+                return None;
+            }
+            let PathArguments::AngleBracketed(args) = &last.arguments else { return None };
+            args.args.iter().find_map(|arg| match arg {
+                GenericArgument::Type(inner) => find_fn_bound(inner),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Returns the name of the trait in `dyn Trait` / `impl Trait`,
+/// looking through references and `Box<..>`.
+pub fn trait_object_name(ty: &Type) -> Option<String> {
+    let bounds = match ty {
+        Type::Reference(r) => return trait_object_name(&r.elem),
+        Type::Paren(p) => return trait_object_name(&p.elem),
+        Type::ImplTrait(t) => &t.bounds,
+        Type::TraitObject(t) => &t.bounds,
+        Type::Path(tp) => {
+            let last = tp.path.segments.last()?;
+            if last.ident != "Box" {
+                return None;
+            }
+            let PathArguments::AngleBracketed(args) = &last.arguments else { return None };
+            return args.args.iter().find_map(|arg| match arg {
+                GenericArgument::Type(inner) => trait_object_name(inner),
+                _ => None,
+            });
+        }
+        _ => return None,
+    };
+    bounds.iter().find_map(|bound| match bound {
+        TypeParamBound::Trait(t) => t.path.segments.last().map(|last| last.ident.to_string()),
+        _ => None,
+    })
+}
+
+/// Finds `T` in an `impl Trait<Item = T>` / `dyn Trait<Item = T>` bound named `trait_name`,
+/// looking through references, `Box<..>` and `Pin<..>`.
+pub fn find_item_bound<'a>(ty: &'a Type, trait_name: &str) -> Option<&'a Type> {
+    let bounds = match ty {
+        Type::Reference(r) => return find_item_bound(&r.elem, trait_name),
+        Type::Paren(p) => return find_item_bound(&p.elem, trait_name),
+        Type::ImplTrait(t) => &t.bounds,
+        Type::TraitObject(t) => &t.bounds,
+        Type::Path(tp) => {
+            let last = tp.path.segments.last()?;
+            if last.ident != "Box" && last.ident != "Pin" {
+                return None;
+            }
+            let PathArguments::AngleBracketed(args) = &last.arguments else { return None };
+            return args.args.iter().find_map(|arg| match arg {
+                GenericArgument::Type(inner) => find_item_bound(inner, trait_name),
+                _ => None,
+            });
+        }
+        _ => return None,
+    };
+    bounds.iter().find_map(|bound| {
+        let TypeParamBound::Trait(t) = bound else { return None };
+        let last = t.path.segments.last()?;
+        if last.ident != trait_name {
+            return None;
+        }
+        let PathArguments::AngleBracketed(args) = &last.arguments else { return None };
+        args.args.iter().find_map(|arg| match arg {
+            GenericArgument::AssocType(assoc) if assoc.ident == "Item" => Some(&assoc.ty),
+            _ => None,
+        })
+    })
+}
+
+/// Modules whose `IntoIter` yields the first type argument. Trailing arguments are allocators or `const N`.
+const ELEMENT_INTO_ITER: &[&str] = &[
+    "vec",
+    "vec_deque",
+    "linked_list",
+    "hash_set",
+    "btree_set",
+    "binary_heap",
+    "array",
+    "option",
+    "result",
+];
+
+/// Modules whose `IntoIter` yields `(K, V)` pairs of the first two type arguments.
+const MAP_INTO_ITER: &[&str] = &["hash_map", "btree_map"];
+
+/// Returns the item type of an iterator: the `Item` of an `impl Iterator<Item = T>` bound,
+/// or the element(s) of a collection `IntoIter` such as `std::vec::IntoIter<T>`.
+///
+/// `IntoIter` is recognised by its module (`vec::IntoIter`, `hash_map::IntoIter`, ...), so an
+/// unqualified `IntoIter` is rejected rather than guessed from its argument count.
+pub fn iterator_item_type(ty: &Type) -> Option<Type> {
+    if let Some(item) = find_item_bound(ty, "Iterator") {
+        return Some(item.clone());
+    }
+    let Type::Path(tp) = ty else { return None };
+    let mut segments = tp.path.segments.iter().rev();
+    let last = segments.next()?;
+    if last.ident != "IntoIter" {
+        return None;
+    }
+    let module = segments.next().map(|seg| seg.ident.to_string()).unwrap_or_else(|| {
+        panic!("Qualify `IntoIter` with its module, e.g. `std::vec::IntoIter<T>` or `hash_map::IntoIter<K, V>`")
+    });
+    let PathArguments::AngleBracketed(args) = &last.arguments else { return None };
+    let mut elems = args
+        .args
+        .iter()
+        .filter_map(|arg| if let GenericArgument::Type(inner) = arg { Some(inner) } else { None });
+    if ELEMENT_INTO_ITER.contains(&module.as_str()) {
+        elems.next().cloned()
+    } else if MAP_INTO_ITER.contains(&module.as_str()) {
+        let (key, value) = (elems.next()?, elems.next()?);
+        Some(syn::parse_quote!((#key, #value)))
+    } else {
+        panic!("Unsupported iterator type `{}::IntoIter`", module)
+    }
+}
+
+/// `Rc` のカウントは非アトミックだが、JVM のハンドルは Cleaner スレッドから解放される
+pub const RC_UNSUPPORTED: &str = "`Rc<T>` cannot cross the FFI boundary: JVM handles are released from other threads. Use `Arc<T>` with `#[xross(shared)]` instead.";
+
 pub fn map_type(ty: &syn::Type) -> XrossType {
+    if let Some(f) = find_fn_bound(ty) {
+        return XrossType::Function {
+            args: f.inputs.iter().map(map_type).collect(),
+            ret: Box::new(match &f.output {
+                syn::ReturnType::Default => XrossType::Void,
+                syn::ReturnType::Type(_, ret) => map_type(ret),
+            }),
+        };
+    }
+    if let Some(item) = find_item_bound(ty, "Stream") {
+        return XrossType::Stream(Box::new(map_type(item)));
+    }
+    if let Some(item) = iterator_item_type(ty) {
+        return XrossType::Iterator(Box::new(map_type(&item)));
+    }
     match ty {
         Type::Reference(r) => map_type(&r.elem),
 
         Type::Slice(s) => XrossType::Slice(Box::new(map_type(&s.elem))),
 
+        // dyn Trait / impl Trait は #[xross_trait] のプロキシとして扱う
+        Type::TraitObject(syn::TypeTraitObject { bounds, .. })
+        | Type::ImplTrait(syn::TypeImplTrait { bounds, .. }) => bounds
+            .iter()
+            .find_map(|bound| match bound {
+                TypeParamBound::Trait(t) => t.path.segments.last(),
+                _ => None,
+            })
+            .map(|last| XrossType::Object {
+                signature: last.ident.to_string(),
+                ownership: Ownership::Owned,
+            })
+            .unwrap_or(XrossType::Pointer),
+
+        Type::Tuple(t) if t.elems.is_empty() => XrossType::Void,
+        Type::Tuple(t) => XrossType::Tuple(t.elems.iter().map(map_type).collect()),
+
         Type::Path(TypePath { path, .. }) => {
             let last_segment = path.segments.last().unwrap();
             let last_ident = last_segment.ident.to_string();
@@ -25,11 +214,23 @@ pub fn map_type(ty: &syn::Type) -> XrossType {
                 "f32" => XrossType::F32,
                 "f64" => XrossType::F64,
                 "bool" => XrossType::Bool,
-                "String" => XrossType::String,
+                "String" | "str" => XrossType::String,
+                // Cow<'_, str> は借用可能な文字列として扱う
+                "Cow" => match &last_segment.arguments {
+                    PathArguments::AngleBracketed(args) => args
+                        .args
+                        .iter()
+                        .find_map(|arg| match arg {
+                            GenericArgument::Type(inner_ty) => Some(map_type(inner_ty)),
+                            _ => None,
+                        })
+                        .unwrap_or(XrossType::Pointer),
+                    _ => XrossType::Pointer,
+                },
 
                 // ジェネリック型の処理
-                "Box" | "Option" | "Result" | "Vec" | "VecDeque" | "LinkedList" | "HashSet"
-                | "BTreeSet" | "BinaryHeap" | "HashMap" | "BTreeMap" => {
+                "Box" | "Arc" | "Rc" | "Option" | "Result" | "Vec" | "VecDeque" | "LinkedList"
+                | "HashSet" | "BTreeSet" | "BinaryHeap" | "HashMap" | "BTreeMap" => {
                     if let PathArguments::AngleBracketed(args) = &last_segment.arguments {
                         let generic_types: Vec<XrossType> = args
                             .args
@@ -51,6 +252,14 @@ pub fn map_type(ty: &syn::Type) -> XrossType {
                                 }
                                 inner
                             }
+                            "Arc" => {
+                                let mut inner = generic_types[0].clone();
+                                if let XrossType::Object { ownership, .. } = &mut inner {
+                                    *ownership = Ownership::Shared;
+                                }
+                                inner
+                            }
+                            "Rc" => panic!("{}", RC_UNSUPPORTED),
                             "Option" => XrossType::Option(Box::new(generic_types[0].clone())),
                             "Vec" => XrossType::Vec(Box::new(generic_types[0].clone())),
                             "VecDeque" => XrossType::VecDeque(Box::new(generic_types[0].clone())),
@@ -94,9 +303,9 @@ pub fn map_type(ty: &syn::Type) -> XrossType {
 
 #[cfg(test)]
 mod tests {
-    use super::map_type;
+    use super::{map_type, trait_object_name};
     use syn::Type;
-    use xross_metadata::XrossType;
+    use xross_metadata::{Ownership, XrossType};
 
     #[test]
     fn maps_std_collections() {
@@ -114,4 +323,137 @@ mod tests {
 
         assert!(matches!(map_type(&nested), XrossType::Option(_)));
     }
+
+    #[test]
+    fn maps_borrowed_strings() {
+        let borrowed: Type = syn::parse_str("&str").unwrap();
+        let cow: Type = syn::parse_str("std::borrow::Cow<'_, str>").unwrap();
+
+        assert_eq!(map_type(&borrowed), XrossType::String);
+        assert_eq!(map_type(&cow), XrossType::String);
+    }
+
+    #[test]
+    fn maps_reference_counted_objects() {
+        let arc: Type = syn::parse_str("std::sync::Arc<Engine>").unwrap();
+
+        assert!(matches!(map_type(&arc), XrossType::Object { ownership: Ownership::Shared, .. }));
+    }
+
+    #[test]
+    #[should_panic(expected = "Rc<T>")]
+    fn rejects_rc() {
+        let rc: Type = syn::parse_str("std::rc::Rc<Engine>").unwrap();
+        map_type(&rc);
+    }
+
+    #[test]
+    fn maps_closures_to_functions() {
+        let expected = XrossType::Function {
+            args: vec![XrossType::String, XrossType::I32],
+            ret: Box::new(XrossType::Bool),
+        };
+        for src in ["impl Fn(&str, i32) -> bool", "Box<dyn FnMut(&str, i32) -> bool + Send>"] {
+            let ty: Type = syn::parse_str(src).unwrap();
+            assert_eq!(map_type(&ty), expected);
+        }
+        let handler: Type = syn::parse_str("&dyn Fn()").unwrap();
+        assert_eq!(
+            map_type(&handler),
+            XrossType::Function { args: vec![], ret: Box::new(XrossType::Void) }
+        );
+    }
+
+    #[test]
+    fn maps_trait_objects() {
+        let boxed: Type = syn::parse_str("Box<dyn Storage + Send>").unwrap();
+        let borrowed: Type = syn::parse_str("&dyn Logger").unwrap();
+
+        assert!(matches!(
+            map_type(&boxed),
+            XrossType::Object { ref signature, ownership: Ownership::Boxed } if signature == "Storage"
+        ));
+        assert!(
+            matches!(map_type(&borrowed), XrossType::Object { ref signature, .. } if signature == "Logger")
+        );
+    }
+
+    #[test]
+    fn names_trait_objects() {
+        let boxed: Type = syn::parse_str("Box<dyn Storage + Send>").unwrap();
+        let borrowed: Type = syn::parse_str("&mut dyn Logger").unwrap();
+        let concrete: Type = syn::parse_str("Box<Storage>").unwrap();
+
+        assert_eq!(trait_object_name(&boxed).as_deref(), Some("Storage"));
+        assert_eq!(trait_object_name(&borrowed).as_deref(), Some("Logger"));
+        assert_eq!(trait_object_name(&concrete), None);
+    }
+
+    #[test]
+    fn maps_tuples() {
+        let pair: Type = syn::parse_str("(i32, String)").unwrap();
+        let unit: Type = syn::parse_str("()").unwrap();
+
+        assert_eq!(map_type(&pair), XrossType::Tuple(vec![XrossType::I32, XrossType::String]));
+        assert_eq!(map_type(&unit), XrossType::Void);
+    }
+
+    #[test]
+    fn maps_streams() {
+        let opaque: Type = syn::parse_str("impl Stream<Item = String> + Send").unwrap();
+        let pinned: Type =
+            syn::parse_str("Pin<Box<dyn futures::Stream<Item = Result<i32, String>> + Send>>")
+                .unwrap();
+
+        assert_eq!(map_type(&opaque), XrossType::Stream(Box::new(XrossType::String)));
+        assert_eq!(
+            map_type(&pinned),
+            XrossType::Stream(Box::new(XrossType::Result {
+                ok: Box::new(XrossType::I32),
+                err: Box::new(XrossType::String),
+            }))
+        );
+    }
+
+    #[test]
+    fn maps_iterators() {
+        let opaque: Type = syn::parse_str("impl Iterator<Item = u64> + '_").unwrap();
+        let vec_iter: Type = syn::parse_str("std::vec::IntoIter<String>").unwrap();
+        let map_iter: Type = syn::parse_str("hash_map::IntoIter<String, i32>").unwrap();
+
+        assert_eq!(map_type(&opaque), XrossType::Iterator(Box::new(XrossType::U64)));
+        assert_eq!(map_type(&vec_iter), XrossType::Iterator(Box::new(XrossType::String)));
+        assert_eq!(
+            map_type(&map_iter),
+            XrossType::Iterator(Box::new(XrossType::Tuple(vec![
+                XrossType::String,
+                XrossType::I32
+            ])))
+        );
+    }
+
+    #[test]
+    fn maps_into_iter_by_module() {
+        let vec_iter: Type = syn::parse_str("std::vec::IntoIter<u8, std::alloc::Global>").unwrap();
+        let map_iter: Type =
+            syn::parse_str("btree_map::IntoIter<String, i32, std::alloc::Global>").unwrap();
+        let array_iter: Type = syn::parse_str("std::array::IntoIter<u16, 4>").unwrap();
+
+        assert_eq!(map_type(&vec_iter), XrossType::Iterator(Box::new(XrossType::U8)));
+        assert_eq!(
+            map_type(&map_iter),
+            XrossType::Iterator(Box::new(XrossType::Tuple(vec![
+                XrossType::String,
+                XrossType::I32
+            ])))
+        );
+        assert_eq!(map_type(&array_iter), XrossType::Iterator(Box::new(XrossType::U16)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Qualify `IntoIter`")]
+    fn rejects_unqualified_into_iter() {
+        let bare: Type = syn::parse_str("IntoIter<String>").unwrap();
+        map_type(&bare);
+    }
 }
@@ -1,5 +1,5 @@
-use crate::metadata::discover_signature;
-use crate::types::mapping::map_type;
+use crate::metadata::{discover_signature, is_xross_trait};
+use crate::types::mapping::{map_type, trait_object_name};
 use syn::{Attribute, Type};
 use xross_metadata::{Ownership, XrossType};
 
@@ -20,9 +20,9 @@ pub fn resolve_type_with_attr(
     };
 
     if let XrossType::Object { ownership: base_ow, .. } = &base_ty
-        && *base_ow == Ownership::Boxed
+        && (*base_ow == Ownership::Boxed || base_ow.is_shared())
     {
-        ownership = Ownership::Boxed;
+        ownership = base_ow.clone();
     }
 
     let mut xross_ty = None;
@@ -65,6 +65,17 @@ pub fn resolve_type_with_attr(
 
     let mut final_ty = map_type(inner_ty);
 
+    // dyn Trait / impl Trait は #[xross_trait] のプロキシがある場合のみ渡せる
+    if let XrossType::Object { .. } = &final_ty
+        && let Some(name) = trait_object_name(ty)
+        && !is_xross_trait(&name)
+    {
+        panic!(
+            "`{}` is not declared with #[xross_trait]: only xross traits can cross the boundary as `dyn Trait` / `impl Trait`",
+            name
+        );
+    }
+
     if let XrossType::Object { ownership: o, signature } = &mut final_ty {
         if ownership != Ownership::Owned {
             *o = ownership;
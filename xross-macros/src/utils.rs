@@ -1,7 +1,9 @@
 pub mod attributes;
+pub mod defaults;
 pub mod ordinal;
 
 pub use attributes::*;
+pub use defaults::*;
 pub use ordinal::*;
 
 use heck::ToSnakeCase;
@@ -42,6 +44,9 @@ pub fn register_xross_function(
         is_async: ffi_data.is_async,
         args: ffi_data.args_meta.clone(),
         ret: ret_ty.clone(),
+        ret_accessors: crate::codegen::ffi::collection_accessors(&ffi_data.symbol_name, ret_ty),
+        ret_iterator: crate::codegen::ffi::iterator_accessors(&ffi_data.symbol_name, ret_ty),
+        notify_symbol: crate::codegen::ffi::notify_symbol(&ffi_data.symbol_name, ffi_data.is_async),
         docs: docs.clone(),
     };
 
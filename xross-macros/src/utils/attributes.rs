@@ -1,3 +1,4 @@
+use super::parse_safety_name;
 use syn::{Attribute, Expr, ExprLit, Lit, Meta, Token};
 use xross_metadata::{HandleMode, ThreadSafety};
 
@@ -39,8 +40,9 @@ pub fn parse_handle_mode_nested(meta: &syn::meta::ParseNestedMeta) -> syn::Resul
     Ok(HandleMode::Normal)
 }
 
-pub fn extract_handle_mode(attrs: &[Attribute]) -> HandleMode {
-    let mut mode = HandleMode::Normal;
+/// Reads the handle mode set on a method, falling back to `default` when none is set.
+pub fn extract_handle_mode(attrs: &[Attribute], default: HandleMode) -> HandleMode {
+    let mut mode = default;
 
     for attr in attrs {
         let path = attr.path();
@@ -55,6 +57,10 @@ pub fn extract_handle_mode(attrs: &[Attribute]) -> HandleMode {
                     mode = HandleMode::Critical { allow_heap_access };
                 } else if meta.path.is_ident("panicable") {
                     mode = HandleMode::Panicable;
+                } else if meta.path.is_ident("blocking") {
+                    mode = HandleMode::Blocking;
+                } else if meta.path.is_ident("normal") {
+                    mode = HandleMode::Normal;
                 }
                 Ok(())
             });
@@ -141,6 +147,26 @@ pub fn extract_is_clonable(attrs: &[Attribute]) -> bool {
     false
 }
 
+/// Checks `#[xross(shared)]`: the type is handed to the JVM as `Arc<Self>`.
+pub fn extract_is_shared(attrs: &[Attribute]) -> bool {
+    attrs.iter().filter(|attr| attr.path().is_ident("xross")).any(|attr| {
+        let mut is_shared = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("shared") {
+                is_shared = true;
+            } else if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<proc_macro2::TokenStream>()?;
+            } else if meta.input.peek(Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            }
+            Ok(())
+        });
+        is_shared
+    })
+}
+
 pub fn extract_package(attrs: &[Attribute]) -> String {
     for attr in attrs {
         if attr.path().is_ident("xross_package")
@@ -168,8 +194,10 @@ pub fn extract_docs(attrs: &[Attribute]) -> Vec<String> {
         .collect()
 }
 
+/// Reads the safety level set on a member, falling back to `default` when none is set.
 pub fn extract_safety_attr(attrs: &[Attribute], default: ThreadSafety) -> ThreadSafety {
     let mut safety = default;
+    let mut is_explicit = false;
     let mut is_unsafe_acknowledged = false;
 
     for attr in attrs {
@@ -180,14 +208,8 @@ pub fn extract_safety_attr(attrs: &[Attribute], default: ThreadSafety) -> Thread
             let _ = attr.parse_nested_meta(|meta| {
                 if meta.path.is_ident("safety") {
                     let value = meta.value()?.parse::<syn::Ident>()?;
-                    safety = match value.to_string().as_str() {
-                        "Unsafe" => ThreadSafety::Unsafe,
-                        "Direct" => ThreadSafety::Direct,
-                        "Atomic" => ThreadSafety::Atomic,
-                        "Immutable" => ThreadSafety::Immutable,
-                        "Lock" => ThreadSafety::Lock,
-                        _ => default,
-                    };
+                    safety = parse_safety_name(&value.to_string()).unwrap_or(default);
+                    is_explicit = true;
                 } else if meta.path.is_ident("unsafe") {
                     is_unsafe_acknowledged = true;
                 }
@@ -196,7 +218,10 @@ pub fn extract_safety_attr(attrs: &[Attribute], default: ThreadSafety) -> Thread
         }
     }
 
-    if (safety == ThreadSafety::Unsafe || safety == ThreadSafety::Direct) && !is_unsafe_acknowledged
+    // A default coming from the impl, type or crate was acknowledged where it was set.
+    if is_explicit
+        && (safety == ThreadSafety::Unsafe || safety == ThreadSafety::Direct)
+        && !is_unsafe_acknowledged
     {
         panic!(
             "Using safety level 'Unsafe' or 'Direct' requires an explicit 'unsafe' attribute. e.g. #[xross_method(safety = Direct, unsafe)]"
@@ -224,7 +249,7 @@ pub fn extract_base_type(ty: &syn::Type) -> &syn::Type {
         syn::Type::Path(tp) => {
             if let Some(last_segment) = tp.path.segments.last() {
                 let name = last_segment.ident.to_string();
-                if (name == "Box" || name == "Option" || name == "Result")
+                if matches!(name.as_str(), "Box" | "Arc" | "Rc" | "Option" | "Result")
                     && let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments
                     && let Some(syn::GenericArgument::Type(inner)) = args.args.first()
                 {
@@ -0,0 +1,218 @@
+use proc_macro2::TokenStream;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::SystemTime;
+use syn::Attribute;
+use syn::meta::ParseNestedMeta;
+use syn::parse::Parser;
+use xross_metadata::{HandleMode, ThreadSafety, XrossDefaults};
+
+pub fn parse_safety_name(name: &str) -> Option<ThreadSafety> {
+    match name {
+        "Unsafe" => Some(ThreadSafety::Unsafe),
+        "Direct" => Some(ThreadSafety::Direct),
+        "Atomic" => Some(ThreadSafety::Atomic),
+        "Immutable" => Some(ThreadSafety::Immutable),
+        "Lock" => Some(ThreadSafety::Lock),
+        _ => None,
+    }
+}
+
+fn parse_handle_mode_name(name: &str) -> Option<HandleMode> {
+    match name {
+        "normal" => Some(HandleMode::Normal),
+        "critical" => Some(HandleMode::Critical { allow_heap_access: false }),
+        "panicable" => Some(HandleMode::Panicable),
+        "blocking" => Some(HandleMode::Blocking),
+        _ => None,
+    }
+}
+
+/// `blocking` needs a receiver the task can own, so it is only accepted on the member itself.
+const BLOCKING_DEFAULT_ERROR: &str =
+    "`blocking` cannot be a default; mark each method with `#[xross_method(blocking)]`";
+
+/// Collects the handle mode and safety keys of an attribute, skipping any other key.
+#[derive(Default)]
+struct DefaultsParser {
+    defaults: XrossDefaults,
+    is_unsafe_acknowledged: bool,
+}
+
+impl DefaultsParser {
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("critical") {
+            let allow_heap_access = super::parse_critical_nested(&meta)?;
+            self.defaults.handle_mode = Some(HandleMode::Critical { allow_heap_access });
+        } else if meta.path.is_ident("panicable") {
+            self.defaults.handle_mode = Some(HandleMode::Panicable);
+        } else if meta.path.is_ident("blocking") {
+            return Err(meta.error(BLOCKING_DEFAULT_ERROR));
+        } else if meta.path.is_ident("normal") {
+            self.defaults.handle_mode = Some(HandleMode::Normal);
+        } else if meta.path.is_ident("safety") {
+            let value = meta.value()?.parse::<syn::Ident>()?;
+            let safety = parse_safety_name(&value.to_string())
+                .ok_or_else(|| meta.error(format!("Unknown safety level `{}`", value)))?;
+            self.defaults.safety = Some(safety);
+        } else if meta.path.is_ident("unsafe") {
+            self.is_unsafe_acknowledged = true;
+        } else if meta.input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in meta.input);
+            content.parse::<TokenStream>()?;
+        } else if meta.input.peek(syn::Token![=]) {
+            meta.value()?.parse::<syn::Expr>()?;
+        }
+        Ok(())
+    }
+
+    fn finish(self, example: &str) -> XrossDefaults {
+        check_unsafe_acknowledged(self.defaults.safety, self.is_unsafe_acknowledged, example);
+        self.defaults
+    }
+}
+
+fn check_unsafe_acknowledged(safety: Option<ThreadSafety>, acknowledged: bool, example: &str) {
+    if matches!(safety, Some(ThreadSafety::Unsafe | ThreadSafety::Direct)) && !acknowledged {
+        panic!(
+            "Using safety level 'Unsafe' or 'Direct' requires an explicit 'unsafe' attribute. e.g. {}",
+            example
+        );
+    }
+}
+
+/// Reads the defaults given to `#[xross_methods(...)]`, e.g. `#[xross_methods(panicable, safety = Immutable)]`.
+pub fn parse_impl_defaults(attr: TokenStream) -> XrossDefaults {
+    let mut parser = DefaultsParser::default();
+    if let Err(e) = syn::meta::parser(|meta| parser.parse(meta)).parse2(attr) {
+        panic!("Failed to parse xross_methods attributes: {}", e);
+    }
+    parser.finish("#[xross_methods(safety = Direct, unsafe)]")
+}
+
+/// Reads the defaults set with `#[xross(...)]` on a type. `None` if the type sets none.
+pub fn extract_type_defaults(attrs: &[Attribute]) -> Option<XrossDefaults> {
+    let mut parser = DefaultsParser::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("xross")) {
+        if let Err(e) = attr.parse_nested_meta(|meta| parser.parse(meta)) {
+            panic!("Failed to parse xross attributes: {}", e);
+        }
+    }
+    let defaults = parser.finish("#[xross(safety = Direct, unsafe)]");
+    (!defaults.is_empty()).then_some(defaults)
+}
+
+/// Reads the crate-wide defaults from `[package.metadata.xross]` in the crate's `Cargo.toml`:
+///
+/// ```toml
+/// [package.metadata.xross]
+/// handle_mode = "panicable"
+/// safety = "Immutable"
+/// ```
+///
+/// The parsed defaults are cached per manifest and re-read only when its modification time changes,
+/// as the proc-macro server of an IDE outlives edits to it.
+pub fn crate_defaults() -> XrossDefaults {
+    /// Defaults by manifest path, with the modification time they were read at.
+    type Cache = HashMap<PathBuf, (Option<SystemTime>, XrossDefaults)>;
+    static CACHE: LazyLock<Mutex<Cache>> = LazyLock::new(Default::default);
+
+    let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") else {
+        return XrossDefaults::default();
+    };
+    let path = Path::new(&manifest_dir).join("Cargo.toml");
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+    // A panic while parsing leaves nothing half-written behind, so a poisoned cache is still usable.
+    let mut cache = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(&(read_at, defaults)) = cache.get(&path)
+        && read_at == modified
+    {
+        return defaults;
+    }
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return XrossDefaults::default();
+    };
+    let defaults = parse_crate_defaults(&content);
+    cache.insert(path, (modified, defaults));
+    defaults
+}
+
+fn parse_crate_defaults(manifest: &str) -> XrossDefaults {
+    let manifest: toml::Table =
+        manifest.parse().unwrap_or_else(|e| panic!("Failed to parse Cargo.toml: {}", e));
+    let Some(config) =
+        manifest.get("package").and_then(|p| p.get("metadata")).and_then(|m| m.get("xross"))
+    else {
+        return XrossDefaults::default();
+    };
+    let read = |key: &str| {
+        config.get(key).map(|v| {
+            v.as_str().unwrap_or_else(|| {
+                panic!("[package.metadata.xross] `{}` must be a string, found {}", key, v)
+            })
+        })
+    };
+    let handle_mode = read("handle_mode").map(|name| match parse_handle_mode_name(name) {
+        Some(HandleMode::Blocking) => panic!("[package.metadata.xross] {}", BLOCKING_DEFAULT_ERROR),
+        Some(mode) => mode,
+        None => panic!("[package.metadata.xross] Unknown handle mode `{}`", name),
+    });
+    let safety = read("safety").map(|name| {
+        parse_safety_name(name)
+            .unwrap_or_else(|| panic!("[package.metadata.xross] Unknown safety level `{}`", name))
+    });
+    let acknowledged = config.get("unsafe").and_then(|v| v.as_bool()).unwrap_or(false);
+    check_unsafe_acknowledged(safety, acknowledged, "`unsafe = true` in [package.metadata.xross]");
+    XrossDefaults { handle_mode, safety }
+}
+
+/// Resolves the handle mode and safety level a member falls back to,
+/// after `defaults` the crate-wide defaults and finally `Normal` / `Lock`.
+pub fn effective_defaults(defaults: XrossDefaults) -> (HandleMode, ThreadSafety) {
+    let defaults = defaults.or(crate_defaults());
+    (defaults.handle_mode.unwrap_or_default(), defaults.safety.unwrap_or(ThreadSafety::Lock))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    #[test]
+    fn layers_defaults_from_impl_type_and_crate() {
+        let impl_defaults = parse_impl_defaults(quote!(panicable));
+        let type_defaults: syn::ItemStruct = syn::parse_quote! {
+            #[xross(clonable(panicable), safety = Immutable, critical)]
+            struct S;
+        };
+        let type_defaults = extract_type_defaults(&type_defaults.attrs).unwrap();
+        let crate_defaults = parse_crate_defaults(
+            "[package]\nname = \"x\"\n[package.metadata.xross]\nsafety = \"Atomic\"\nhandle_mode = \"normal\"\n",
+        );
+
+        let layered = impl_defaults.or(type_defaults).or(crate_defaults);
+        assert_eq!(layered.handle_mode, Some(HandleMode::Panicable));
+        assert_eq!(layered.safety, Some(ThreadSafety::Immutable));
+        assert_eq!(
+            type_defaults.or(crate_defaults).handle_mode,
+            Some(HandleMode::Critical { allow_heap_access: false })
+        );
+        assert_eq!(parse_crate_defaults("[package]\nname = \"x\"\n"), XrossDefaults::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "`blocking` cannot be a default")]
+    fn rejects_blocking_crate_default() {
+        parse_crate_defaults(
+            "[package]\nname = \"x\"\n[package.metadata.xross]\nhandle_mode = \"blocking\"\n",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "requires an explicit 'unsafe' attribute")]
+    fn rejects_unacknowledged_unsafe_default() {
+        parse_impl_defaults(quote!(safety = Direct));
+    }
+}
@@ -1,5 +1,24 @@
+/// Returns the name of a struct or variant field as used in symbols, layouts and metadata.
+pub fn field_name(member: &syn::Member) -> String {
+    match member {
+        syn::Member::Named(ident) => ident.to_string(),
+        syn::Member::Unnamed(index) => positional_name(index.index as usize),
+    }
+}
+
+/// Returns the name of the field at `index`, named by `ident` or by its position.
+pub fn nth_field_name(ident: Option<&syn::Ident>, index: usize) -> String {
+    ident.map_or_else(|| positional_name(index), |ident| ident.to_string())
+}
+
+/// Returns the identifier of the positional field at `index`, its ordinal with every
+/// character that is not valid in an identifier replaced by `_` (21 → "twenty_first").
+pub fn positional_name(index: usize) -> String {
+    ordinal_name(index).replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_")
+}
+
 /// Returns the English ordinal name as a word (no digits).
-/// For example: 0 → "zeroth", 1 → "first", 21 → "twenty-first", 100 → "one hundredth"
+/// For example: 0 → "zeroth", 1 → "first", 21 → "twenty-first", 100 → "one_hundredth"
 pub fn ordinal_name(i: usize) -> String {
     if i == 0 {
         return "zeroth".to_string();
@@ -141,3 +160,19 @@ fn cardinal_to_ordinal(n: usize) -> String {
         format!("{}th", s.trim_end_matches(|c: char| c.is_alphabetic() && !c.is_whitespace()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::positional_name;
+
+    #[test]
+    fn positional_names_are_identifiers() {
+        assert_eq!(positional_name(0), "zeroth");
+        assert_eq!(positional_name(21), "twenty_first");
+        assert_eq!(positional_name(100), "one_hundredth");
+        for i in 0..2_500 {
+            let name = positional_name(i);
+            assert!(syn::parse_str::<syn::Ident>(&name).is_ok(), "{} -> {:?}", i, name);
+        }
+    }
+}
@@ -76,3 +76,635 @@ pub mod test {
         }
     }
 }
+
+#[derive(XrossClass, Clone)]
+pub struct OptionHolder {
+    #[xross_field]
+    pub opt: Option<i32>,
+}
+
+#[xross_methods]
+impl OptionHolder {
+    #[xross_new]
+    pub fn new(opt: Option<i32>) -> Self {
+        OptionHolder { opt }
+    }
+
+    #[xross_method]
+    pub fn get_opt(&self) -> Option<i32> {
+        self.opt
+    }
+}
+
+#[test]
+fn option_of_primitive_keeps_some_zero() {
+    use xross_core::XrossOption;
+
+    unsafe {
+        let ptr = xross_macros_option_holder_new(XrossOption::from(Some(0)));
+        assert_eq!(xross_macros_option_holder_get_opt(ptr).into_option(), Some(0));
+        assert_eq!(xross_macros_option_holder_property_opt_opt_get(ptr).into_option(), Some(0));
+
+        xross_macros_option_holder_property_opt_opt_set(ptr, XrossOption::from(None));
+        assert_eq!(xross_macros_option_holder_get_opt(ptr).into_option(), None);
+
+        xross_macros_option_holder_drop(ptr as *mut OptionHolder);
+    }
+}
+
+#[xross_function(package = "test_func")]
+pub fn word_lengths(a: String, b: String) -> std::collections::HashMap<String, i32> {
+    [a, b].into_iter().map(|w| (w.clone(), w.len() as i32)).collect()
+}
+
+#[test]
+fn returned_hash_map_is_readable_and_freed() {
+    use xross_core::XrossString;
+
+    unsafe {
+        let (a, b) = ("ab", "xyz");
+        let map =
+            xross_macros_test_func_word_lengths(a.as_ptr(), a.len(), 0, b.as_ptr(), b.len(), 0);
+        assert_eq!(xross_macros_test_func_word_lengths_ret_len(map), 2);
+
+        let key = "xyz";
+        let mut value = 0i32;
+        assert!(xross_macros_test_func_word_lengths_ret_get(
+            map,
+            key.as_ptr(),
+            key.len(),
+            0,
+            &mut value
+        ));
+        assert_eq!(value, 3);
+        assert!(!xross_macros_test_func_word_lengths_ret_contains(map, "q".as_ptr(), 1, 0));
+
+        let mut keys = [XrossString { ptr: std::ptr::null_mut(), len: 0, cap: 0 }; 2];
+        assert_eq!(xross_macros_test_func_word_lengths_ret_snapshot(map, keys.as_mut_ptr(), 2), 2);
+        let mut values = [0i32; 2];
+        assert_eq!(xross_macros_test_func_word_lengths_ret_values(map, values.as_mut_ptr(), 2), 2);
+        for (k, v) in keys.iter().zip(values) {
+            let k = std::str::from_utf8(std::slice::from_raw_parts(k.ptr, k.len)).unwrap();
+            assert_eq!(k.len() as i32, v);
+        }
+
+        xross_macros_test_func_word_lengths_ret_drop(map);
+    }
+}
+
+#[derive(XrossClass, Clone)]
+pub struct Tag {
+    #[xross_field]
+    pub id: i32,
+}
+
+#[xross_function(package = "test_func")]
+pub fn join_words(words: Vec<String>, sep: String) -> String {
+    words.join(&sep)
+}
+
+#[xross_function(package = "test_func")]
+pub fn split_words(s: String) -> Vec<String> {
+    s.split(' ').map(str::to_string).collect()
+}
+
+#[xross_function(package = "test_func")]
+pub fn sum_present(values: &[Option<i32>]) -> i32 {
+    values.iter().flatten().sum()
+}
+
+#[xross_function(package = "test_func")]
+pub fn tag_ids(tags: &[Tag]) -> Vec<i32> {
+    tags.iter().map(|t| t.id).collect()
+}
+
+#[test]
+fn sequences_of_non_primitives_cross_the_boundary() {
+    use std::mem::MaybeUninit;
+    use xross_core::{XrossArray, XrossOption, XrossStringView};
+
+    let view = |s: &'static str| XrossStringView { ptr: s.as_ptr(), len: s.len(), encoding: 0 };
+    unsafe {
+        let words = [view("a"), view("b"), view("c")];
+        let mut out = MaybeUninit::uninit();
+        xross_macros_test_func_join_words(out.as_mut_ptr(), words.as_ptr(), 3, "-".as_ptr(), 1, 0);
+        assert_eq!(out.assume_init().into_string(), "a-b-c");
+
+        let s = "x yy";
+        let mut out = MaybeUninit::<XrossArray>::uninit();
+        xross_macros_test_func_split_words(out.as_mut_ptr(), s.as_ptr(), s.len(), 0);
+        let parts: Vec<String> = out
+            .assume_init()
+            .into_vec::<xross_core::XrossString>()
+            .into_iter()
+            .map(|xs| xs.into_string())
+            .collect();
+        assert_eq!(parts, ["x", "yy"]);
+
+        let opts =
+            [XrossOption::from(Some(0)), XrossOption::from(None), XrossOption::from(Some(5))];
+        assert_eq!(xross_macros_test_func_sum_present(opts.as_ptr(), 3), 5);
+
+        let tags = [Box::into_raw(Box::new(Tag { id: 1 })), Box::into_raw(Box::new(Tag { id: 2 }))];
+        let handles = tags.map(|t| t as *mut std::ffi::c_void);
+        let mut out = MaybeUninit::<XrossArray>::uninit();
+        xross_macros_test_func_tag_ids(out.as_mut_ptr(), handles.as_ptr(), 2);
+        assert_eq!(out.assume_init().into_vec::<i32>(), vec![1, 2]);
+        // The slice only borrowed the objects, so the handles are still valid.
+        assert_eq!(tags.map(|t| Box::from_raw(t).id), [1, 2]);
+    }
+}
+
+#[derive(XrossClass, Clone)]
+pub struct Span {
+    #[xross_field]
+    pub bounds: (i32, i32),
+}
+
+#[xross_function(package = "test_func")]
+pub fn min_max(values: &[i32]) -> (i32, i32) {
+    (*values.iter().min().unwrap(), *values.iter().max().unwrap())
+}
+
+#[xross_function(package = "test_func")]
+pub fn describe(entry: (i32, String)) -> String {
+    format!("{}={}", entry.0, entry.1)
+}
+
+#[xross_function(package = "test_func")]
+pub fn find_entry(id: i32) -> Option<(i32, String)> {
+    (id > 0).then(|| (id, "found".to_string()))
+}
+
+#[test]
+fn tuples_cross_the_boundary_as_repr_c_structs() {
+    use std::mem::MaybeUninit;
+    use xross_core::{XrossString, XrossStringView};
+
+    #[repr(C)]
+    struct Pair<A, B>(A, B);
+
+    unsafe {
+        let values = [3, -1, 7];
+        let mut out = MaybeUninit::<Pair<i32, i32>>::uninit();
+        xross_macros_test_func_min_max(out.as_mut_ptr().cast(), values.as_ptr(), 3);
+        let Pair(min, max) = out.assume_init();
+        assert_eq!((min, max), (-1, 7));
+
+        let entry = Pair(4, XrossStringView { ptr: "four".as_ptr(), len: 4, encoding: 0 });
+        let mut out = MaybeUninit::<XrossString>::uninit();
+        xross_macros_test_func_describe(out.as_mut_ptr(), &entry as *const _ as *const _);
+        assert_eq!(out.assume_init().into_string(), "4=four");
+
+        assert!(xross_macros_test_func_find_entry(0).is_null());
+        let found =
+            Box::from_raw(xross_macros_test_func_find_entry(2) as *mut Pair<i32, XrossString>);
+        assert_eq!(found.0, 2);
+        assert_eq!(found.1.into_string(), "found");
+
+        // Registered like a constructor would, so a stale slot at a reused address is renewed.
+        let span = xross_core::xross_track_handle(Box::into_raw(Box::new(Span { bounds: (0, 0) })));
+        xross_macros_span_property_bounds_set(span, &Pair(1, 9) as *const _ as *const _);
+        let mut out = MaybeUninit::<Pair<i32, i32>>::uninit();
+        xross_macros_span_property_bounds_get(span, out.as_mut_ptr().cast());
+        let Pair(lo, hi) = out.assume_init();
+        assert_eq!((lo, hi), (1, 9));
+        xross_core::xross_release_handle(span, "span").unwrap();
+        drop(Box::from_raw(span as *mut Span));
+    }
+}
+
+#[xross_function(package = "test_func")]
+pub fn shout(s: &str) -> String {
+    s.to_uppercase()
+}
+
+#[xross_function(package = "test_func")]
+pub fn is_borrowed(s: std::borrow::Cow<'_, str>) -> bool {
+    matches!(s, std::borrow::Cow::Borrowed(_))
+}
+
+#[xross_function(package = "test_func")]
+pub fn greeting() -> &'static str {
+    "hello"
+}
+
+#[test]
+fn borrowed_strings_avoid_copies_for_ascii() {
+    use std::mem::MaybeUninit;
+    use xross_core::XrossString;
+
+    unsafe {
+        let mut out = MaybeUninit::<XrossString>::uninit();
+        xross_macros_test_func_shout(out.as_mut_ptr(), "abc".as_ptr(), 3, 0);
+        assert_eq!(out.assume_init().into_string(), "ABC");
+
+        assert!(xross_macros_test_func_is_borrowed("abc".as_ptr(), 3, 0));
+        let latin1 = [0x63, 0x61, 0x66, 0xE9];
+        assert!(!xross_macros_test_func_is_borrowed(latin1.as_ptr(), 4, 0));
+
+        let mut out = MaybeUninit::<XrossString>::uninit();
+        xross_macros_test_func_greeting(out.as_mut_ptr());
+        assert_eq!(out.assume_init().into_string(), "hello");
+    }
+}
+
+#[derive(XrossClass, Clone)]
+#[xross(shared)]
+pub struct Engine {
+    #[xross_field]
+    pub rpm: i32,
+}
+
+#[xross_function(package = "test_func")]
+pub fn shared_engine(rpm: i32) -> std::sync::Arc<Engine> {
+    std::sync::Arc::new(Engine { rpm })
+}
+
+#[xross_function(package = "test_func")]
+pub fn engine_refs(engine: std::sync::Arc<Engine>) -> usize {
+    std::sync::Arc::strong_count(&engine)
+}
+
+#[test]
+fn shared_handles_hold_one_strong_reference_each() {
+    use std::sync::Arc;
+
+    unsafe {
+        let handle = xross_macros_test_func_shared_engine(42);
+        let copy = xross_macros_engine_arc_clone(handle as *const Engine);
+        assert_eq!(copy as *mut std::ffi::c_void, handle);
+        // Two JVM handles plus the strong reference moved into the call.
+        assert_eq!(xross_macros_test_func_engine_refs(handle), 3);
+        xross_macros_engine_arc_drop(copy);
+        assert_eq!(Arc::strong_count(&Arc::from_raw(handle as *const Engine)), 1);
+    }
+}
+
+#[xross_function(package = "test_func")]
+pub fn apply_twice(f: impl Fn(i32) -> i32, x: i32) -> i32 {
+    f(f(x))
+}
+
+#[xross_function(package = "test_func")]
+pub fn count_matching(words: Vec<String>, pred: &dyn Fn(&str) -> bool) -> i32 {
+    words.iter().filter(|w| pred(w)).count() as i32
+}
+
+#[xross_function(package = "test_func")]
+pub fn report_progress(steps: i32, mut on_step: Box<dyn FnMut(i32) + Send>) {
+    for i in 1..=steps {
+        on_step(i);
+    }
+}
+
+#[test]
+fn callbacks_call_back_into_the_jvm_and_release_once() {
+    use std::ffi::c_void;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use xross_core::{XrossCallback, XrossString, XrossStringView};
+
+    struct Ctx {
+        calls: AtomicI32,
+        released: AtomicI32,
+    }
+    unsafe extern "C" fn release(ctx: *mut c_void) {
+        unsafe { &*(ctx as *const Ctx) }.released.fetch_add(1, Ordering::SeqCst);
+    }
+    unsafe extern "C" fn add_ten(ctx: *mut c_void, x: i32) -> i32 {
+        unsafe { &*(ctx as *const Ctx) }.calls.fetch_add(1, Ordering::SeqCst);
+        x + 10
+    }
+    unsafe extern "C" fn is_long(_: *mut c_void, s: XrossString) -> bool {
+        s.len > 2
+    }
+    unsafe extern "C" fn on_step(ctx: *mut c_void, step: i32) {
+        unsafe { &*(ctx as *const Ctx) }.calls.fetch_add(step, Ordering::SeqCst);
+    }
+
+    let ctx = Ctx { calls: AtomicI32::new(0), released: AtomicI32::new(0) };
+    let callback = |func: *const c_void| XrossCallback {
+        func,
+        ctx: &ctx as *const Ctx as *mut c_void,
+        release: Some(release),
+    };
+    let view = |s: &'static str| XrossStringView { ptr: s.as_ptr(), len: s.len(), encoding: 0 };
+
+    unsafe {
+        assert_eq!(xross_macros_test_func_apply_twice(callback(add_ten as *const c_void), 1), 21);
+        assert_eq!(ctx.calls.load(Ordering::SeqCst), 2);
+
+        let words = [view("a"), view("long"), view("longer")];
+        let n = xross_macros_test_func_count_matching(
+            words.as_ptr(),
+            3,
+            callback(is_long as *const c_void),
+        );
+        assert_eq!(n, 2);
+
+        xross_macros_test_func_report_progress(3, callback(on_step as *const c_void));
+        assert_eq!(ctx.calls.load(Ordering::SeqCst), 2 + 6);
+    }
+    assert_eq!(ctx.released.load(Ordering::SeqCst), 3);
+}
+
+#[xross_function(package = "test_func", panicable)]
+pub fn apply_checked(f: impl Fn(i32) -> i32, x: i32) -> i32 {
+    f(x)
+}
+
+#[test]
+fn null_callbacks_are_rejected_and_released() {
+    use std::ffi::c_void;
+    use std::mem::MaybeUninit;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use xross_core::{XrossCallback, XrossError, XrossErrorKind, XrossResult, xross_free_error};
+
+    static RELEASED: AtomicI32 = AtomicI32::new(0);
+    unsafe extern "C" fn release(_: *mut c_void) {
+        RELEASED.fetch_add(1, Ordering::SeqCst);
+    }
+
+    let callback =
+        XrossCallback { func: std::ptr::null(), ctx: std::ptr::null_mut(), release: Some(release) };
+    unsafe {
+        let mut out = MaybeUninit::<XrossResult>::uninit();
+        xross_macros_test_func_apply_checked(out.as_mut_ptr(), callback, 1);
+        let res = out.assume_init();
+        assert!(!res.is_ok);
+        let err = res.ptr as *mut XrossError;
+        assert_eq!((*err).kind, XrossErrorKind::InvalidArgument);
+        xross_free_error(err);
+    }
+    assert_eq!(RELEASED.load(Ordering::SeqCst), 1);
+}
+
+#[xross_core::xross_trait(package = "test_trait")]
+pub trait Counter {
+    fn add(&mut self, n: i32);
+    fn total(&self) -> i32;
+    fn weigh(&self, label: &str) -> usize;
+}
+
+#[xross_function(package = "test_func")]
+pub fn drive_counter(counter: &mut dyn Counter, times: i32) -> i32 {
+    for i in 1..=times {
+        counter.add(i);
+    }
+    counter.total()
+}
+
+#[xross_function(package = "test_func")]
+pub fn weigh_boxed(counter: Box<dyn Counter + Send>) -> usize {
+    counter.weigh("abcd")
+}
+
+#[test]
+fn jvm_vtables_implement_rust_traits() {
+    use std::ffi::c_void;
+    use xross_core::XrossString;
+
+    struct State {
+        total: i32,
+        released: bool,
+    }
+    unsafe extern "C" fn add(ctx: *mut c_void, n: i32) {
+        unsafe { &mut *(ctx as *mut State) }.total += n;
+    }
+    unsafe extern "C" fn total(ctx: *mut c_void) -> i32 {
+        unsafe { &*(ctx as *const State) }.total
+    }
+    unsafe extern "C" fn weigh(_: *mut c_void, label: XrossString) -> usize {
+        label.len * 10
+    }
+    unsafe extern "C" fn release(ctx: *mut c_void) {
+        unsafe { &mut *(ctx as *mut State) }.released = true;
+    }
+
+    let mut state = State { total: 0, released: false };
+    let vtable = XrossCounterVTable {
+        ctx: &mut state as *mut State as *mut c_void,
+        release: Some(release),
+        add,
+        total,
+        weigh,
+    };
+    unsafe {
+        assert!(xross_macros_test_trait_counter_new(std::ptr::null()).is_null());
+        let proxy = xross_macros_test_trait_counter_new(&vtable);
+        assert_eq!(xross_macros_test_func_drive_counter(proxy, 3), 6);
+        assert_eq!(xross_macros_test_func_weigh_boxed(proxy), 40);
+    }
+    // Passing the proxy as Box<dyn Counter> handed it to Rust, which dropped it.
+    assert!(state.released);
+}
+
+#[derive(XrossClass, Clone)]
+pub struct Meters(#[xross_field] pub f64, pub u8);
+
+#[derive(XrossClass, Clone)]
+pub struct Marker;
+
+#[test]
+fn tuple_and_unit_structs_expose_positional_fields() {
+    use std::mem::MaybeUninit;
+    use xross_core::XrossString;
+
+    let layout = |f: unsafe extern "C" fn(*mut XrossString)| unsafe {
+        let mut out = MaybeUninit::uninit();
+        f(out.as_mut_ptr());
+        out.assume_init().into_string()
+    };
+    let expected = format!(
+        "{};zeroth:{}:8;first:{}:1",
+        size_of::<Meters>(),
+        std::mem::offset_of!(Meters, 0),
+        std::mem::offset_of!(Meters, 1)
+    );
+    assert_eq!(layout(xross_macros_meters_layout), expected);
+    assert_eq!(layout(xross_macros_marker_layout), "0");
+
+    let m = Box::into_raw(Box::new(Meters(1.5, 0))) as *mut std::ffi::c_void;
+    unsafe {
+        assert_eq!(xross_macros_meters_property_zeroth_get(m), 1.5);
+        xross_macros_meters_property_zeroth_set(m, 2.0);
+        assert_eq!(Box::from_raw(m as *mut Meters).0, 2.0);
+    }
+}
+
+#[derive(XrossClass, Clone)]
+#[xross(instantiate(Buffer<f32> as FloatBuffer, Buffer<i64> as LongBuffer))]
+pub struct Buffer<T> {
+    #[xross_field]
+    pub len: usize,
+    #[xross_field]
+    pub data: Vec<T>,
+}
+
+#[xross_methods]
+impl<T: Copy + Default> Buffer<T> {
+    #[xross_new]
+    pub fn new(len: usize) -> Self {
+        Self { len, data: vec![T::default(); len] }
+    }
+
+    #[xross_method]
+    pub fn fill(&mut self, value: T) {
+        self.data.iter_mut().for_each(|v| *v = value);
+    }
+
+    #[xross_method]
+    pub fn first(&self) -> T {
+        self.data[0]
+    }
+}
+
+#[test]
+fn generic_types_are_bridged_per_instantiation() {
+    unsafe {
+        let floats = xross_macros_float_buffer_new(2);
+        xross_macros_float_buffer_fill(floats, 2.5);
+        assert_eq!(xross_macros_float_buffer_first(floats), 2.5);
+        assert_eq!(xross_macros_float_buffer_property_len_get(floats), 2);
+
+        let longs = xross_macros_long_buffer_new(3);
+        xross_macros_long_buffer_fill(longs, -7);
+        assert_eq!(xross_macros_long_buffer_first(longs), -7);
+        let data = xross_macros_long_buffer_property_data_get(longs);
+        assert_eq!(data.into_vec::<i64>(), vec![-7; 3]);
+
+        drop(Box::from_raw(floats as *mut FloatBuffer));
+        drop(Box::from_raw(longs as *mut LongBuffer));
+    }
+}
+
+#[derive(XrossClass, Clone)]
+#[xross(panicable, safety = Atomic)]
+pub struct Gauge {
+    #[xross_field]
+    pub level: i32,
+}
+
+#[xross_methods(safety = Immutable)]
+impl Gauge {
+    #[xross_new(normal)]
+    pub fn new(level: i32) -> Self {
+        Self { level }
+    }
+
+    #[xross_method]
+    pub fn checked_level(&self) -> i32 {
+        assert!(self.level >= 0, "negative level");
+        self.level
+    }
+}
+
+#[test]
+fn type_and_impl_defaults_apply_unless_a_method_overrides_them() {
+    use std::mem::MaybeUninit;
+    use xross_core::{XrossError, XrossErrorKind, XrossResult, xross_free_error};
+
+    unsafe {
+        // `normal` on the constructor overrides the type's `panicable`.
+        let gauge = xross_macros_gauge_new(-1);
+        let mut out = MaybeUninit::<XrossResult>::uninit();
+        xross_macros_gauge_checked_level(out.as_mut_ptr(), gauge);
+        let res = out.assume_init_read();
+        assert!(!res.is_ok);
+        let err = res.ptr as *mut XrossError;
+        assert_eq!((*err).kind, XrossErrorKind::Panic);
+        xross_free_error(err);
+        drop(Box::from_raw(gauge as *mut Gauge));
+    }
+}
+
+#[xross_function(package = "test_func", panicable)]
+pub fn engine_rpm(engine: &std::sync::Arc<Engine>) -> i32 {
+    engine.rpm
+}
+
+#[test]
+fn checked_handles_reject_a_handle_after_it_is_dropped() {
+    use std::mem::MaybeUninit;
+    use xross_core::{XrossError, XrossErrorKind, XrossResult, xross_free_error};
+
+    unsafe {
+        let handle = xross_macros_test_func_shared_engine(9);
+        let key = xross_core::xross_handle_key(handle);
+        assert_ne!(key, 0);
+        // Keeps the engine alive after the JVM handle is gone, so its address is not reused.
+        std::sync::Arc::increment_strong_count(handle as *const Engine);
+
+        let mut out = MaybeUninit::<XrossResult>::uninit();
+        xross_macros_test_func_engine_rpm(out.as_mut_ptr(), handle);
+        assert!(out.assume_init_read().is_ok);
+
+        // A handle to an object of another type is rejected before it is read.
+        let ticket = xross_macros_ticket_new(3);
+        xross_macros_test_func_engine_rpm(out.as_mut_ptr(), ticket);
+        let res = out.assume_init_read();
+        assert!(!res.is_ok);
+        xross_free_error(res.ptr as *mut XrossError);
+        assert_eq!(xross_macros_ticket_redeem(ticket), 3);
+
+        xross_macros_engine_arc_drop(handle as *const Engine);
+        xross_macros_test_func_engine_rpm(out.as_mut_ptr(), handle);
+        let res = out.assume_init_read();
+        assert!(!res.is_ok);
+        let err = res.ptr as *mut XrossError;
+        assert_eq!((*err).kind, XrossErrorKind::InvalidHandle);
+        let message = std::slice::from_raw_parts((*err).message.ptr, (*err).message.len);
+        let message = String::from_utf8_lossy(message);
+        assert!(message.contains("xross_macros_test_func_engine_rpm"), "{}", message);
+        xross_free_error(err);
+        let err = xross_core::xross_check_handle_key(handle, key);
+        assert_eq!((*err).kind, XrossErrorKind::InvalidHandle);
+        xross_free_error(err);
+
+        // An export without an error return skips the call and records the violation instead.
+        assert!(xross_core::xross_take_handle_error().is_null());
+        xross_macros_engine_arc_drop(handle as *const Engine);
+        let err = xross_core::xross_take_handle_error();
+        assert_eq!((*err).kind, XrossErrorKind::InvalidHandle);
+        xross_free_error(err);
+        assert!(xross_core::xross_take_handle_error().is_null());
+
+        xross_core::xross_borrow_handle(handle);
+        drop(std::sync::Arc::from_raw(handle as *const Engine));
+    }
+}
+
+#[derive(XrossClass, Clone)]
+pub struct Ticket {
+    #[xross_field]
+    pub seat: i32,
+}
+
+#[xross_methods]
+impl Ticket {
+    #[xross_new]
+    pub fn new(seat: i32) -> Self {
+        Self { seat }
+    }
+
+    #[xross_method]
+    pub fn redeem(self) -> i32 {
+        self.seat
+    }
+}
+
+#[test]
+fn live_objects_uncount_handles_consumed_by_value() {
+    use xross_core::xross_object_count;
+
+    let before = xross_object_count::<Ticket>();
+    unsafe {
+        let ticket = xross_macros_ticket_new(12);
+        assert_eq!(xross_object_count::<Ticket>().live, before.live + 1);
+        assert_eq!(xross_macros_ticket_redeem(ticket), 12);
+    }
+    let after = xross_object_count::<Ticket>();
+    assert_eq!(after.live, before.live);
+    assert_eq!(after.total, before.total + 1);
+    assert_eq!(<Ticket as xross_core::XrossObject>::SIGNATURE, "Ticket");
+}
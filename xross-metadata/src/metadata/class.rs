@@ -1,4 +1,4 @@
-use crate::{XrossField, XrossMethod};
+use crate::{HandleMode, ThreadSafety, XrossField, XrossMethod};
 use serde::{Deserialize, Serialize};
 
 /// Represents the definition of a type shared between Rust and JVM.
@@ -13,6 +13,10 @@ pub enum XrossDefinition {
     Opaque(XrossOpaque),
     /// A standalone function.
     Function(XrossFunction),
+    /// A trait that can be implemented on the JVM through a vtable.
+    Trait(XrossTrait),
+    /// An error type thrown on the JVM as one exception class per variant.
+    Error(XrossErrorDef),
 }
 
 impl XrossDefinition {
@@ -23,6 +27,36 @@ impl XrossDefinition {
             XrossDefinition::Enum(e) => &e.signature,
             XrossDefinition::Opaque(o) => &o.signature,
             XrossDefinition::Function(f) => &f.signature,
+            XrossDefinition::Trait(t) => &t.signature,
+            XrossDefinition::Error(e) => &e.signature,
+        }
+    }
+    /// Returns the generic type this definition instantiates, if any.
+    pub fn instance_of(&self) -> Option<&XrossInstantiation> {
+        match self {
+            XrossDefinition::Struct(s) => s.instance_of.as_ref(),
+            XrossDefinition::Enum(e) => e.instance_of.as_ref(),
+            _ => None,
+        }
+    }
+    /// Returns the member defaults declared on the type, if any.
+    pub fn defaults(&self) -> Option<&XrossDefaults> {
+        match self {
+            XrossDefinition::Struct(s) => s.defaults.as_ref(),
+            XrossDefinition::Enum(e) => e.defaults.as_ref(),
+            XrossDefinition::Opaque(o) => o.defaults.as_ref(),
+            _ => None,
+        }
+    }
+    /// Returns the package this definition belongs to.
+    pub fn package_name(&self) -> &str {
+        match self {
+            XrossDefinition::Struct(s) => &s.package_name,
+            XrossDefinition::Enum(e) => &e.package_name,
+            XrossDefinition::Opaque(o) => &o.package_name,
+            XrossDefinition::Function(f) => &f.package_name,
+            XrossDefinition::Trait(t) => &t.package_name,
+            XrossDefinition::Error(e) => &e.package_name,
         }
     }
     /// Returns the name of this definition.
@@ -32,6 +66,8 @@ impl XrossDefinition {
             XrossDefinition::Enum(e) => &e.name,
             XrossDefinition::Opaque(o) => &o.name,
             XrossDefinition::Function(f) => &f.name,
+            XrossDefinition::Trait(t) => &t.name,
+            XrossDefinition::Error(e) => &e.name,
         }
     }
 }
@@ -48,6 +84,13 @@ pub struct XrossStruct {
     pub methods: Vec<XrossMethod>,
     pub docs: Vec<String>,
     pub is_copy: bool,
+    /// Handed to the JVM as `Arc<Self>`; `{symbol_prefix}_arc_clone` / `_arc_drop` are exported.
+    #[serde(default)]
+    pub is_shared: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_of: Option<XrossInstantiation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<XrossDefaults>,
 }
 
 /// Metadata for a Rust enum to be bridged to JVM.
@@ -62,6 +105,49 @@ pub struct XrossEnum {
     pub methods: Vec<XrossMethod>,
     pub docs: Vec<String>,
     pub is_copy: bool,
+    /// Handed to the JVM as `Arc<Self>`; `{symbol_prefix}_arc_clone` / `_arc_drop` are exported.
+    #[serde(default)]
+    pub is_shared: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance_of: Option<XrossInstantiation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<XrossDefaults>,
+}
+
+/// Handle mode and safety level used by the members of a type that do not set their own.
+///
+/// Set with `#[xross(...)]` on the type; unset values fall back to the crate-wide defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct XrossDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handle_mode: Option<HandleMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety: Option<ThreadSafety>,
+}
+
+impl XrossDefaults {
+    /// Fills the values left unset in `self` from `fallback`.
+    pub fn or(self, fallback: XrossDefaults) -> XrossDefaults {
+        XrossDefaults {
+            handle_mode: self.handle_mode.or(fallback.handle_mode),
+            safety: self.safety.or(fallback.safety),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handle_mode.is_none() && self.safety.is_none()
+    }
+}
+
+/// A concrete instantiation of a generic Rust type, e.g. `Buffer<f32>` bridged as `FloatBuffer`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct XrossInstantiation {
+    /// Name of the generic type (`Buffer`).
+    pub generic: String,
+    /// Concrete type arguments as Rust source (`["f32"]`).
+    pub args: Vec<String>,
 }
 
 /// Metadata for a single variant of an enum.
@@ -86,6 +172,11 @@ pub struct XrossOpaque {
     pub docs: Vec<String>,
     pub is_clonable: bool,
     pub is_copy: bool,
+    /// Handed to the JVM as `Arc<Self>`; `{symbol_prefix}_arc_clone` / `_arc_drop` are exported.
+    #[serde(default)]
+    pub is_shared: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defaults: Option<XrossDefaults>,
 }
 
 /// Metadata for a standalone function.
@@ -99,3 +190,50 @@ pub struct XrossFunction {
     pub method: XrossMethod,
     pub docs: Vec<String>,
 }
+
+/// Metadata for a Rust trait implemented on the JVM.
+///
+/// The JVM fills a `#[repr(C)]` vtable laid out as `ctx`, `release`, followed by one
+/// function pointer per method in declaration order. Each function takes `ctx` first.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct XrossTrait {
+    pub signature: String,
+    pub symbol_prefix: String,
+    pub package_name: String,
+    pub name: String,
+    /// Methods in vtable slot order. `symbol` is the name of the slot.
+    pub methods: Vec<XrossMethod>,
+    pub docs: Vec<String>,
+}
+
+/// Metadata for an error type deriving `XrossError`.
+///
+/// Errors cross the boundary as owned handles. `{symbol_prefix}_describe` fills an
+/// `XrossErrorInfo` with the variant index, the message and the `source()` chain.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct XrossErrorDef {
+    pub signature: String,
+    pub symbol_prefix: String,
+    pub package_name: String,
+    pub name: String,
+    /// Variants in declaration order. A struct is recorded as a single variant named after the type.
+    pub variants: Vec<XrossErrorVariant>,
+    pub docs: Vec<String>,
+}
+
+/// Metadata for a single variant of an error type.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct XrossErrorVariant {
+    pub name: String,
+    /// Message template from `#[error("...")]`, if any. The runtime message is always the `Display` output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub fields: Vec<XrossField>,
+    /// Field holding the underlying error (`#[source]`, `#[from]` or a field named `source`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub docs: Vec<String>,
+}
@@ -1,4 +1,8 @@
+mod collection;
+mod iterator;
 mod types;
+pub use collection::*;
+pub use iterator::*;
 pub use types::*;
 
 use crate::metadata::ThreadSafety;
@@ -27,6 +31,15 @@ pub struct XrossMethod {
     pub args: Vec<XrossField>,
     /// Return type of the method.
     pub ret: XrossType,
+    /// Helper exports for reading and releasing a returned collection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ret_accessors: Option<Box<XrossCollectionAccessors>>,
+    /// Helper exports for consuming and releasing a returned iterator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ret_iterator: Option<Box<XrossIteratorAccessors>>,
+    /// Export of an async method that reports completion through an upcall instead of polling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify_symbol: Option<String>,
     /// Documentation comments from Rust source.
     pub docs: Vec<String>,
     /// Thread safety level for calling this method.
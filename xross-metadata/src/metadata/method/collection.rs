@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// Native helper exports generated for a collection returned by a method.
+/// Non-primitive elements handed out by these helpers are borrowed from the
+/// collection and remain valid until `drop` is called.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct XrossCollectionAccessors {
+    /// Symbol returning the number of elements.
+    pub len: String,
+    /// Symbol looking up a value by key (maps) or by index (sequences).
+    pub get: Option<String>,
+    /// Symbol testing whether a key (maps) or an element (sets) is present.
+    pub contains: Option<String>,
+    /// Symbol copying the elements (or the keys of a map) into a caller-provided array.
+    pub snapshot: String,
+    /// Symbol copying the values of a map into a caller-provided array.
+    pub values: Option<String>,
+    /// Symbol releasing the collection.
+    pub drop: String,
+}
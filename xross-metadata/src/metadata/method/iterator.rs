@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Native helper exports generated for an iterator returned by a method.
+/// Items handed out by `next` and `next_chunk` are owned by the caller.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct XrossIteratorAccessors {
+    /// Symbol advancing the iterator and writing the next item to an out pointer.
+    pub next: String,
+    /// Symbol returning the lower bound of the remaining length and writing the upper bound.
+    pub size_hint: String,
+    /// Symbol moving up to `cap` primitive items into a caller-provided array.
+    pub next_chunk: Option<String>,
+    /// Symbol releasing the iterator.
+    pub drop: String,
+}
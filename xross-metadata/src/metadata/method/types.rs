@@ -12,6 +12,9 @@ pub enum XrossMethodType {
     /// An instance method that consumes ownership of self.
     /// The handle on the JVM side must be invalidated after this call.
     OwnedInstance,
+    /// An instance method that takes a new strong reference to self (`self: Arc<Self>`).
+    /// The handle on the JVM side must be a shared handle and stays valid.
+    SharedInstance,
 }
 
 /// Defines how the native method handle should be invoked.
@@ -30,4 +33,7 @@ pub enum HandleMode {
     },
     /// Can panic and should be caught to propagate as an exception to JVM.
     Panicable,
+    /// Synchronous method run on a blocking thread pool.
+    /// Exported like an async method, returning an `XrossTask`.
+    Blocking,
 }
@@ -11,6 +11,16 @@ pub enum Ownership {
     Ref,
     /// Mutable reference (&mut T).
     MutRef,
+    /// Atomically reference-counted value (Arc<T>).
+    /// The JVM handle holds one strong reference.
+    Shared,
+}
+
+impl Ownership {
+    /// Returns true if the value is behind a reference-counted pointer.
+    pub fn is_shared(&self) -> bool {
+        matches!(self, Ownership::Shared)
+    }
 }
 
 /// Represents the data types supported by the Xross bridge.
@@ -49,8 +59,10 @@ pub enum XrossType {
     /// UTF-8 string.
     String,
     /// A slice of values (&[T]).
+    /// Passed from the JVM as a pointer to an array of element representations and its length.
     Slice(Box<XrossType>),
     /// An owned vector of values (Vec<T>).
+    /// Returned to the JVM as an `XrossArray` whose non-primitive elements are owned by the caller.
     Vec(Box<XrossType>),
     /// Double-ended queue (VecDeque<T>).
     VecDeque(Box<XrossType>),
@@ -74,6 +86,8 @@ pub enum XrossType {
         ownership: Ownership,
     },
     /// An optional value.
+    /// Primitive payloads are passed as a tagged `XrossOption { is_some, value }`,
+    /// all other payloads as a nullable pointer.
     Option(Box<XrossType>),
     /// A result value that can be either Ok or Err.
     Result {
@@ -82,19 +96,58 @@ pub enum XrossType {
         /// Type of the error value.
         err: Box<XrossType>,
     },
+    /// A tuple of values ((A, B, ...)).
+    /// Passed as a `#[repr(C)]` struct whose fields are the element representations.
+    Tuple(Vec<XrossType>),
+    /// A callback supplied by the JVM (Fn / FnMut / FnOnce).
+    /// Passed as an `XrossCallback` upcall stub and released once Rust drops the closure.
+    Function {
+        /// Types of the arguments Rust passes to the callback.
+        args: Vec<XrossType>,
+        /// Type of the value returned by the callback.
+        ret: Box<XrossType>,
+    },
     /// An asynchronous computation.
     Async(Box<XrossType>),
+    /// An asynchronous stream of values (impl Stream<Item = T>).
+    /// Returned as an `XrossStream` handle that the JVM polls item by item.
+    Stream(Box<XrossType>),
+    /// A lazily consumed sequence of values (impl Iterator<Item = T>, std::vec::IntoIter<T>).
+    /// Returned as an opaque handle read through the `ret_iterator` helper exports.
+    Iterator(Box<XrossType>),
 }
 
 impl XrossType {
+    /// Returns true if the type is a primitive passed by value across the FFI boundary.
+    pub fn is_primitive(&self) -> bool {
+        matches!(
+            self,
+            XrossType::Bool
+                | XrossType::I8
+                | XrossType::U8
+                | XrossType::I16
+                | XrossType::U16
+                | XrossType::I32
+                | XrossType::U32
+                | XrossType::I64
+                | XrossType::U64
+                | XrossType::ISize
+                | XrossType::USize
+                | XrossType::F32
+                | XrossType::F64
+        )
+    }
+
     /// Returns true if the type represents an owned value.
     pub fn is_owned(&self) -> bool {
         match self {
             XrossType::Object { ownership, .. } => {
-                matches!(ownership, Ownership::Owned | Ownership::Boxed)
+                matches!(ownership, Ownership::Owned | Ownership::Boxed | Ownership::Shared)
             }
+            XrossType::Option(inner) => !inner.is_primitive(),
             XrossType::Result { .. }
-            | XrossType::Option(_)
+            | XrossType::Stream(_)
+            | XrossType::Iterator(_)
             | XrossType::Vec(_)
             | XrossType::VecDeque(_)
             | XrossType::LinkedList(_)
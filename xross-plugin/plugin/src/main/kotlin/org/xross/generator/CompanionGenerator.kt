@@ -1,6 +1,7 @@
 package org.xross.generator
 
 import com.squareup.kotlinpoet.*
+import org.xross.generator.util.CollectionHandles
 import org.xross.generator.util.FFMConstants
 import org.xross.generator.util.GeneratorUtils
 import org.xross.helper.StringHelper.toCamelCase
@@ -49,7 +50,7 @@ object CompanionGenerator {
             when (meta) {
                 is XrossDefinition.Struct -> LayoutGenerator.buildStructLayoutInit(init, meta)
                 is XrossDefinition.Enum -> LayoutGenerator.buildEnumLayoutInit(init, meta)
-                is XrossDefinition.Opaque -> {}
+                else -> {}
             }
 
             init.beginControlFlow("if (layoutRaw != %T.NULL)", MEMORY_SEGMENT)
@@ -87,6 +88,9 @@ object CompanionGenerator {
 
         if (meta !is XrossDefinition.Function) {
             handles.addAll(listOf("dropHandle", "layoutHandle", "xrossFreeStringHandle"))
+            if (meta.isShared) {
+                handles.add("arcDropHandle")
+            }
             if (meta.methods.any { it.name == "clone" }) {
                 handles.add("cloneHandle")
             }
@@ -130,10 +134,18 @@ object CompanionGenerator {
                     addPropertyHandles(handles, field, baseCamel, isOpaque = true)
                 }
             }
-            is XrossDefinition.Function -> {}
+            is XrossDefinition.Function, is XrossDefinition.Trait, is XrossDefinition.Error -> {}
         }
 
-        meta.methods.filter { !it.isConstructor }.forEach { handles.add("${it.name.toCamelCase()}Handle") }
+        meta.methods.filter { !it.isConstructor }.forEach { method ->
+            val camel = method.name.toCamelCase()
+            handles.add("${camel}Handle")
+            if (method.ret is XrossType.Iterator && method.retIterator != null) {
+                handles.add("${camel}NextHandle")
+                handles.add("${camel}IterDropHandle")
+            }
+            if (method.retAccessors != null) handles.addAll(CollectionHandles(method).all)
+        }
 
         handles.distinct().forEach { name ->
             builder.addProperty(PropertySpec.builder(name, HANDLE_TYPE, KModifier.INTERNAL).addAnnotation(JVM_FIELD).mutable().build())
@@ -159,8 +171,13 @@ object CompanionGenerator {
                 handles.add("${baseCamel}ResGetHandle")
                 handles.add("${baseCamel}ResSetHandle")
             }
+            is XrossType.Tuple -> {
+                handles.add("${baseCamel}GetHandle")
+                handles.add("${baseCamel}SetHandle")
+            }
             else -> {
-                if (isOpaque) {
+                // Arc<T> フィールドは強参照を取るため、構造体でもアクセサ経由で読み書きする
+                if (isOpaque || field.ty.isShared) {
                     handles.add("${baseCamel}GetHandle")
                     handles.add("${baseCamel}SetHandle")
                 }
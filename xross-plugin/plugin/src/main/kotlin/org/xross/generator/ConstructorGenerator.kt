@@ -81,7 +81,7 @@ object ConstructorGenerator {
             Triple::class.asTypeName(),
         )
 
-        if (method.args.any { it.ty is XrossType.RustString || it.ty is XrossType.Optional || it.ty is XrossType.Result }) {
+        if (GeneratorUtils.needsArgumentArena(method.args)) {
             body.endControlFlow()
         }
 
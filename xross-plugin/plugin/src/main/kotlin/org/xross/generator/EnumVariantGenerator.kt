@@ -59,7 +59,7 @@ object EnumVariantGenerator {
                             handleMode = org.xross.structures.HandleMode.Normal,
                         )
 
-                        if (fields.any { it.ty is XrossType.RustString || it.ty is XrossType.Optional || it.ty is XrossType.Result }) {
+                        if (GeneratorUtils.needsArgumentArena(fields)) {
                             endControlFlow()
                         }
 
@@ -97,6 +97,7 @@ object EnumVariantGenerator {
                 .addStatement("if (ptr == %T.NULL) throw %T(%S)", MEMORY_SEGMENT, NullPointerException::class.asTypeName(), "Pointer is NULL")
                 .addStatement("val outBuf = java.lang.foreign.Arena.ofAuto().allocate(%L)", FFMConstants.XROSS_STRING_LAYOUT_CODE)
                 .addStatement("getVariantNameHandle.invokeExact(outBuf, ptr)")
+                .addHandleCheck(basePackage)
                 .addRustStringResolution("outBuf", "n", basePackage = basePackage)
                 .addStatement("n")
                 .endControlFlow()
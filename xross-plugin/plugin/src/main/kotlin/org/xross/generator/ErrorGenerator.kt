@@ -0,0 +1,136 @@
+package org.xross.generator
+
+import com.squareup.kotlinpoet.*
+import com.squareup.kotlinpoet.ParameterizedTypeName.Companion.parameterizedBy
+import org.xross.generator.util.FFMConstants
+import org.xross.generator.util.GeneratorUtils
+import org.xross.structures.XrossDefinition
+import java.io.File
+import java.lang.foreign.Arena
+import java.lang.foreign.MemorySegment
+import java.lang.foreign.SymbolLookup
+import java.lang.invoke.MethodHandle
+
+/**
+ * Generates the Kotlin side of a `#[derive(XrossError)]` type: an exception class per variant,
+ * built from an error handle by `fromHandle`.
+ */
+object ErrorGenerator {
+    private val MEMORY_SEGMENT = MemorySegment::class.asTypeName()
+    private val HANDLE_TYPE = MethodHandle::class.asClassName()
+    private val JVM_FIELD = ClassName("kotlin.jvm", "JvmField")
+    private val STRING_LIST = List::class.asClassName().parameterizedBy(String::class.asTypeName())
+
+    /** Size of `XrossErrorInfo { variant: u32, message: XrossString, sources: XrossArray }`. */
+    private const val INFO_SIZE = 72L
+
+    fun generate(meta: XrossDefinition.Error, outputDir: File, targetPackage: String, basePackage: String) {
+        val runtimePkg = "$basePackage.xross.runtime"
+        val errors = ClassName(runtimePkg, "XrossErrors")
+        val selfType = GeneratorUtils.getClassName(meta.signature, basePackage)
+        // 構造体は型名の単一バリアントとして記録される
+        val isStruct = meta.variants.size == 1 && meta.variants[0].name == meta.name
+
+        val init = CodeBlock.builder()
+            .addStatement("val lookup = %T.loaderLookup()", SymbolLookup::class.asTypeName())
+            .addStatement(
+                "this.describeHandle = linker.downcallHandle(lookup.find(%S).get(), %T.of(%M, %M, %M))",
+                "${meta.symbolPrefix}_describe",
+                FFMConstants.FUNCTION_DESCRIPTOR,
+                FFMConstants.JAVA_BYTE,
+                FFMConstants.ADDRESS,
+                FFMConstants.ADDRESS,
+            )
+            .addStatement(
+                "this.dropHandle = linker.downcallHandle(lookup.find(%S).get(), %T.ofVoid(%M))",
+                "${meta.symbolPrefix}_drop",
+                FFMConstants.FUNCTION_DESCRIPTOR,
+                FFMConstants.ADDRESS,
+            )
+
+        val fromHandle = CodeBlock.builder()
+            .beginControlFlow("if (ptr == %T.NULL)", MEMORY_SEGMENT)
+            .addStatement("throw %T(%S)", NullPointerException::class.asTypeName(), "Null ${meta.name} handle")
+            .endControlFlow()
+            .beginControlFlow("val (variant, message, sources) = try")
+            .beginControlFlow("%T.ofConfined().use { arena ->", Arena::class)
+            .addStatement("val info = arena.allocate(%LL, 8L)", INFO_SIZE)
+            .addStatement("check(describeHandle.invokeExact(info, ptr) as Byte != (0).toByte()) { %S }", "Failed to describe ${meta.name}")
+            .addStatement(
+                "Triple(info.get(%M, 0L), %T.takeString(info.asSlice(8L, 24L)), %T.takeStrings(info.asSlice(32L, 40L)))",
+                FFMConstants.JAVA_INT,
+                errors,
+                errors,
+            )
+            .endControlFlow()
+            .nextControlFlow("finally")
+            .addStatement("%T.invokeDrop(dropHandle, ptr)", ClassName(runtimePkg, "XrossRuntime"))
+            .endControlFlow()
+        if (isStruct) {
+            fromHandle.addStatement("return %T(message, sources)", selfType)
+        } else {
+            fromHandle.beginControlFlow("return when (variant)")
+            meta.variants.forEachIndexed { i, v ->
+                fromHandle.addStatement("%L -> %T(message, sources)", i, selfType.nestedClass(v.name))
+            }
+            fromHandle.addStatement("else -> throw %T(%P)", IllegalStateException::class.asTypeName(), "Unknown ${meta.name} variant \$variant")
+            fromHandle.endControlFlow()
+        }
+
+        val companion = TypeSpec.companionObjectBuilder()
+            .addProperty(PropertySpec.builder("linker", java.lang.foreign.Linker::class, KModifier.PRIVATE).initializer("%T.nativeLinker()", java.lang.foreign.Linker::class).build())
+            .addProperty(PropertySpec.builder("describeHandle", HANDLE_TYPE, KModifier.PRIVATE).mutable().build())
+            .addProperty(PropertySpec.builder("dropHandle", HANDLE_TYPE, KModifier.INTERNAL).addAnnotation(JVM_FIELD).mutable().build())
+            .addInitializerBlock(init.build())
+            .addFunction(
+                FunSpec.builder("fromHandle")
+                    .addKdoc("Returns the exception for the error handle [ptr], which is released.\n")
+                    .addParameter("ptr", MEMORY_SEGMENT)
+                    .returns(selfType)
+                    .addCode(fromHandle.build())
+                    .build(),
+            )
+            .build()
+
+        fun constructor(visibility: KModifier) = FunSpec.constructorBuilder()
+            .addModifiers(visibility)
+            .addParameter("message", String::class)
+            .addParameter("sources", STRING_LIST)
+            .build()
+        val classBuilder = TypeSpec.classBuilder(selfType)
+            .addKdoc(meta.docs.joinToString("\n"))
+            .superclass(ClassName(runtimePkg, "XrossException"))
+            .addSuperclassConstructorParameter("message")
+            .addSuperclassConstructorParameter("sources")
+            // sealed クラスのコンストラクタは internal にできない
+            .primaryConstructor(constructor(if (isStruct) KModifier.INTERNAL else KModifier.PROTECTED))
+            .addProperty(
+                PropertySpec.builder("message", String::class, KModifier.OVERRIDE)
+                    .addKdoc("The `Display` output of the error.\n")
+                    .initializer("message").build(),
+            )
+
+        if (!isStruct) {
+            classBuilder.addModifiers(KModifier.SEALED)
+            meta.variants.forEach { v ->
+                classBuilder.addType(
+                    TypeSpec.classBuilder(v.name)
+                        .addKdoc(v.docs.joinToString("\n"))
+                        .superclass(selfType)
+                        .addSuperclassConstructorParameter("message")
+                        .addSuperclassConstructorParameter("sources")
+                        .primaryConstructor(constructor(KModifier.INTERNAL))
+                        .build(),
+                )
+            }
+        }
+        classBuilder.addType(companion)
+
+        val fileSpec = FileSpec.builder(targetPackage, selfType.simpleName)
+            .addType(classBuilder.build())
+            .indent("    ")
+            .build()
+
+        GeneratorUtils.writeToDisk(fileSpec, outputDir)
+    }
+}
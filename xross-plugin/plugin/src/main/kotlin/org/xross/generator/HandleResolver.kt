@@ -1,16 +1,15 @@
 package org.xross.generator
 
 import com.squareup.kotlinpoet.CodeBlock
-import com.squareup.kotlinpoet.MemberName
 import com.squareup.kotlinpoet.asTypeName
 import com.squareup.kotlinpoet.joinToCode
+import org.xross.generator.util.CollectionHandles
 import org.xross.generator.util.FFMConstants
 import org.xross.generator.util.FFMConstants.ADDRESS
 import org.xross.generator.util.FFMConstants.FUNCTION_DESCRIPTOR
 import org.xross.generator.util.FFMConstants.JAVA_BYTE
 import org.xross.generator.util.FFMConstants.JAVA_INT
 import org.xross.generator.util.FFMConstants.JAVA_LONG
-import org.xross.generator.util.FFMConstants.VAL_LAYOUT
 import org.xross.generator.util.GeneratorUtils
 import org.xross.helper.StringHelper.toCamelCase
 import org.xross.structures.*
@@ -46,13 +45,21 @@ object HandleResolver {
                 }
                 init.addStatement("this.${suffix}Handle = linker.downcallHandle(lookup.find(%S).get(), %L%L)", symbol, desc, options)
             }
+            if (meta.isShared) {
+                init.addStatement(
+                    "this.arcDropHandle = linker.downcallHandle(lookup.find(%S).get(), %T.ofVoid(%M))",
+                    "${meta.symbolPrefix}_arc_drop",
+                    FUNCTION_DESCRIPTOR,
+                    ADDRESS,
+                )
+            }
         }
 
         when (meta) {
             is XrossDefinition.Struct -> resolveStructHandles(init, meta)
             is XrossDefinition.Enum -> resolveEnumHandles(init, meta)
             is XrossDefinition.Opaque -> resolveOpaqueHandles(init, meta)
-            is XrossDefinition.Function -> {}
+            is XrossDefinition.Function, is XrossDefinition.Trait, is XrossDefinition.Error -> {}
         }
 
         resolveMethodHandles(init, meta)
@@ -135,10 +142,11 @@ object HandleResolver {
             val baseCamel = field.name.toCamelCase()
             when (field.ty) {
                 is XrossType.RustString -> {
-                    addGetterSetter(init, prefix, field.name, baseCamel, "str", "ADDRESS")
+                    addGetterSetter(init, prefix, field.name, baseCamel, "str", FFMConstants.XROSS_STRING_LAYOUT_CODE)
                 }
                 is XrossType.Optional -> {
-                    addGetterSetter(init, prefix, field.name, baseCamel, "opt", "ADDRESS")
+                    // プリミティブの Option は XrossOption 構造体として値渡しされる
+                    addGetterSetter(init, prefix, field.name, baseCamel, "opt", field.ty.layoutCode)
                 }
                 is XrossType.Result -> {
                     val getSymbol = "${prefix}_property_${field.name}_res_get"
@@ -158,8 +166,25 @@ object HandleResolver {
                         FFMConstants.XROSS_RESULT_LAYOUT_CODE,
                     )
                 }
+                is XrossType.Tuple -> {
+                    // タプルは #[repr(C)] 構造体へのポインタで受け渡す
+                    init.addStatement(
+                        "this.${baseCamel}GetHandle = linker.downcallHandle(lookup.find(%S).get(), %T.ofVoid(%M, %M))",
+                        "${prefix}_property_${field.name}_get",
+                        FUNCTION_DESCRIPTOR,
+                        ADDRESS,
+                        ADDRESS,
+                    )
+                    init.addStatement(
+                        "this.${baseCamel}SetHandle = linker.downcallHandle(lookup.find(%S).get(), %T.ofVoid(%M, %M))",
+                        "${prefix}_property_${field.name}_set",
+                        FUNCTION_DESCRIPTOR,
+                        ADDRESS,
+                        ADDRESS,
+                    )
+                }
                 else -> {
-                    if (isOpaque) {
+                    if (isOpaque || field.ty.isShared) {
                         val getSymbol = "${prefix}_property_${field.name}_get"
                         val setSymbol = "${prefix}_property_${field.name}_set"
                         init.addStatement(
@@ -188,20 +213,17 @@ object HandleResolver {
         rawName: String,
         camelName: String,
         suffix: String,
-        retLayout: String,
+        valueLayout: CodeBlock,
     ) {
         val isStr = suffix == "str"
         val getSymbol = "${prefix}_property_${rawName}_${suffix}_get"
         val setSymbol = "${prefix}_property_${rawName}_${suffix}_set"
 
-        val getRetLayout = if (isStr) FFMConstants.XROSS_STRING_LAYOUT_CODE else MemberName(VAL_LAYOUT, retLayout)
-        val setArgLayout = if (isStr) FFMConstants.XROSS_STRING_VIEW_LAYOUT_CODE else MemberName(VAL_LAYOUT, "ADDRESS")
-
         init.addStatement(
             "this.${camelName}${suffix.replaceFirstChar { it.uppercase() }}GetHandle = linker.downcallHandle(lookup.find(%S).get(), %T.of(%L, %M))",
             getSymbol,
             FUNCTION_DESCRIPTOR,
-            getRetLayout,
+            valueLayout,
             ADDRESS,
         )
         init.addStatement(
@@ -209,7 +231,7 @@ object HandleResolver {
             setSymbol,
             FUNCTION_DESCRIPTOR,
             ADDRESS,
-            if (isStr) CodeBlock.of("%M, %M, %M", ADDRESS, JAVA_LONG, JAVA_BYTE) else setArgLayout,
+            if (isStr) CodeBlock.of("%M, %M, %M", ADDRESS, JAVA_LONG, JAVA_BYTE) else valueLayout,
         )
     }
 
@@ -221,7 +243,11 @@ object HandleResolver {
             if (method.methodType != XrossMethodType.Static) args.add(CodeBlock.of("%M", ADDRESS))
             args.addAll(getArgLayouts(method.args))
 
-            val isComplexRet = method.ret is XrossType.RustString || method.isAsync
+            val isComplexRet = method.ret is XrossType.RustString ||
+                method.ret is XrossType.Vec ||
+                method.ret is XrossType.Tuple ||
+                method.ret is XrossType.Stream ||
+                method.isAsync
 
             val isPanicable = method.handleMode is HandleMode.Panicable
             val desc = if (method.ret is XrossType.Void && !method.isAsync && !isPanicable) {
@@ -249,6 +275,65 @@ object HandleResolver {
                 desc,
                 options,
             )
+
+            val iterator = method.retIterator
+            if (method.ret is XrossType.Iterator && iterator != null) {
+                // 要素は out ポインタに書き込まれ、所有権は呼び出し側に移る
+                val camel = method.name.toCamelCase()
+                init.addStatement(
+                    "this.${camel}NextHandle = linker.downcallHandle(lookup.find(%S).get(), %T.of(%M, %M, %M))",
+                    iterator.next,
+                    FUNCTION_DESCRIPTOR,
+                    JAVA_BYTE,
+                    ADDRESS,
+                    ADDRESS,
+                )
+                init.addStatement(
+                    "this.${camel}IterDropHandle = linker.downcallHandle(lookup.find(%S).get(), %T.ofVoid(%M))",
+                    iterator.drop,
+                    FUNCTION_DESCRIPTOR,
+                    ADDRESS,
+                )
+            }
+            if (method.retAccessors != null) resolveCollectionHandles(init, method)
+        }
+    }
+
+    /**
+     * Binds the `retAccessors` exports of a method returning a collection.
+     * Elements are written to a caller-provided buffer, and lookups return whether one was found.
+     */
+    private fun resolveCollectionHandles(init: CodeBlock.Builder, method: XrossMethod) {
+        val handles = CollectionHandles(method)
+        val accessors = handles.accessors
+        val coll = checkNotNull(GeneratorUtils.returnedCollection(method.ret)) { "${method.name} returns no collection" }
+        val key = when (coll) {
+            is XrossType.HashMap -> coll.key
+            is XrossType.BTreeMap -> coll.key
+            is XrossType.HashSet -> coll.inner
+            is XrossType.BTreeSet -> coll.inner
+            else -> XrossType.USize
+        }
+        val keyLayouts = when {
+            key is XrossType.RustString -> listOf(CodeBlock.of("%M", ADDRESS), CodeBlock.of("%M", JAVA_LONG), CodeBlock.of("%M", JAVA_BYTE))
+            key is XrossType.Object -> listOf(CodeBlock.of("%M", ADDRESS))
+            else -> listOf(CodeBlock.of("%M", key.layoutMember))
+        }
+        fun bind(handle: String, symbol: String, desc: CodeBlock) {
+            init.addStatement("this.%L = linker.downcallHandle(lookup.find(%S).get(), %L)", handle, symbol, desc)
+        }
+        val copy = CodeBlock.of("%T.of(%M, %M, %M, %M)", FUNCTION_DESCRIPTOR, JAVA_LONG, ADDRESS, ADDRESS, JAVA_LONG)
+        bind(handles.len, accessors.len, CodeBlock.of("%T.of(%M, %M)", FUNCTION_DESCRIPTOR, JAVA_LONG, ADDRESS))
+        bind(handles.snapshot, accessors.snapshot, copy)
+        handles.values?.let { bind(it, accessors.values!!, copy) }
+        handles.get?.let {
+            val args = listOf(CodeBlock.of("%M", ADDRESS)) + keyLayouts + CodeBlock.of("%M", ADDRESS)
+            bind(it, accessors.get!!, CodeBlock.of("%T.of(%M, %L)", FUNCTION_DESCRIPTOR, JAVA_BYTE, args.joinToCode(", ")))
+        }
+        handles.contains?.let {
+            val args = listOf(CodeBlock.of("%M", ADDRESS)) + keyLayouts
+            bind(it, accessors.contains!!, CodeBlock.of("%T.of(%M, %L)", FUNCTION_DESCRIPTOR, JAVA_BYTE, args.joinToCode(", ")))
         }
+        bind(handles.drop, accessors.drop, CodeBlock.of("%T.ofVoid(%M)", FUNCTION_DESCRIPTOR, ADDRESS))
     }
 }
@@ -149,10 +149,11 @@ object InvocationGenerator {
         val flagType = ClassName("$basePackage.xross.runtime", "AliveFlag")
         val runtimePkg = "$basePackage.xross.runtime"
         val isPanicable = method.handleMode is HandleMode.Panicable
+        val collection = method.retAccessors?.let { CollectionHandles(method) }
 
-        fun getExprs(type: TypeName) = Triple(
+        fun getExprs(type: TypeName, dropHandleName: String = "dropHandle") = Triple(
             if (type == selfType) CodeBlock.of("STRUCT_SIZE") else CodeBlock.of("%T.STRUCT_SIZE", type),
-            if (type == selfType) CodeBlock.of("dropHandle") else CodeBlock.of("%T.dropHandle", type),
+            if (type == selfType) CodeBlock.of(dropHandleName) else CodeBlock.of("%T.%L", type, dropHandleName),
             if (type == selfType) CodeBlock.of("fromPointer") else CodeBlock.of("%T.fromPointer", type),
         )
 
@@ -162,19 +163,52 @@ object InvocationGenerator {
             body.addStatement("val taskPtr = task.get(%M, 0L)", ADDRESS)
             body.addStatement("val pollFnPtr = task.get(%M, 8L)", ADDRESS)
             body.addStatement("val dropFnPtr = task.get(%M, 16L)", ADDRESS)
+            body.addStatement("val statusFnPtr = task.get(%M, 32L)", ADDRESS)
 
             body.addStatement("val pollFn = linker.downcallHandle(pollFnPtr, %T.of(%L, %M))", FFMConstants.FUNCTION_DESCRIPTOR, FFMConstants.XROSS_RESULT_LAYOUT_CODE, ADDRESS)
             body.addStatement("val dropFn = linker.downcallHandle(dropFnPtr, %T.ofVoid(%M))", FFMConstants.FUNCTION_DESCRIPTOR, ADDRESS)
-
-            body.beginControlFlow("%T.awaitFuture(taskPtr, pollFn, dropFn)", ClassName(runtimePkg, "XrossAsync"))
-            body.addResultVariantResolution(
-                method.ret,
-                "it",
-                returnType,
-                selfType,
-                basePackage,
-                "dropHandle",
+            body.addStatement("val statusFn = linker.downcallHandle(statusFnPtr, %T.of(%M, %M))", FFMConstants.FUNCTION_DESCRIPTOR, FFMConstants.JAVA_BYTE, ADDRESS)
+
+            val asyncRet = method.ret
+            // Err は awaitFuture が例外として投げるので、パニック以外は Result.failure に戻す
+            if (asyncRet is XrossType.Result) body.beginControlFlow("try")
+            body.beginControlFlow(
+                "%T.awaitFuture(taskPtr, pollFn, statusFn, dropFn, %L)",
+                ClassName(runtimePkg, "XrossAsync"),
+                if (asyncRet is XrossType.Result) errorValueMapper(asyncRet.err, selfType, basePackage) else CodeBlock.of("null"),
             )
+            addPolledValueResolution(body, asyncRet, returnType, selfType, basePackage, collection)
+            body.endControlFlow()
+            if (asyncRet is XrossType.Result) {
+                body.nextControlFlow("catch (e: %T)", ClassName(runtimePkg, "XrossPanicException"))
+                body.addStatement("throw e")
+                body.nextControlFlow("catch (e: %T)", ClassName(runtimePkg, "XrossException"))
+                body.addStatement("Result.failure(e)")
+                body.endControlFlow()
+            }
+            body.endControlFlow()
+            return body.build()
+        }
+
+        val streamTy = method.ret
+        if (streamTy is XrossType.Stream) {
+            // ストリームはパニック時も XrossStream を書き込み、パニックは poll_next が報告する
+            val itemType = (returnType as ParameterizedTypeName).typeArguments.first()
+            body.beginControlFlow("run")
+            body.addStatement("val stream = %L as %T", call, MEMORY_SEGMENT)
+            val itemTy = streamTy.inner
+            if (itemTy is XrossType.Result) {
+                // Err の要素はフローを終わらせず Result.failure として流す
+                body.beginControlFlow(
+                    "%T.flow(stream, %L, { e -> Result.failure<%T>(e) })",
+                    ClassName(runtimePkg, "XrossStreams"),
+                    errorValueMapper(itemTy.err, selfType, basePackage),
+                    GeneratorUtils.resolveReturnType(itemTy.ok, basePackage),
+                )
+            } else {
+                body.beginControlFlow("%T.flow(stream)", ClassName(runtimePkg, "XrossStreams"))
+            }
+            addPolledValueResolution(body, streamTy.inner, itemType, selfType, basePackage)
             body.endControlFlow()
             body.endControlFlow()
             return body.build()
@@ -186,22 +220,40 @@ object InvocationGenerator {
             body.addStatement("val resRaw = %L", callExpr)
             body.addStatement("val isOk = resRaw.get(%M, 0L) != (0).toByte()", FFMConstants.JAVA_BYTE)
             body.addStatement("val ptr = resRaw.get(%M, 8L)", ADDRESS)
-            body.beginControlFlow("if (!isOk)")
-            body.add("val errVal = ")
-            body.addResultVariantResolution(
-                XrossType.RustString,
-                "ptr",
-                String::class.asTypeName(),
-                selfType,
-                basePackage,
-                "dropHandle",
-            )
-            body.addStatement("throw %T(errVal)", ClassName(runtimePkg, "XrossException"))
-            body.endControlFlow()
+            val retTy = method.ret
+            if (retTy is XrossType.Result) {
+                // Err は XrossError として届き、パニック以外は Result.failure で返す
+                body.beginControlFlow("if (!isOk)")
+                body.add(
+                    "val error = %T.takeError(ptr, %L)\n",
+                    ClassName(runtimePkg, "XrossErrors"),
+                    errorValueMapper(retTy.err, selfType, basePackage),
+                )
+                body.addStatement("if (error is %T) throw error", ClassName(runtimePkg, "XrossPanicException"))
+                body.addStatement("Result.failure(error)")
+                body.nextControlFlow("else")
+                body.add("val okVal = ")
+                body.addResultVariantResolution(
+                    retTy.ok,
+                    "ptr",
+                    GeneratorUtils.resolveReturnType(retTy.ok, basePackage),
+                    selfType,
+                    basePackage,
+                    "dropHandle",
+                    collection,
+                )
+                body.addStatement("Result.success(okVal)")
+                body.endControlFlow()
+                body.endControlFlow()
+                return body.build()
+            }
+            body.addStatement("if (!isOk) throw %T.takeError(ptr)", ClassName(runtimePkg, "XrossErrors"))
 
             // Success case: resolve the actual return type
             if (method.ret is XrossType.Void) {
                 body.addStatement("Unit")
+            } else if (method.ret is XrossType.Iterator) {
+                addIteratorConstruction(body, method, "ptr", returnType, selfType, basePackage)
             } else {
                 body.add("val okVal = ")
                 body.addResultVariantResolution(
@@ -211,6 +263,7 @@ object InvocationGenerator {
                     selfType,
                     basePackage,
                     "dropHandle",
+                    collection,
                 )
                 body.addStatement("okVal")
             }
@@ -221,6 +274,7 @@ object InvocationGenerator {
         when (val retTy = method.ret) {
             is XrossType.Void -> {
                 body.addStatement("%L", call)
+                body.addHandleCheck(basePackage)
             }
 
             is XrossType.RustString -> {
@@ -234,10 +288,11 @@ object InvocationGenerator {
                 body.beginControlFlow("run")
                 val callExpr = if (call.toString() == "outPanic") call else CodeBlock.of("%L as %T", call, MEMORY_SEGMENT)
                 body.addStatement("val resRaw = %L", callExpr)
+                body.addHandleCheck(basePackage)
                 body.beginControlFlow("if (resRaw == %T.NULL)", MEMORY_SEGMENT)
                     .addStatement("throw %T(%S)", NullPointerException::class.asTypeName(), "Unexpected NULL return")
                 body.nextControlFlow("else")
-                val (size, drop, from) = getExprs(returnType)
+                val (size, drop, from) = getExprs(returnType, if (retTy.isShared) "arcDropHandle" else "dropHandle")
                 body.addResourceConstruction(retTy, "resRaw", size, from, drop, flagType)
                 body.endControlFlow().endControlFlow()
             }
@@ -246,7 +301,8 @@ object InvocationGenerator {
                 body.beginControlFlow("run")
                 val callExpr = if (call.toString() == "outPanic") call else CodeBlock.of("%L as %T", call, MEMORY_SEGMENT)
                 body.addStatement("val resRaw = %L", callExpr)
-                body.addOptionalResolution(retTy.inner, "resRaw", selfType, basePackage)
+                body.addHandleCheck(basePackage)
+                body.addOptionalResolution(retTy.inner, "resRaw", selfType, basePackage, collection = collection)
                 body.endControlFlow()
             }
 
@@ -254,14 +310,135 @@ object InvocationGenerator {
                 body.beginControlFlow("run")
                 val callExpr = if (call.toString() == "outPanic") call else CodeBlock.of("%L as %T", call, MEMORY_SEGMENT)
                 body.addStatement("val resRaw = %L", callExpr)
-                body.addResultResolution(retTy, "resRaw", selfType, basePackage)
+                body.addHandleCheck(basePackage)
+                body.addResultResolution(retTy, "resRaw", selfType, basePackage, collection = collection)
+                body.endControlFlow()
+            }
+
+            is XrossType.Tuple -> {
+                body.addTupleResolution(retTy, call.toString(), selfType, basePackage)
+            }
+
+            is XrossType.Vec -> {
+                // XrossArray は out バッファに値渡しで書き込まれる
+                body.beginControlFlow("run")
+                body.addArrayResolution(retTy, call.toString(), boxed = false, returnType, selfType, basePackage)
+                body.add("\n")
+                body.endControlFlow()
+            }
+
+            is XrossType.Iterator -> {
+                body.beginControlFlow("run")
+                body.addStatement("val handle = %L as %T", call, MEMORY_SEGMENT)
+                body.addHandleCheck(basePackage)
+                addIteratorConstruction(body, method, "handle", returnType, selfType, basePackage)
+                body.endControlFlow()
+            }
+
+            is XrossType.VecDeque,
+            is XrossType.LinkedList,
+            is XrossType.HashSet,
+            is XrossType.BTreeSet,
+            is XrossType.BinaryHeap,
+            is XrossType.HashMap,
+            is XrossType.BTreeMap
+            -> {
+                // コレクションは Box のポインタで返り、retAccessors のエクスポート越しに読む
+                body.beginControlFlow("run")
+                body.addStatement("val resRaw = %L as %T", call, MEMORY_SEGMENT)
+                body.addHandleCheck(basePackage)
+                body.addCollectionConstruction(retTy, "resRaw", checkNotNull(collection) { "Collection returned by ${method.name} has no accessor exports" }, selfType, basePackage)
                 body.endControlFlow()
             }
             // 数値型やBooleanなどのプリミティブ型
             else -> {
-                body.addStatement("%L as %T", call, returnType)
+                body.beginControlFlow("run")
+                body.addStatement("val value = %L as %T", call, returnType)
+                body.addHandleCheck(basePackage)
+                body.addStatement("value")
+                body.endControlFlow()
             }
         }
         return body.build()
     }
+
+    /**
+     * Resolves a value handed out by a task or stream poll, available as `it`.
+     */
+    private fun addPolledValueResolution(
+        body: CodeBlock.Builder,
+        type: XrossType,
+        targetType: TypeName,
+        selfType: ClassName,
+        basePackage: String,
+        collection: CollectionHandles? = null,
+    ) {
+        // Result の Err は呼び出し側で扱われ、ここには Ok の値だけが届く
+        val valueType = if (type is XrossType.Result) type.ok else type
+        // None は NULL ではなく箱詰めされたタグ付きの値で届く
+        val valueName = if (valueType is XrossType.Optional && !valueType.isTaggedOption) {
+            body.addStatement("val value = %T.takeOption(it)", ClassName("$basePackage.xross.runtime", "XrossAsync"))
+            "value"
+        } else {
+            "it"
+        }
+        if (type is XrossType.Result) {
+            body.add("val okVal = ")
+            body.addResultVariantResolution(type.ok, valueName, GeneratorUtils.resolveReturnType(type.ok, basePackage), selfType, basePackage, "dropHandle", collection)
+            body.addStatement("Result.success(okVal)")
+        } else {
+            body.addResultVariantResolution(type, valueName, targetType, selfType, basePackage, "dropHandle", collection)
+        }
+    }
+
+    /**
+     * Wraps the iterator handle in [handleName] into a once-only sequence over an `XrossNativeIterator`,
+     * which reads items through the `retIterator` exports.
+     */
+    private fun addIteratorConstruction(
+        body: CodeBlock.Builder,
+        method: XrossMethod,
+        handleName: String,
+        returnType: TypeName,
+        selfType: ClassName,
+        basePackage: String,
+    ) {
+        val item = (method.ret as XrossType.Iterator).inner
+        val itemType = (returnType as ParameterizedTypeName).typeArguments.first()
+        val camel = method.name.toCamelCase()
+        checkNotNull(method.retIterator) { "Iterator returned by ${method.name} has no accessor exports" }
+
+        // 要素は next の out ポインタに値渡しで書き込まれる（文字列は XrossString、その他の型はポインタ）
+        val itemLayout = when {
+            item.isTaggedOption || item is XrossType.RustString -> item.layoutCode
+            item.isPrimitive -> CodeBlock.of("%M", item.layoutMember)
+            else -> CodeBlock.of("%M", ADDRESS)
+        }
+        body.beginControlFlow("if (%L == %T.NULL)", handleName, MEMORY_SEGMENT)
+        body.addStatement("throw %T(%S)", NullPointerException::class.asTypeName(), "Unexpected NULL return")
+        body.endControlFlow()
+        body.beginControlFlow(
+            "%T(%L, ${camel}NextHandle, ${camel}IterDropHandle, %L) { out ->",
+            ClassName("$basePackage.xross.runtime", "XrossNativeIterator"),
+            handleName,
+            itemLayout,
+        )
+        when {
+            item is XrossType.Optional && item.isTaggedOption -> {
+                body.addOptionalResolution(item.inner, "out", selfType, basePackage)
+            }
+            item is XrossType.RustString -> {
+                body.addRustStringResolution("out", basePackage = basePackage)
+                body.addStatement("str")
+            }
+            item is XrossType.Bool -> body.addStatement("out.get(%M, 0L) != (0).toByte()", FFMConstants.JAVA_BYTE)
+            item.isPrimitive -> body.addStatement("out.get(%M, 0L)", item.layoutMember)
+            else -> {
+                body.addStatement("val itemPtr = out.get(%M, 0L)", ADDRESS)
+                body.addResultVariantResolution(item, "itemPtr", itemType, selfType, basePackage, "dropHandle")
+            }
+        }
+        body.unindent()
+        body.add("}.asSequence()\n")
+    }
 }
@@ -3,6 +3,8 @@ package org.xross.generator
 import com.squareup.kotlinpoet.*
 import org.xross.generator.util.FFMConstants
 import org.xross.generator.util.GeneratorUtils
+import org.xross.generator.util.addHandleCheck
+import org.xross.generator.util.addHandleKeyCheck
 import org.xross.helper.StringHelper.escapeKotlinKeyword
 import org.xross.helper.StringHelper.toCamelCase
 import org.xross.structures.*
@@ -79,6 +81,13 @@ object MethodGenerator {
                 body.beginControlFlow("if (currentSegment == %T.NULL || !this.isValid)", MEMORY_SEGMENT)
                 body.addStatement("throw %T(%S)", NullPointerException::class.asTypeName(), "Object dropped or invalid")
                 body.endControlFlow()
+                body.addHandleKeyCheck("this", basePackage)
+            }
+            // `self: Arc<Self>` needs an Arc handle; `&mut self` and `self` must not alias one.
+            if (method.methodType == XrossMethodType.SharedInstance) {
+                body.addStatement("check(this.isSharedHandle) { %S }", "${method.name} needs a shared handle, returned by a method returning Arc<Self>")
+            } else if (meta.isShared && (method.methodType == XrossMethodType.MutInstance || method.methodType == XrossMethodType.OwnedInstance)) {
+                body.addStatement("check(!this.isSharedHandle) { %S }", "${method.name} cannot be called on a shared handle")
             }
 
             if (method.ret !is XrossType.Void) body.add("return ")
@@ -88,20 +97,29 @@ object MethodGenerator {
             if (method.methodType != XrossMethodType.Static) callArgs.add(CodeBlock.of("currentSegment"))
 
             val argPrep = CodeBlock.builder()
-            val needsArena = method.args.any { it.ty is XrossType.RustString || it.ty is XrossType.Optional || it.ty is XrossType.Result }
+            val needsArena = GeneratorUtils.needsArgumentArena(method.args)
             val arenaForArg = GeneratorUtils.prepareArgumentsAndArena(method, argPrep, basePackage, callArgs, checkObjectValidity = true)
 
             val handleName = "${method.name.toCamelCase()}Handle"
             val isPanicable = method.handleMode is HandleMode.Panicable
-            val isComplexRet = method.ret is XrossType.RustString || method.isAsync
+            val isComplexRet = method.ret is XrossType.RustString ||
+                method.ret is XrossType.Vec ||
+                method.ret is XrossType.Tuple ||
+                method.ret is XrossType.Stream ||
+                method.isAsync
 
             val call = if (isComplexRet || isPanicable) {
-                val layout = if (isPanicable) {
-                    FFMConstants.XROSS_RESULT_LAYOUT_CODE
-                } else if (method.isAsync) {
+                // Panicable async methods and streams still write their handle; panics are reported through it.
+                val layout = if (method.isAsync) {
                     FFMConstants.XROSS_TASK_LAYOUT_CODE
+                } else if (method.ret is XrossType.Stream) {
+                    FFMConstants.XROSS_STREAM_LAYOUT_CODE
+                } else if (isPanicable) {
+                    FFMConstants.XROSS_RESULT_LAYOUT_CODE
                 } else if (method.ret is XrossType.RustString) {
                     FFMConstants.XROSS_STRING_LAYOUT_CODE
+                } else if (method.ret is XrossType.Tuple) {
+                    CodeBlock.of("%LL, %LL", method.ret.byteSize(incoming = false), method.ret.byteAlign(incoming = false))
                 } else {
                     method.ret.layoutCode
                 }
@@ -109,11 +127,18 @@ object MethodGenerator {
                 val pArgs = mutableListOf(CodeBlock.of("outBuf"))
                 pArgs.addAll(callArgs)
                 argPrep.addStatement("$handleName.invokeExact(%L)", pArgs.joinToCode(", "))
+                // Only the `XrossResult` return reports handle violations itself
+                if (!isPanicable || method.isAsync || method.ret is XrossType.Stream) argPrep.addHandleCheck(basePackage)
                 CodeBlock.of("outBuf")
             } else {
                 if (method.ret is XrossType.Void) {
                     // Use invoke for Void to avoid Descriptor mismatch in Kotlin
                     CodeBlock.of("$handleName.invoke(%L)", callArgs.joinToCode(", "))
+                } else if (method.ret.isTaggedOption) {
+                    // XrossOption is returned by value, so the downcall takes an allocator first
+                    val pArgs = mutableListOf(CodeBlock.of("$arenaForArg as %T", java.lang.foreign.SegmentAllocator::class.asTypeName()))
+                    pArgs.addAll(callArgs)
+                    CodeBlock.of("$handleName.invokeExact(%L)", pArgs.joinToCode(", "))
                 } else {
                     CodeBlock.of("$handleName.invokeExact(%L)", callArgs.joinToCode(", "))
                 }
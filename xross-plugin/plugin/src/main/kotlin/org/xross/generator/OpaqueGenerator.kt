@@ -23,7 +23,7 @@ object OpaqueGenerator {
             val kType = if (field.ty is XrossType.Object) {
                 GeneratorUtils.getClassName(field.ty.signature, basePackage)
             } else {
-                field.ty.kotlinType
+                GeneratorUtils.resolveReturnType(field.ty, basePackage)
             }
 
             val backingFieldName = GeneratorUtils.addBackingPropertyIfNeeded(classBuilder, field, baseName, kType)
@@ -42,7 +42,7 @@ object OpaqueGenerator {
     private fun buildOpaqueGetter(field: XrossField, kType: TypeName, backingFieldName: String?, basePackage: String): FunSpec {
         val baseName = field.name.toCamelCase()
         val body = CodeBlock.builder()
-        GeneratorUtils.addAliveCheck(body, "Access error")
+        GeneratorUtils.addAliveCheck(body, basePackage, "Access error")
 
         if (backingFieldName != null) {
             body.addStatement("val cached = this.$backingFieldName")
@@ -65,6 +65,7 @@ object OpaqueGenerator {
         when (field.ty) {
             is XrossType.Result -> {
                 body.addStatement("val resRaw = $getHandle.invokeExact(this.segment) as %T", MemorySegment::class)
+                body.addHandleCheck(basePackage)
                 body.add("val res = ")
                 body.addResultResolution(field.ty, "resRaw", ClassName("", "UNUSED"), basePackage)
                 body.add("\n")
@@ -72,11 +73,40 @@ object OpaqueGenerator {
 
             is XrossType.RustString -> {
                 body.addRustStringResolution("$getHandle.invokeExact(java.lang.foreign.Arena.ofAuto() as java.lang.foreign.SegmentAllocator, this.segment)", "s", basePackage = basePackage)
+                body.addHandleCheck(basePackage)
                 body.addStatement("val res = s")
             }
 
+            is XrossType.Optional -> {
+                if (field.ty.isTaggedOption) {
+                    body.addStatement("val resRaw = $getHandle.invokeExact(java.lang.foreign.Arena.ofAuto() as java.lang.foreign.SegmentAllocator, this.segment) as %T", MemorySegment::class)
+                } else {
+                    body.addStatement("val resRaw = $getHandle.invokeExact(this.segment) as %T", MemorySegment::class)
+                }
+                body.addHandleCheck(basePackage)
+                body.add("val res = ")
+                body.addOptionalResolution(field.ty.inner, "resRaw", ClassName("", "UNUSED"), basePackage)
+            }
+
+            is XrossType.Object -> {
+                // getter は複製 (Arc<T> なら強参照) を所有権付きで返す
+                body.addStatement("val resRaw = $getHandle.invokeExact(this.segment) as %T", MemorySegment::class)
+                body.addHandleCheck(basePackage)
+                body.add("val res = ")
+                body.addResultVariantResolution(field.ty, "resRaw", kType, ClassName("", "UNUSED"), basePackage)
+            }
+
+            is XrossType.Tuple -> {
+                body.addStatement("val outRaw = java.lang.foreign.Arena.ofAuto().allocate(%LL, %LL)", field.ty.byteSize(incoming = false), field.ty.byteAlign(incoming = false))
+                body.addStatement("$getHandle.invoke(this.segment, outRaw)")
+                body.addHandleCheck(basePackage)
+                body.add("val res = ")
+                body.addTupleResolution(field.ty, "outRaw", ClassName("", "UNUSED"), basePackage)
+            }
+
             else -> {
                 body.addStatement("val res = $getHandle.invokeExact(this.segment) as %T", kType)
+                body.addHandleCheck(basePackage)
             }
         }
 
@@ -93,7 +123,7 @@ object OpaqueGenerator {
 
     private fun buildOpaqueSetterBody(field: XrossField, backingFieldName: String?, basePackage: String): CodeBlock {
         val body = CodeBlock.builder()
-        GeneratorUtils.addAliveCheck(body, "Object invalid")
+        GeneratorUtils.addAliveCheck(body, basePackage, "Object invalid")
 
         val setHandle = when (field.ty) {
             is XrossType.RustString -> "${field.name.toCamelCase()}StrSetHandle"
@@ -105,6 +135,7 @@ object OpaqueGenerator {
         val callArgs = mutableListOf<CodeBlock>()
         body.addArgumentPreparation(field.ty, "v", callArgs, basePackage = basePackage)
         body.addStatement("$setHandle.invoke(this.segment, ${callArgs.joinToString(", ")})")
+        body.addHandleCheck(basePackage)
         body.endControlFlow()
 
         if (backingFieldName != null) {
@@ -17,7 +17,7 @@ object PropertyGenerator {
         meta.fields.forEach { field ->
             val baseName = field.name.toCamelCase()
             val escapedName = baseName.escapeKotlinKeyword()
-            val isPrimitive = field.ty !is XrossType.Object && field.ty !is XrossType.Optional && field.ty !is XrossType.Result
+            val isPrimitive = field.ty !is XrossType.Object && field.ty !is XrossType.Optional && field.ty !is XrossType.Result && field.ty !is XrossType.Tuple
             val vhName: String = when {
                 isPrimitive -> "VH_$baseName"
                 else -> "null"
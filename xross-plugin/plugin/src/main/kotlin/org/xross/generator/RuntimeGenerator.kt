@@ -1,6 +1,7 @@
 package org.xross.generator
 
 import com.squareup.kotlinpoet.*
+import com.squareup.kotlinpoet.ParameterizedTypeName.Companion.parameterizedBy
 import org.xross.generator.util.*
 import java.io.File
 import java.lang.foreign.Arena
@@ -35,10 +36,41 @@ object RuntimeGenerator {
         val pkg = if (basePackage.isEmpty()) "xross.runtime" else "$basePackage.xross.runtime"
 
         // --- XrossException ---
+        val stringList = List::class.asClassName().parameterizedBy(String::class.asTypeName())
+        fun exceptionConstructor() = FunSpec.constructorBuilder()
+            .addParameter("error", Any::class)
+            .addParameter(ParameterSpec.builder("sources", stringList).defaultValue("emptyList()").build())
+            .addParameter(ParameterSpec.builder("nativeBacktrace", String::class.asTypeName().copy(nullable = true)).defaultValue("null").build())
+            .build()
         val xrossException = TypeSpec.classBuilder("XrossException")
+            .addKdoc("Thrown for a failed Rust call; [error] is the `Err` value, or the error message when it has no Kotlin form.\n")
+            .addModifiers(KModifier.OPEN)
             .superclass(Throwable::class)
-            .primaryConstructor(FunSpec.constructorBuilder().addParameter("error", Any::class).build())
+            .addSuperclassConstructorParameter("error.toString()")
+            .primaryConstructor(exceptionConstructor())
             .addProperty(PropertySpec.builder("error", Any::class).initializer("error").build())
+            .addProperty(
+                PropertySpec.builder("sources", stringList)
+                    .addKdoc("The `Display` output of each `source()` in the chain, nearest first.\n")
+                    .initializer("sources").build(),
+            )
+            .addProperty(
+                PropertySpec.builder("nativeBacktrace", String::class.asTypeName().copy(nullable = true))
+                    .addKdoc("The Rust backtrace of the failure, when `RUST_BACKTRACE` enabled capturing it.\n")
+                    .mutable()
+                    .setter(FunSpec.setterBuilder().addModifiers(KModifier.INTERNAL).build())
+                    .initializer("nativeBacktrace").build(),
+            )
+            .build()
+
+        // --- XrossPanicException ---
+        val xrossPanicException = TypeSpec.classBuilder("XrossPanicException")
+            .addKdoc("Thrown when the Rust side of a call panicked.\n")
+            .superclass(ClassName(pkg, "XrossException"))
+            .primaryConstructor(exceptionConstructor())
+            .addSuperclassConstructorParameter("error")
+            .addSuperclassConstructorParameter("sources")
+            .addSuperclassConstructorParameter("nativeBacktrace")
             .build()
 
         // --- XrossObject Interface ---
@@ -72,6 +104,12 @@ object RuntimeGenerator {
                 PropertySpec.builder("isPersistent", Boolean::class, KModifier.PRIVATE)
                     .initializer("isPersistent").build(),
             )
+            .addProperty(
+                PropertySpec.builder("handleKey", Long::class, KModifier.INTERNAL)
+                    .addKdoc("Key of the `checked-handles` slot of an owned or shared [segment], passed to Rust before each use; 0 if untracked.\n")
+                    .initializer("if (isPersistent || parent != null) 0L else %T.handleKey(segment)", ClassName(pkg, "XrossRuntime"))
+                    .build(),
+            )
             .addProperty(
                 PropertySpec.builder("_isValid", ClassName("java.util.concurrent.atomic", "AtomicBoolean"), KModifier.PRIVATE)
                     .initializer("java.util.concurrent.atomic.AtomicBoolean(true)").build(),
@@ -90,6 +128,14 @@ object RuntimeGenerator {
                     .delegate("lazy(LazyThreadSafetyMode.PUBLICATION) { XrossLockState() }")
                     .build(),
             )
+            .addProperty(
+                PropertySpec.builder("isSharedHandle", Boolean::class)
+                    .addKdoc("Whether [segment] holds a strong reference of an `Arc<T>` rather than owning or borrowing the value.\n")
+                    .mutable(true)
+                    .initializer("false")
+                    .setter(FunSpec.setterBuilder().addModifiers(KModifier.INTERNAL).build())
+                    .build(),
+            )
             .addProperty(
                 PropertySpec.builder("cleanable", CLEANABLE.copy(nullable = true), KModifier.PRIVATE)
                     .initializer("null").mutable(true).build(),
@@ -108,9 +154,10 @@ object RuntimeGenerator {
                         "if (isPersistent || parent != null) return\n" +
                             "val s = segment\n" +
                             "val v = _isValid\n" +
+                            "val k = handleKey\n" +
                             "this.cleanable = %T.registerCleaner(this) {\n" +
                             "    if (v.compareAndSet(true, false)) {\n" +
-                            "        %T.invokeDrop(dropHandle, s)\n" +
+                            "        %T.invokeDrop(dropHandle, s, k)\n" +
                             "    }\n" +
                             "}",
                         ClassName(pkg, "XrossRuntime"),
@@ -244,12 +291,107 @@ object RuntimeGenerator {
                     .addCode("return try { (STRING_CODER_VH?.get(s) as? Byte) ?: 0.toByte() } catch (_: Throwable) { 0.toByte() }")
                     .build(),
             )
+            .addFunction(
+                FunSpec.builder("copyStringBytes")
+                    .addKdoc(
+                        "Copies [s] into [allocator] as the bytes of an `XrossStringView` and returns them with their encoding:\n" +
+                            "the compact Latin1 or UTF-16 bytes of the string when readable, UTF-16LE otherwise.\n",
+                    )
+                    .addParameter("s", String::class)
+                    .addParameter("allocator", ClassName("java.lang.foreign", "SegmentAllocator"))
+                    .returns(Pair::class.asClassName().parameterizedBy(MEMORY_SEGMENT, BYTE))
+                    .addCode(
+                        "val value = getStringValue(s)\n" +
+                            "if (value != null) return Pair(allocator.allocateFrom(ValueLayout.JAVA_BYTE, *value), getStringCoder(s))\n" +
+                            "return Pair(allocator.allocateFrom(ValueLayout.JAVA_BYTE, *s.toByteArray(java.nio.charset.StandardCharsets.UTF_16LE)), 1.toByte())\n",
+                    )
+                    .build(),
+            )
+            .addProperty(
+                PropertySpec.builder("takeHandleErrorHandle", MethodHandle::class.asClassName().copy(nullable = true), KModifier.PRIVATE)
+                    .addKdoc("`xross_take_handle_error`, exported only by libraries built with `checked-handles`.\n")
+                    .delegate(
+                        "lazy(LazyThreadSafetyMode.SYNCHRONIZED) { " +
+                            "SymbolLookup.loaderLookup().find(\"xross_take_handle_error\").map { " +
+                            "Linker.nativeLinker().downcallHandle(it, FunctionDescriptor.of(ValueLayout.ADDRESS)) }.orElse(null) }",
+                    ).build(),
+            )
+            .addFunction(
+                FunSpec.builder("checkHandles")
+                    .addKdoc(
+                        "Throws the handle violation recorded by the last export called on this thread, if any.\n" +
+                            "Exports that cannot return an error skip the call and return zero instead.\n",
+                    )
+                    .addCode(
+                        "val handle = takeHandleErrorHandle ?: return\n" +
+                            "val err = handle.invokeExact() as MemorySegment\n" +
+                            "if (err != MemorySegment.NULL) throw XrossErrors.takeError(err)\n",
+                    )
+                    .build(),
+            )
+            .addProperty(
+                PropertySpec.builder("handleKeyHandle", MethodHandle::class.asClassName().copy(nullable = true), KModifier.PRIVATE)
+                    .addKdoc("`xross_handle_key`, exported only by libraries built with `checked-handles`.\n")
+                    .delegate(
+                        "lazy(LazyThreadSafetyMode.SYNCHRONIZED) { " +
+                            "SymbolLookup.loaderLookup().find(\"xross_handle_key\").map { " +
+                            "Linker.nativeLinker().downcallHandle(it, FunctionDescriptor.of(ValueLayout.JAVA_LONG, ValueLayout.ADDRESS)) }.orElse(null) }",
+                    ).build(),
+            )
+            .addProperty(
+                PropertySpec.builder("checkHandleKeyHandle", MethodHandle::class.asClassName().copy(nullable = true), KModifier.PRIVATE)
+                    .addKdoc("`xross_check_handle_key`, exported only by libraries built with `checked-handles`.\n")
+                    .delegate(
+                        "lazy(LazyThreadSafetyMode.SYNCHRONIZED) { " +
+                            "SymbolLookup.loaderLookup().find(\"xross_check_handle_key\").map { " +
+                            "Linker.nativeLinker().downcallHandle(it, FunctionDescriptor.of(ValueLayout.ADDRESS, ValueLayout.ADDRESS, ValueLayout.JAVA_LONG)) }.orElse(null) }",
+                    ).build(),
+            )
+            .addFunction(
+                FunSpec.builder("handleKey")
+                    .addKdoc("Returns the slot key of the handle [segment] just returned by an export, or 0 if it is not tracked.\n")
+                    .addParameter("segment", MEMORY_SEGMENT)
+                    .returns(Long::class)
+                    .addCode(
+                        "val handle = handleKeyHandle ?: return 0L\n" +
+                            "return handle.invokeExact(segment) as Long\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("checkHandle")
+                    .addKdoc(
+                        "Throws if the handle of [obj] is stale: its object was dropped, even if another one\n" +
+                            "now lives at the same address. Only checked with `checked-handles`.\n",
+                    )
+                    .addParameter("obj", ClassName(pkg, "XrossObject"))
+                    .addCode(
+                        "val key = (obj as? XrossNativeObject)?.handleKey ?: return\n" +
+                            "checkHandleKey(obj.segment, key)\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("checkHandleKey")
+                    .addModifiers(KModifier.PRIVATE)
+                    .addParameter("segment", MEMORY_SEGMENT)
+                    .addParameter("key", Long::class)
+                    .addCode(
+                        "if (key == 0L) return\n" +
+                            "val handle = checkHandleKeyHandle ?: return\n" +
+                            "val err = handle.invokeExact(segment, key) as MemorySegment\n" +
+                            "if (err != MemorySegment.NULL) throw XrossErrors.takeError(err)\n",
+                    )
+                    .build(),
+            )
             .addFunction(
                 FunSpec.builder("invokeDrop")
                     .addParameter("handle", MethodHandle::class)
                     .addParameter("segment", MEMORY_SEGMENT)
+                    .addParameter(ParameterSpec.builder("key", Long::class).defaultValue("0L").build())
                     .addCode(
                         "try {\n" +
+                            "    checkHandleKey(segment, key)\n" +
                             "    if (handle.type().returnType() == java.lang.Void.TYPE && handle.type().parameterCount() == 2) {\n" +
                             "        java.lang.foreign.Arena.ofConfined().use { arena ->\n" +
                             "            val outPanic = arena.allocate(16)\n" +
@@ -257,6 +399,7 @@ object RuntimeGenerator {
                             "        }\n" +
                             "    } else {\n" +
                             "        handle.invoke(segment)\n" +
+                            "        checkHandles()\n" +
                             "    }\n" +
                             "} catch (e: Throwable) { e.printStackTrace() }\n",
                     )
@@ -291,14 +434,54 @@ object RuntimeGenerator {
             .build()
 
         // --- XrossAsync ---
+        // xross-core の XrossTaskStatus の判別値と一致させる
+        val taskStatuses = listOf("PENDING" to 0, "COMPLETED" to 1, "PANICKED" to 2, "CANCELLED" to 3)
         val xrossAsync = TypeSpec.objectBuilder("XrossAsync")
+            .addProperties(
+                taskStatuses.map { (name, value) ->
+                    PropertySpec.builder(name, Byte::class, KModifier.CONST)
+                        .addKdoc("`XrossTaskStatus::%L`, as returned by a task's `status_fn`.\n", name.lowercase().replaceFirstChar { it.uppercase() })
+                        .initializer("%L", value)
+                        .build()
+                },
+            )
+            .addProperty(
+                PropertySpec.builder("freeOptionHandle", MethodHandle::class, KModifier.PRIVATE)
+                    .delegate(
+                        "lazy(LazyThreadSafetyMode.SYNCHRONIZED) { " +
+                            "Linker.nativeLinker().downcallHandle(java.lang.foreign.SymbolLookup.loaderLookup().find(\"xross_free_option\").get(), " +
+                            "FunctionDescriptor.ofVoid(ValueLayout.ADDRESS)) }",
+                    ).build(),
+            )
+            .addFunction(
+                FunSpec.builder("takeOption")
+                    .addKdoc(
+                        "Frees the tagged option an optional task result is boxed in and returns its value,\n" +
+                            "or [MemorySegment.NULL] for `None`.\n",
+                    )
+                    .addParameter("ptr", MEMORY_SEGMENT)
+                    .returns(MEMORY_SEGMENT)
+                    .addCode(
+                        "val opt = ptr.reinterpret(16L)\n" +
+                            "val value = if (opt.get(ValueLayout.JAVA_BYTE, 0L) != (0).toByte()) opt.get(ValueLayout.ADDRESS, 8L) else MemorySegment.NULL\n" +
+                            "freeOptionHandle.invoke(ptr)\n" +
+                            "return value\n",
+                    )
+                    .build(),
+            )
             .addFunction(
                 FunSpec.builder("awaitFuture")
                     .addModifiers(KModifier.SUSPEND)
                     .addTypeVariable(TypeVariableName("T"))
                     .addParameter("taskPtr", MEMORY_SEGMENT)
                     .addParameter("pollFn", MethodHandle::class)
+                    .addParameter("statusFn", MethodHandle::class)
                     .addParameter("dropFn", MethodHandle::class)
+                    .addParameter(
+                        ParameterSpec.builder("errorValue", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = ANY).copy(nullable = true))
+                            .defaultValue("null")
+                            .build(),
+                    )
                     .addParameter(
                         "mapper",
                         LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = TypeVariableName("T")),
@@ -306,27 +489,766 @@ object RuntimeGenerator {
                     .returns(TypeVariableName("T"))
                     .addCode(
                         "try {\n" +
-                            "    java.lang.foreign.Arena.ofConfined().use { arena ->\n" +
+                            "    var status = statusFn.invokeExact(taskPtr) as Byte\n" +
+                            "    while (status == PENDING) {\n" +
+                            "        kotlinx.coroutines.delay(1)\n" +
+                            "        status = statusFn.invokeExact(taskPtr) as Byte\n" +
+                            "    }\n" +
+                            "    // A confined arena must not live across a suspension point, which may resume on another thread\n" +
+                            "    val (isOk, ptr) = java.lang.foreign.Arena.ofConfined().use { arena ->\n" +
+                            "        val resultRaw = pollFn.invokeExact(arena as java.lang.foreign.SegmentAllocator, taskPtr) as MemorySegment\n" +
+                            "        Pair(resultRaw.get(java.lang.foreign.ValueLayout.JAVA_BYTE, 0L) != (0).toByte(), resultRaw.get(java.lang.foreign.ValueLayout.ADDRESS, 8L))\n" +
+                            "    }\n" +
+                            "    when (status) {\n" +
+                            "        COMPLETED -> {\n" +
+                            "            if (!isOk) throw XrossErrors.takeError(ptr, errorValue)\n" +
+                            "            return mapper(ptr)\n" +
+                            "        }\n" +
+                            "        PANICKED -> throw XrossErrors.takeError(ptr)\n" +
+                            "        else -> throw kotlinx.coroutines.CancellationException(\"The native task was cancelled\")\n" +
+                            "    }\n" +
+                            "} finally {\n" +
+                            "    dropFn.invoke(taskPtr)\n" +
+                            "}\n",
+                    )
+                    .build(),
+            )
+            .build()
+
+        // --- XrossErrors ---
+        val xrossErrors = TypeSpec.objectBuilder("XrossErrors")
+            .addKdoc("Decodes errors, and the strings and string arrays handed out with them, releasing them on the Rust side.\n")
+            .addProperty(
+                PropertySpec.builder("STRING_LAYOUT", ClassName("java.lang.foreign", "StructLayout"), KModifier.PRIVATE)
+                    .initializer(
+                        "java.lang.foreign.MemoryLayout.structLayout(" +
+                            "ValueLayout.ADDRESS.withName(\"ptr\"), ValueLayout.JAVA_LONG.withName(\"len\"), ValueLayout.JAVA_LONG.withName(\"cap\"))",
+                    ).build(),
+            )
+            .addProperty(
+                PropertySpec.builder("ARRAY_LAYOUT", ClassName("java.lang.foreign", "StructLayout"), KModifier.PRIVATE)
+                    .initializer(
+                        "java.lang.foreign.MemoryLayout.structLayout(" +
+                            "ValueLayout.ADDRESS.withName(\"ptr\"), ValueLayout.JAVA_LONG.withName(\"len\"), ValueLayout.JAVA_LONG.withName(\"cap\"), " +
+                            "ValueLayout.JAVA_LONG.withName(\"elemSize\"), ValueLayout.JAVA_LONG.withName(\"elemAlign\"))",
+                    ).build(),
+            )
+            .addProperty(
+                PropertySpec.builder("freeStringHandle", MethodHandle::class, KModifier.PRIVATE)
+                    .delegate(
+                        "lazy(LazyThreadSafetyMode.SYNCHRONIZED) { " +
+                            "Linker.nativeLinker().downcallHandle(SymbolLookup.loaderLookup().find(\"xross_free_string\").get(), " +
+                            "FunctionDescriptor.ofVoid(STRING_LAYOUT)) }",
+                    ).build(),
+            )
+            .addProperty(
+                PropertySpec.builder("ERROR_SIZE", Long::class, KModifier.PRIVATE, KModifier.CONST)
+                    .addKdoc("Size of `XrossError`.\n")
+                    .initializer("104L").build(),
+            )
+            .addProperty(
+                PropertySpec.builder("freeErrorHandle", MethodHandle::class, KModifier.PRIVATE)
+                    .delegate(
+                        "lazy(LazyThreadSafetyMode.SYNCHRONIZED) { " +
+                            "Linker.nativeLinker().downcallHandle(SymbolLookup.loaderLookup().find(\"xross_free_error\").get(), " +
+                            "FunctionDescriptor.ofVoid(ValueLayout.ADDRESS)) }",
+                    ).build(),
+            )
+            .addProperty(
+                PropertySpec.builder("freeArrayHandle", MethodHandle::class, KModifier.PRIVATE)
+                    .delegate(
+                        "lazy(LazyThreadSafetyMode.SYNCHRONIZED) { " +
+                            "Linker.nativeLinker().downcallHandle(SymbolLookup.loaderLookup().find(\"xross_free_array\").get(), " +
+                            "FunctionDescriptor.ofVoid(ARRAY_LAYOUT)) }",
+                    ).build(),
+            )
+            .addFunction(
+                FunSpec.builder("takeString")
+                    .addKdoc("Decodes the `XrossString` stored in [string] and frees it.\n")
+                    .addParameter("string", MEMORY_SEGMENT)
+                    .returns(String::class)
+                    .addCode(
+                        "val value = readString(string)\n" +
+                            "freeStringHandle.invoke(string)\n" +
+                            "return value\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("readString")
+                    .addModifiers(KModifier.PRIVATE)
+                    .addParameter("string", MEMORY_SEGMENT)
+                    .returns(String::class)
+                    .addCode(
+                        "val ptr = string.get(ValueLayout.ADDRESS, 0L)\n" +
+                            "val len = string.get(ValueLayout.JAVA_LONG, 8L)\n" +
+                            "return if (ptr == MemorySegment.NULL || len == 0L) \"\" else java.nio.charset.StandardCharsets.UTF_8.decode(ptr.reinterpret(len).asByteBuffer()).toString()\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("takeError")
+                    .addKdoc(
+                        "Builds the exception for the `XrossError` at [ptr] and frees it with `xross_free_error`.\n" +
+                            "The `value` handle of an `Err` object is passed to [value], whose result becomes the error;\n" +
+                            "a returned [XrossException] is used as the exception itself.\n",
+                    )
+                    .addParameter("ptr", MEMORY_SEGMENT)
+                    .addParameter(
+                        ParameterSpec.builder("value", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = ANY).copy(nullable = true))
+                            .defaultValue("null")
+                            .build(),
+                    )
+                    .returns(ClassName(pkg, "XrossException"))
+                    .addCode(
+                        "check(ptr != MemorySegment.NULL) { \"Null XrossError\" }\n" +
+                            "// XrossError { kind, message, backtrace, causes, value }\n" +
+                            "val err = ptr.reinterpret(ERROR_SIZE)\n" +
+                            "val kind = err.get(ValueLayout.JAVA_BYTE, 0L).toInt()\n" +
+                            "val message = readString(err.asSlice(8L, 24L))\n" +
+                            "val backtrace = if (err.get(ValueLayout.ADDRESS, 32L) == MemorySegment.NULL) null else readString(err.asSlice(32L, 24L))\n" +
+                            "val causesPtr = err.get(ValueLayout.ADDRESS, 56L)\n" +
+                            "val causesLen = err.get(ValueLayout.JAVA_LONG, 64L)\n" +
+                            "val sources = if (causesPtr == MemorySegment.NULL) emptyList() else {\n" +
+                            "    val elems = causesPtr.reinterpret(causesLen * STRING_LAYOUT.byteSize())\n" +
+                            "    (0L until causesLen).map { readString(elems.asSlice(it * STRING_LAYOUT.byteSize(), STRING_LAYOUT.byteSize())) }\n" +
+                            "}\n" +
+                            "val valuePtr = err.get(ValueLayout.ADDRESS, 96L)\n" +
+                            "freeErrorHandle.invoke(ptr)\n" +
+                            "// XrossErrorKind: 0 Panic, 1 Err, 2 InvalidArgument, 3 NullHandle, 4 InvalidHandle\n" +
+                            "if (kind == 0) return XrossPanicException(message, sources, backtrace)\n" +
+                            "val error = if (valuePtr != MemorySegment.NULL && value != null) value(valuePtr) else message\n" +
+                            "if (error is XrossException) return error.also { it.nativeBacktrace = backtrace }\n" +
+                            "return XrossException(error, sources, backtrace)\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("takeStrings")
+                    .addKdoc("Decodes the `XrossArray` of `XrossString`s stored in [array], freeing the strings and the array.\n")
+                    .addParameter("array", MEMORY_SEGMENT)
+                    .returns(List::class.asClassName().parameterizedBy(String::class.asTypeName()))
+                    .addCode(
+                        "val ptr = array.get(ValueLayout.ADDRESS, 0L)\n" +
+                            "val len = array.get(ValueLayout.JAVA_LONG, 8L)\n" +
+                            "if (ptr == MemorySegment.NULL) return emptyList()\n" +
+                            "val elems = ptr.reinterpret(len * STRING_LAYOUT.byteSize())\n" +
+                            "val values = (0L until len).map { takeString(elems.asSlice(it * STRING_LAYOUT.byteSize(), STRING_LAYOUT.byteSize())) }\n" +
+                            "freeArrayHandle.invoke(array)\n" +
+                            "return values\n",
+                    )
+                    .build(),
+            )
+            .build()
+
+        // --- XrossArrays ---
+        val elementType = TypeVariableName("T")
+        val xrossArrays = TypeSpec.objectBuilder("XrossArrays")
+            .addKdoc("Decodes the `XrossArray`s returned for `Vec<T>`, releasing their buffers on the Rust side.\n")
+            .addProperty(
+                PropertySpec.builder("ARRAY_LAYOUT", ClassName("java.lang.foreign", "StructLayout"), KModifier.PRIVATE)
+                    .addKdoc("`XrossArray { ptr, len, cap, elemSize, elemAlign }`.\n")
+                    .initializer(
+                        "java.lang.foreign.MemoryLayout.structLayout(" +
+                            "ValueLayout.ADDRESS.withName(\"ptr\"), ValueLayout.JAVA_LONG.withName(\"len\"), ValueLayout.JAVA_LONG.withName(\"cap\"), " +
+                            "ValueLayout.JAVA_LONG.withName(\"elemSize\"), ValueLayout.JAVA_LONG.withName(\"elemAlign\"))",
+                    ).build(),
+            )
+            .addProperty(
+                PropertySpec.builder("freeArrayHandle", MethodHandle::class, KModifier.PRIVATE)
+                    .delegate(
+                        "lazy(LazyThreadSafetyMode.SYNCHRONIZED) { " +
+                            "Linker.nativeLinker().downcallHandle(SymbolLookup.loaderLookup().find(\"xross_free_array\").get(), " +
+                            "FunctionDescriptor.ofVoid(ARRAY_LAYOUT)) }",
+                    ).build(),
+            )
+            .addProperty(
+                PropertySpec.builder("freeBoxedArrayHandle", MethodHandle::class, KModifier.PRIVATE)
+                    .delegate(
+                        "lazy(LazyThreadSafetyMode.SYNCHRONIZED) { " +
+                            "Linker.nativeLinker().downcallHandle(SymbolLookup.loaderLookup().find(\"xross_free_boxed_array\").get(), " +
+                            "FunctionDescriptor.ofVoid(ValueLayout.ADDRESS)) }",
+                    ).build(),
+            )
+            .addFunction(
+                FunSpec.builder("take")
+                    .addKdoc(
+                        "Converts each element of the `XrossArray` stored in [array] with [read] and frees the buffer.\n" +
+                            "[read] gets the element representation and takes ownership of the value it holds.\n",
+                    )
+                    .addTypeVariable(elementType)
+                    .addParameter("array", MEMORY_SEGMENT)
+                    .addParameter("read", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = elementType))
+                    .returns(List::class.asClassName().parameterizedBy(elementType))
+                    .addCode(
+                        "try {\n" +
+                            "    return readElements(array, read)\n" +
+                            "} finally {\n" +
+                            "    freeArrayHandle.invoke(array)\n" +
+                            "}\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("takeBoxed")
+                    .addKdoc("Like [take] for an array boxed behind [ptr], as returned through a result, an option or a task. The box is freed too.\n")
+                    .addTypeVariable(elementType)
+                    .addParameter("ptr", MEMORY_SEGMENT)
+                    .addParameter("read", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = elementType))
+                    .returns(List::class.asClassName().parameterizedBy(elementType))
+                    .addCode(
+                        "check(ptr != MemorySegment.NULL) { \"Null XrossArray\" }\n" +
+                            "try {\n" +
+                            "    return readElements(ptr.reinterpret(ARRAY_LAYOUT.byteSize()), read)\n" +
+                            "} finally {\n" +
+                            "    freeBoxedArrayHandle.invoke(ptr)\n" +
+                            "}\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("readElements")
+                    .addModifiers(KModifier.PRIVATE)
+                    .addTypeVariable(elementType)
+                    .addParameter("array", MEMORY_SEGMENT)
+                    .addParameter("read", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = elementType))
+                    .returns(List::class.asClassName().parameterizedBy(elementType))
+                    .addCode(
+                        "val ptr = array.get(ValueLayout.ADDRESS, 0L)\n" +
+                            "val len = array.get(ValueLayout.JAVA_LONG, 8L)\n" +
+                            "val elemSize = array.get(ValueLayout.JAVA_LONG, 24L)\n" +
+                            "if (ptr == MemorySegment.NULL || len == 0L) return emptyList()\n" +
+                            "val elems = ptr.reinterpret(len * elemSize)\n" +
+                            "return (0L until len).map { read(elems.asSlice(it * elemSize, elemSize)) }\n",
+                    )
+                    .build(),
+            )
+            .build()
+
+        // --- XrossStreams ---
+        val xrossStreams = TypeSpec.objectBuilder("XrossStreams")
+            .addProperty(
+                PropertySpec.builder("POLL_LAYOUT", ClassName("java.lang.foreign", "StructLayout"), KModifier.PRIVATE)
+                    .addKdoc("`XrossStreamPoll { status, value: XrossResult }`.\n")
+                    .initializer(
+                        "java.lang.foreign.MemoryLayout.structLayout(" +
+                            "ValueLayout.JAVA_BYTE.withName(\"status\"), java.lang.foreign.MemoryLayout.paddingLayout(7), " +
+                            "ValueLayout.JAVA_BYTE.withName(\"isOk\"), java.lang.foreign.MemoryLayout.paddingLayout(7), " +
+                            "ValueLayout.ADDRESS.withName(\"ptr\"))",
+                    ).build(),
+            )
+            .addFunction(
+                FunSpec.builder("flow")
+                    .addKdoc(
+                        "Wraps the `XrossStream` written to [stream] in a cold [kotlinx.coroutines.flow.Flow] that can be collected once.\n" +
+                            "An `Err` item is emitted as converted by [errorItem], or ends the flow when there is none.\n" +
+                            "The native stream is released when collection ends, or by the cleaner if it is never collected.\n",
+                    )
+                    .addTypeVariable(TypeVariableName("T"))
+                    .addParameter("stream", MEMORY_SEGMENT)
+                    .addParameter(
+                        ParameterSpec.builder("errorValue", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = ANY).copy(nullable = true))
+                            .defaultValue("null")
+                            .build(),
+                    )
+                    .addParameter(
+                        ParameterSpec.builder(
+                            "errorItem",
+                            LambdaTypeName.get(null, ClassName(pkg, "XrossException"), returnType = TypeVariableName("T")).copy(nullable = true),
+                        )
+                            .defaultValue("null")
+                            .build(),
+                    )
+                    .addParameter(
+                        "mapper",
+                        LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = TypeVariableName("T")),
+                    )
+                    .returns(ClassName("kotlinx.coroutines.flow", "Flow").parameterizedBy(TypeVariableName("T")))
+                    .addCode(
+                        "val linker = Linker.nativeLinker()\n" +
+                            "val streamPtr = stream.get(ValueLayout.ADDRESS, 0L)\n" +
+                            "val pollNextFn = linker.downcallHandle(stream.get(ValueLayout.ADDRESS, 8L), FunctionDescriptor.of(POLL_LAYOUT, ValueLayout.ADDRESS))\n" +
+                            "val dropFn = linker.downcallHandle(stream.get(ValueLayout.ADDRESS, 24L), FunctionDescriptor.ofVoid(ValueLayout.ADDRESS))\n" +
+                            "val released = AtomicBoolean(false)\n" +
+                            "val release = Runnable { if (released.compareAndSet(false, true)) dropFn.invoke(streamPtr) }\n" +
+                            "val collected = AtomicBoolean(false)\n" +
+                            "val result = kotlinx.coroutines.flow.flow {\n" +
+                            "    check(collected.compareAndSet(false, true)) { \"A native stream can only be collected once\" }\n" +
+                            "    try {\n" +
                             "        while (true) {\n" +
-                            "            val resultRaw = pollFn.invokeExact(arena as java.lang.foreign.SegmentAllocator, taskPtr) as MemorySegment\n" +
-                            "            val isOk = resultRaw.get(java.lang.foreign.ValueLayout.JAVA_BYTE, 0L) != (0).toByte()\n" +
-                            "            val ptr = resultRaw.get(java.lang.foreign.ValueLayout.ADDRESS, 8L)\n" +
-                            "            if (ptr != MemorySegment.NULL) {\n" +
-                            "                if (!isOk) {\n" +
-                            "                    val errXs = XrossString(ptr.reinterpret(24))\n" +
-                            "                    throw XrossException(errXs.toString())\n" +
+                            "            // XrossStreamStatus: 0 Pending, 1 Item, 2 Done, 3 Panicked, 4 Cancelled\n" +
+                            "            val (status, isOk, ptr) = Arena.ofConfined().use { arena ->\n" +
+                            "                val poll = pollNextFn.invokeExact(arena as SegmentAllocator, streamPtr) as MemorySegment\n" +
+                            "                Triple(poll.get(ValueLayout.JAVA_BYTE, 0L).toInt(), poll.get(ValueLayout.JAVA_BYTE, 8L) != (0).toByte(), poll.get(ValueLayout.ADDRESS, 16L))\n" +
+                            "            }\n" +
+                            "            when (status) {\n" +
+                            "                0 -> kotlinx.coroutines.delay(1)\n" +
+                            "                1 -> {\n" +
+                            "                    if (isOk) {\n" +
+                            "                        emit(mapper(ptr))\n" +
+                            "                    } else {\n" +
+                            "                        val error = XrossErrors.takeError(ptr, errorValue)\n" +
+                            "                        if (errorItem == null) throw error\n" +
+                            "                        emit(errorItem(error))\n" +
+                            "                    }\n" +
                             "                }\n" +
-                            "                return mapper(ptr)\n" +
+                            "                2 -> return@flow\n" +
+                            "                3 -> throw XrossErrors.takeError(ptr)\n" +
+                            "                else -> throw kotlinx.coroutines.CancellationException(\"The native stream was cancelled\")\n" +
                             "            }\n" +
-                            "            kotlinx.coroutines.delay(1)\n" +
                             "        }\n" +
+                            "    } finally {\n" +
+                            "        release.run()\n" +
                             "    }\n" +
-                            "} finally {\n" +
-                            "    dropFn.invoke(taskPtr)\n" +
+                            "}\n" +
+                            "%T.registerCleaner(result, release)\n" +
+                            "return result\n",
+                        ClassName(pkg, "XrossRuntime"),
+                    )
+                    .build(),
+            )
+            .build()
+
+        // --- XrossNativeIterator ---
+        val itemType = TypeVariableName("T")
+        val xrossNativeIterator = TypeSpec.classBuilder("XrossNativeIterator")
+            .addKdoc(
+                "Iterator over a boxed Rust iterator, advanced through its `next` export.\n" +
+                    "Each item is written to a buffer of [itemLayout] and converted by [read], which takes ownership of it.\n" +
+                    "The native iterator is released once exhausted, on [close], or by the cleaner.\n",
+            )
+            .addTypeVariable(itemType)
+            .addSuperinterface(ClassName("kotlin.collections", "Iterator").parameterizedBy(itemType))
+            .addSuperinterface(AutoCloseable::class)
+            .primaryConstructor(
+                FunSpec.constructorBuilder()
+                    .addParameter("handle", MEMORY_SEGMENT)
+                    .addParameter("nextFn", MethodHandle::class)
+                    .addParameter("dropFn", MethodHandle::class)
+                    .addParameter("itemLayout", ClassName("java.lang.foreign", "MemoryLayout"))
+                    .addParameter("read", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = itemType))
+                    .build(),
+            )
+            .addProperty(PropertySpec.builder("handle", MEMORY_SEGMENT, KModifier.PRIVATE).initializer("handle").build())
+            .addProperty(PropertySpec.builder("nextFn", MethodHandle::class, KModifier.PRIVATE).initializer("nextFn").build())
+            .addProperty(
+                PropertySpec.builder("itemLayout", ClassName("java.lang.foreign", "MemoryLayout"), KModifier.PRIVATE)
+                    .initializer("itemLayout").build(),
+            )
+            .addProperty(
+                PropertySpec.builder("read", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = itemType), KModifier.PRIVATE)
+                    .initializer("read").build(),
+            )
+            .addProperty(
+                // The action captures only the handle so that the iterator itself stays collectable
+                PropertySpec.builder("cleanable", CLEANABLE, KModifier.PRIVATE)
+                    .initializer("%T.registerCleaner(this, Runnable { dropFn.invoke(handle) })", ClassName(pkg, "XrossRuntime"))
+                    .build(),
+            )
+            .addProperty(PropertySpec.builder("closed", Boolean::class, KModifier.PRIVATE).mutable(true).initializer("false").build())
+            .addProperty(PropertySpec.builder("buffered", Boolean::class, KModifier.PRIVATE).mutable(true).initializer("false").build())
+            .addProperty(
+                PropertySpec.builder("nextItem", itemType.copy(nullable = true), KModifier.PRIVATE).mutable(true).initializer("null").build(),
+            )
+            .addFunction(
+                FunSpec.builder("hasNext")
+                    .addModifiers(KModifier.OVERRIDE)
+                    .returns(Boolean::class)
+                    .addCode(
+                        "if (buffered) return true\n" +
+                            "if (closed) return false\n" +
+                            "Arena.ofConfined().use { arena ->\n" +
+                            "    val out = arena.allocate(itemLayout)\n" +
+                            "    if (nextFn.invokeExact(handle, out) as Byte != (0).toByte()) {\n" +
+                            "        nextItem = read(out)\n" +
+                            "        buffered = true\n" +
+                            "    }\n" +
+                            "}\n" +
+                            "if (!buffered) close()\n" +
+                            "return buffered\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("next")
+                    .addModifiers(KModifier.OVERRIDE)
+                    .returns(itemType)
+                    .addCode(
+                        "if (!hasNext()) throw NoSuchElementException()\n" +
+                            "@Suppress(\"UNCHECKED_CAST\")\n" +
+                            "val item = nextItem as T\n" +
+                            "nextItem = null\n" +
+                            "buffered = false\n" +
+                            "return item\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("close")
+                    .addModifiers(KModifier.OVERRIDE)
+                    .addCode(
+                        "if (closed) return\n" +
+                            "closed = true\n" +
+                            "cleanable.clean()\n",
+                    )
+                    .build(),
+            )
+            .build()
+
+        // --- XrossNativeCollection ---
+        val collectionType = ClassName(pkg, "XrossNativeCollection")
+        val memoryLayout = ClassName("java.lang.foreign", "MemoryLayout")
+        val elemType = TypeVariableName("E")
+        val xrossNativeCollection = TypeSpec.classBuilder("XrossNativeCollection")
+            .addKdoc(
+                "A collection returned by Rust, released through its `drop` export on [close] or by the cleaner.\n" +
+                    "Views read it through the `retAccessors` exports; objects they hand out borrow from it and are invalidated with it.\n",
+            )
+            .superclass(ClassName(pkg, "XrossNativeObject"))
+            .addSuperclassConstructorParameter("segment")
+            .primaryConstructor(
+                FunSpec.constructorBuilder()
+                    .addParameter("segment", MEMORY_SEGMENT)
+                    .addParameter("dropFn", MethodHandle::class)
+                    .addParameter("lenFn", MethodHandle::class)
+                    .build(),
+            )
+            .addProperty(PropertySpec.builder("lenFn", MethodHandle::class, KModifier.PRIVATE).initializer("lenFn").build())
+            .addInitializerBlock(CodeBlock.of("registerNativeCleaner(dropFn)\n"))
+            .addProperty(
+                PropertySpec.builder("size", Int::class)
+                    .getter(
+                        FunSpec.getterBuilder()
+                            .addStatement("checkOpen()")
+                            .addStatement("return (lenFn.invokeExact(segment) as Long).toInt()")
+                            .build(),
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("snapshot")
+                    .addKdoc("Copies the elements written by [fn], a `snapshot` or `values` export, converting each with [read].\n")
+                    .addTypeVariable(elemType)
+                    .addParameter("fn", MethodHandle::class)
+                    .addParameter("layout", memoryLayout)
+                    .addParameter("read", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = elemType))
+                    .returns(LIST.parameterizedBy(elemType))
+                    .addCode(
+                        "val cap = size.toLong()\n" +
+                            "if (cap == 0L) return emptyList()\n" +
+                            "return Arena.ofConfined().use { arena ->\n" +
+                            "    val out = arena.allocate(layout, cap)\n" +
+                            "    val written = fn.invokeExact(segment, out, cap) as Long\n" +
+                            "    (0L until written).map { read(out.asSlice(it * layout.byteSize(), layout.byteSize())) }\n" +
+                            "}\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("lookup")
+                    .addKdoc("Passes a buffer of [layout] to [find], a `get` lookup, and converts the element it wrote with [read], or returns [missing].\n")
+                    .addTypeVariable(elemType)
+                    .addParameter("layout", memoryLayout)
+                    .addParameter("find", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = BOOLEAN))
+                    .addParameter("read", LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = elemType))
+                    .addParameter("missing", LambdaTypeName.get(null, parameters = emptyList<ParameterSpec>(), returnType = elemType))
+                    .returns(elemType)
+                    .addCode(
+                        "checkOpen()\n" +
+                            "return Arena.ofConfined().use { arena ->\n" +
+                            "    val out = arena.allocate(layout)\n" +
+                            "    if (find(out)) read(out) else missing()\n" +
                             "}\n",
                     )
                     .build(),
             )
+            .addFunction(
+                FunSpec.builder("checkOpen")
+                    .addModifiers(KModifier.INTERNAL)
+                    .addStatement("check(isValid) { %S }", "The native collection has been closed")
+                    .build(),
+            )
+            .build()
+
+        fun collectionView(name: String, kdoc: String) = TypeSpec.classBuilder(name)
+            .addKdoc(kdoc)
+            .addSuperinterface(AutoCloseable::class)
+            .addProperty(
+                PropertySpec.builder("native", collectionType)
+                    .addKdoc("The collection read by this view; borrowed objects use it as their parent.\n")
+                    .initializer("native").build(),
+            )
+            .addFunction(FunSpec.builder("close").addModifiers(KModifier.OVERRIDE).addStatement("native.close()").build())
+
+        // --- XrossNativeList ---
+        val readElem = LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = elemType)
+        val xrossNativeList = collectionView(
+            "XrossNativeList",
+            "Read-only [List] view of a `VecDeque`, `LinkedList` or `BinaryHeap` returned by Rust.\n" +
+                "[getFn] looks elements up by index when the collection exports `get`; otherwise the elements are copied.\n",
+        )
+            .addTypeVariable(elemType)
+            .superclass(ClassName("kotlin.collections", "AbstractList").parameterizedBy(elemType))
+            .primaryConstructor(
+                FunSpec.constructorBuilder()
+                    .addParameter("native", collectionType)
+                    .addParameter("snapshotFn", MethodHandle::class)
+                    .addParameter("getFn", MethodHandle::class.asClassName().copy(nullable = true))
+                    .addParameter("layout", memoryLayout)
+                    .addParameter("read", readElem)
+                    .build(),
+            )
+            .addProperty(PropertySpec.builder("snapshotFn", MethodHandle::class, KModifier.PRIVATE).initializer("snapshotFn").build())
+            .addProperty(PropertySpec.builder("getFn", MethodHandle::class.asClassName().copy(nullable = true), KModifier.PRIVATE).initializer("getFn").build())
+            .addProperty(PropertySpec.builder("layout", memoryLayout, KModifier.PRIVATE).initializer("layout").build())
+            .addProperty(PropertySpec.builder("read", readElem, KModifier.PRIVATE).initializer("read").build())
+            .addProperty(
+                PropertySpec.builder("size", Int::class, KModifier.OVERRIDE)
+                    .getter(FunSpec.getterBuilder().addStatement("return native.size").build())
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("get")
+                    .addModifiers(KModifier.OVERRIDE)
+                    .addParameter("index", Int::class)
+                    .returns(elemType)
+                    .addCode(
+                        "val fn = getFn ?: return native.snapshot(snapshotFn, layout, read)[index]\n" +
+                            "return native.lookup(layout, { out -> fn.invokeExact(native.segment, index.toLong(), out) as Byte != (0).toByte() }, read) {\n" +
+                            "    throw IndexOutOfBoundsException(\"Index \$index out of bounds for length \$size\")\n" +
+                            "}\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("iterator")
+                    .addModifiers(KModifier.OVERRIDE)
+                    .returns(ClassName("kotlin.collections", "Iterator").parameterizedBy(elemType))
+                    .addStatement("return native.snapshot(snapshotFn, layout, read).iterator()")
+                    .build(),
+            )
+            .build()
+
+        // --- XrossNativeSet ---
+        val xrossNativeSet = collectionView(
+            "XrossNativeSet",
+            "Read-only [Set] view of a `HashSet` or `BTreeSet` returned by Rust.\n" +
+                "[containsFn] tests membership natively when the element type can be looked up; otherwise the elements are copied.\n",
+        )
+            .addTypeVariable(elemType)
+            .superclass(ClassName("kotlin.collections", "AbstractSet").parameterizedBy(elemType))
+            .primaryConstructor(
+                FunSpec.constructorBuilder()
+                    .addParameter("native", collectionType)
+                    .addParameter("snapshotFn", MethodHandle::class)
+                    .addParameter("layout", memoryLayout)
+                    .addParameter("read", readElem)
+                    .addParameter("containsFn", LambdaTypeName.get(null, elemType, returnType = BOOLEAN).copy(nullable = true))
+                    .build(),
+            )
+            .addProperty(PropertySpec.builder("snapshotFn", MethodHandle::class, KModifier.PRIVATE).initializer("snapshotFn").build())
+            .addProperty(PropertySpec.builder("layout", memoryLayout, KModifier.PRIVATE).initializer("layout").build())
+            .addProperty(PropertySpec.builder("read", readElem, KModifier.PRIVATE).initializer("read").build())
+            .addProperty(
+                PropertySpec.builder("containsFn", LambdaTypeName.get(null, elemType, returnType = BOOLEAN).copy(nullable = true), KModifier.PRIVATE)
+                    .initializer("containsFn").build(),
+            )
+            .addProperty(
+                PropertySpec.builder("size", Int::class, KModifier.OVERRIDE)
+                    .getter(FunSpec.getterBuilder().addStatement("return native.size").build())
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("contains")
+                    .addModifiers(KModifier.OVERRIDE)
+                    .addParameter("element", elemType)
+                    .returns(BOOLEAN)
+                    .addCode(
+                        "val fn = containsFn ?: return super.contains(element)\n" +
+                            "native.checkOpen()\n" +
+                            "return fn(element)\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("iterator")
+                    .addModifiers(KModifier.OVERRIDE)
+                    .returns(ClassName("kotlin.collections", "Iterator").parameterizedBy(elemType))
+                    .addStatement("return native.snapshot(snapshotFn, layout, read).iterator()")
+                    .build(),
+            )
+            .build()
+
+        // --- XrossNativeMap ---
+        val keyType = TypeVariableName("K")
+        val valueType = TypeVariableName("V")
+        val readKey = LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = keyType)
+        val readValue = LambdaTypeName.get(null, MEMORY_SEGMENT, returnType = valueType)
+        val getFnType = LambdaTypeName.get(null, keyType, MEMORY_SEGMENT, returnType = BOOLEAN).copy(nullable = true)
+        val containsFnType = LambdaTypeName.get(null, keyType, returnType = BOOLEAN).copy(nullable = true)
+        val xrossNativeMap = collectionView(
+            "XrossNativeMap",
+            "Read-only [Map] view of a `HashMap` or `BTreeMap` returned by Rust.\n" +
+                "[getFn] and [containsFn] look keys up natively when the key type allows it; otherwise the entries are copied.\n",
+        )
+            .addTypeVariable(keyType)
+            .addTypeVariable(valueType)
+            .superclass(ClassName("kotlin.collections", "AbstractMap").parameterizedBy(keyType, valueType))
+            .primaryConstructor(
+                FunSpec.constructorBuilder()
+                    .addParameter("native", collectionType)
+                    .addParameter("keysFn", MethodHandle::class)
+                    .addParameter("valuesFn", MethodHandle::class)
+                    .addParameter("keyLayout", memoryLayout)
+                    .addParameter("valueLayout", memoryLayout)
+                    .addParameter("readKey", readKey)
+                    .addParameter("readValue", readValue)
+                    .addParameter("getFn", getFnType)
+                    .addParameter("containsFn", containsFnType)
+                    .build(),
+            )
+            .addProperty(PropertySpec.builder("keysFn", MethodHandle::class, KModifier.PRIVATE).initializer("keysFn").build())
+            .addProperty(PropertySpec.builder("valuesFn", MethodHandle::class, KModifier.PRIVATE).initializer("valuesFn").build())
+            .addProperty(PropertySpec.builder("keyLayout", memoryLayout, KModifier.PRIVATE).initializer("keyLayout").build())
+            .addProperty(PropertySpec.builder("valueLayout", memoryLayout, KModifier.PRIVATE).initializer("valueLayout").build())
+            .addProperty(PropertySpec.builder("readKey", readKey, KModifier.PRIVATE).initializer("readKey").build())
+            .addProperty(PropertySpec.builder("readValue", readValue, KModifier.PRIVATE).initializer("readValue").build())
+            .addProperty(PropertySpec.builder("getFn", getFnType, KModifier.PRIVATE).initializer("getFn").build())
+            .addProperty(PropertySpec.builder("containsFn", containsFnType, KModifier.PRIVATE).initializer("containsFn").build())
+            .addProperty(
+                PropertySpec.builder("size", Int::class, KModifier.OVERRIDE)
+                    .getter(FunSpec.getterBuilder().addStatement("return native.size").build())
+                    .build(),
+            )
+            .addProperty(
+                PropertySpec.builder("entries", SET.parameterizedBy(ClassName("kotlin.collections", "Map", "Entry").parameterizedBy(keyType, valueType)), KModifier.OVERRIDE)
+                    .getter(
+                        FunSpec.getterBuilder()
+                            .addCode(
+                                "// keys と values は同じ順序で走査される\n" +
+                                    "val keys = native.snapshot(keysFn, keyLayout, readKey)\n" +
+                                    "val values = native.snapshot(valuesFn, valueLayout, readValue)\n" +
+                                    "return keys.zip(values) { k, v -> java.util.AbstractMap.SimpleImmutableEntry(k, v) }.toCollection(LinkedHashSet())\n",
+                            )
+                            .build(),
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("get")
+                    .addModifiers(KModifier.OVERRIDE)
+                    .addParameter("key", keyType)
+                    .returns(valueType.copy(nullable = true))
+                    .addCode(
+                        "val fn = getFn ?: return super.get(key)\n" +
+                            "return native.lookup<V?>(valueLayout, { out -> fn(key, out) }, readValue) { null }\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("containsKey")
+                    .addModifiers(KModifier.OVERRIDE)
+                    .addParameter("key", keyType)
+                    .returns(BOOLEAN)
+                    .addCode(
+                        "val fn = containsFn ?: return super.containsKey(key)\n" +
+                            "native.checkOpen()\n" +
+                            "return fn(key)\n",
+                    )
+                    .build(),
+            )
+            .build()
+
+        // --- XrossCallbacks ---
+        val xrossCallbacks = TypeSpec.objectBuilder("XrossCallbacks")
+            .addKdoc(
+                "Upcall stubs handed to Rust as `XrossCallback`s and trait vtables.\n" +
+                    "Each callback or vtable has its own arena, closed once Rust drops it and calls `release`.\n",
+            )
+            .addProperty(
+                PropertySpec.builder(
+                    "ARENAS",
+                    ClassName("java.util.concurrent", "ConcurrentHashMap").parameterizedBy(LONG, Arena::class.asTypeName()),
+                    KModifier.PRIVATE,
+                ).initializer("java.util.concurrent.ConcurrentHashMap()").build(),
+            )
+            .addProperty(
+                PropertySpec.builder("NEXT_ID", ClassName("java.util.concurrent.atomic", "AtomicLong"), KModifier.PRIVATE)
+                    .initializer("java.util.concurrent.atomic.AtomicLong(1)").build(),
+            )
+            .addProperty(
+                PropertySpec.builder("releaseStub", MEMORY_SEGMENT)
+                    .addKdoc("The `release` function passed to Rust along with a context from [register].\n")
+                    .delegate(
+                        "lazy(LazyThreadSafetyMode.SYNCHRONIZED) { " +
+                            "val release = java.lang.invoke.MethodHandles.lookup().findStatic(XrossCallbacks::class.java, \"release\", " +
+                            "java.lang.invoke.MethodType.methodType(java.lang.Void.TYPE, MemorySegment::class.java)); " +
+                            "Linker.nativeLinker().upcallStub(release, FunctionDescriptor.ofVoid(ValueLayout.ADDRESS), Arena.global()) }",
+                    ).build(),
+            )
+            .addFunction(
+                FunSpec.builder("upcall")
+                    .addKdoc(
+                        "Returns an upcall stub in [arena] calling [adapter], whose parameters and return value match [descriptor].\n" +
+                            "Exceptions must not escape [adapter].\n",
+                    )
+                    .addParameter("adapter", ClassName("kotlin", "Function").parameterizedBy(STAR))
+                    .addParameter("descriptor", ClassName("java.lang.foreign", "FunctionDescriptor"))
+                    .addParameter("arena", Arena::class)
+                    .returns(MEMORY_SEGMENT)
+                    .addCode(
+                        "val arity = descriptor.argumentLayouts().size\n" +
+                            "val target = java.lang.invoke.MethodHandles.publicLookup()\n" +
+                            "    .findVirtual(Class.forName(\"kotlin.jvm.functions.Function\$arity\"), \"invoke\", java.lang.invoke.MethodType.genericMethodType(arity))\n" +
+                            "    .bindTo(adapter)\n" +
+                            "    .asType(descriptor.toMethodType())\n" +
+                            "return Linker.nativeLinker().upcallStub(target, descriptor, arena)\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("register")
+                    .addKdoc("Returns the context under which [arena] is closed once Rust calls [releaseStub].\n")
+                    .addParameter("arena", Arena::class)
+                    .returns(MEMORY_SEGMENT)
+                    .addCode(
+                        "val id = NEXT_ID.getAndIncrement()\n" +
+                            "ARENAS[id] = arena\n" +
+                            "return MemorySegment.ofAddress(id)\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("create")
+                    .addKdoc("Returns an `XrossCallback { func, ctx, release }` calling [adapter]. The first parameter is the context pointer.\n")
+                    .addParameter("adapter", ClassName("kotlin", "Function").parameterizedBy(STAR))
+                    .addParameter("descriptor", ClassName("java.lang.foreign", "FunctionDescriptor"))
+                    .addParameter("allocator", ClassName("java.lang.foreign", "SegmentAllocator"))
+                    .returns(MEMORY_SEGMENT)
+                    .addCode(
+                        "val arena = Arena.ofShared()\n" +
+                            "val stub = try {\n" +
+                            "    upcall(adapter, descriptor, arena)\n" +
+                            "} catch (e: Throwable) {\n" +
+                            "    arena.close()\n" +
+                            "    throw e\n" +
+                            "}\n" +
+                            "val callback = allocator.allocate(24L, 8L)\n" +
+                            "callback.set(ValueLayout.ADDRESS, 0L, stub)\n" +
+                            "callback.set(ValueLayout.ADDRESS, 8L, register(arena))\n" +
+                            "callback.set(ValueLayout.ADDRESS, 16L, releaseStub)\n" +
+                            "return callback\n",
+                    )
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("release")
+                    .addKdoc("Called by Rust when it drops the callback with context [ctx].\n")
+                    .addAnnotation(AnnotationSpec.builder(ClassName("kotlin.jvm", "JvmStatic")).build())
+                    .addParameter("ctx", MEMORY_SEGMENT)
+                    .addCode("ARENAS.remove(ctx.address())?.close()\n")
+                    .build(),
+            )
             .build()
 
         // --- XrossAsyncLock ---
@@ -404,10 +1326,20 @@ object RuntimeGenerator {
             .addImport("java.util.concurrent.locks", "ReentrantReadWriteLock")
             .addImport("java.lang.foreign", "ValueLayout", "SegmentAllocator", "Arena", "Linker", "SymbolLookup", "FunctionDescriptor")
             .addType(xrossException)
+            .addType(xrossPanicException)
             .addType(xrossObject)
             .addType(xrossNativeObject)
             .addType(xrossRuntime)
             .addType(xrossAsync)
+            .addType(xrossErrors)
+            .addType(xrossArrays)
+            .addType(xrossStreams)
+            .addType(xrossNativeIterator)
+            .addType(xrossNativeCollection)
+            .addType(xrossNativeList)
+            .addType(xrossNativeSet)
+            .addType(xrossNativeMap)
+            .addType(xrossCallbacks)
             .addType(xrossAsyncLock)
             .addType(lockState)
             .addType(xrossString)
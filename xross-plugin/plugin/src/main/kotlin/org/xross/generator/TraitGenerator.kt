@@ -0,0 +1,148 @@
+package org.xross.generator
+
+import com.squareup.kotlinpoet.*
+import org.xross.generator.util.FFMConstants
+import org.xross.generator.util.GeneratorUtils
+import org.xross.generator.util.upcallAdapter
+import org.xross.helper.StringHelper.escapeKotlinKeyword
+import org.xross.helper.StringHelper.toCamelCase
+import org.xross.structures.XrossDefinition
+import java.io.File
+import java.lang.foreign.Arena
+import java.lang.foreign.MemorySegment
+import java.lang.foreign.SymbolLookup
+import java.lang.invoke.MethodHandle
+
+/**
+ * Generates the Kotlin side of a `#[xross_trait]`: an `Impl` interface implemented on the JVM
+ * and a handle to the Rust proxy that calls it through a vtable of upcall stubs.
+ */
+object TraitGenerator {
+    private val MEMORY_SEGMENT = MemorySegment::class.asTypeName()
+    private val HANDLE_TYPE = MethodHandle::class.asClassName()
+    private val JVM_FIELD = ClassName("kotlin.jvm", "JvmField")
+
+    fun generate(meta: XrossDefinition.Trait, outputDir: File, targetPackage: String, basePackage: String) {
+        val runtimePkg = "$basePackage.xross.runtime"
+        val xrossObject = ClassName(runtimePkg, "XrossObject")
+        val selfType = GeneratorUtils.getClassName(meta.signature, basePackage)
+        val implType = selfType.nestedClass("Impl")
+
+        val implBuilder = TypeSpec.interfaceBuilder("Impl")
+            .addKdoc(meta.docs.joinToString("\n"))
+        meta.methods.forEach { method ->
+            implBuilder.addFunction(
+                FunSpec.builder(method.name.toCamelCase().escapeKotlinKeyword())
+                    .addModifiers(KModifier.ABSTRACT)
+                    .addKdoc(method.docs.joinToString("\n"))
+                    .addParameters(
+                        method.args.map {
+                            ParameterSpec.builder(it.name.toCamelCase().escapeKotlinKeyword(), GeneratorUtils.resolveReturnType(it.ty, basePackage)).build()
+                        },
+                    )
+                    .returns(GeneratorUtils.resolveReturnType(method.ret, basePackage))
+                    .build(),
+            )
+        }
+
+        val init = CodeBlock.builder()
+            .addStatement("val lookup = %T.loaderLookup()", SymbolLookup::class.asTypeName())
+            .addStatement("%T.initializeHeap(lookup, linker)", ClassName(runtimePkg, "XrossRuntime"))
+            .addStatement(
+                "this.newHandle = linker.downcallHandle(lookup.find(%S).get(), %T.of(%M, %M))",
+                "${meta.symbolPrefix}_new",
+                FFMConstants.FUNCTION_DESCRIPTOR,
+                FFMConstants.ADDRESS,
+                FFMConstants.ADDRESS,
+            )
+            .addStatement(
+                "this.dropHandle = linker.downcallHandle(lookup.find(%S).get(), %T.ofVoid(%M))",
+                "${meta.symbolPrefix}_drop",
+                FFMConstants.FUNCTION_DESCRIPTOR,
+                FFMConstants.ADDRESS,
+            )
+
+        // vtable: ctx, release, メソッドのスロット (宣言順)
+        val callbacks = ClassName(runtimePkg, "XrossCallbacks")
+        val ofBody = CodeBlock.builder()
+            .addStatement("val arena = %T.ofShared()", Arena::class)
+            .beginControlFlow("return %T.ofConfined().use { tmp ->", Arena::class)
+            .addStatement("val vtable = tmp.allocate(%LL, 8L)", (meta.methods.size + 2) * 8L)
+            .beginControlFlow("try")
+        meta.methods.forEachIndexed { i, method ->
+            val (adapter, descriptor) = upcallAdapter(
+                method.args.map { it.ty },
+                method.ret,
+                "impl.${method.name.toCamelCase().escapeKotlinKeyword()}",
+                "a",
+                basePackage,
+            )
+            ofBody.add("vtable.set(%M, %LL, %T.upcall(\n", FFMConstants.ADDRESS, (i + 2) * 8L, callbacks)
+                .indent()
+                .add("%L,\n", adapter)
+                .add("%L,\n", descriptor)
+                .add("arena,\n")
+                .unindent()
+                .add("))\n")
+        }
+        ofBody.nextControlFlow("catch (e: %T)", Throwable::class)
+            .addStatement("arena.close()")
+            .addStatement("throw e")
+            .endControlFlow()
+            .addStatement("vtable.set(%M, 0L, %T.register(arena))", FFMConstants.ADDRESS, callbacks)
+            .addStatement("vtable.set(%M, 8L, %T.releaseStub)", FFMConstants.ADDRESS, callbacks)
+            .addStatement("val ptr = newHandle.invokeExact(vtable) as %T", MEMORY_SEGMENT)
+            .addStatement("%T(ptr, parent = null, isPersistent = false).also { it.registerNativeCleaner(dropHandle) }", selfType)
+            .endControlFlow()
+
+        val companion = TypeSpec.companionObjectBuilder()
+            .addProperty(PropertySpec.builder("linker", java.lang.foreign.Linker::class, KModifier.PRIVATE).initializer("%T.nativeLinker()", java.lang.foreign.Linker::class).build())
+            .addProperty(PropertySpec.builder("newHandle", HANDLE_TYPE, KModifier.PRIVATE).mutable().build())
+            .addProperty(PropertySpec.builder("dropHandle", HANDLE_TYPE, KModifier.INTERNAL).addAnnotation(JVM_FIELD).mutable().build())
+            // プロキシのフィールドは JVM から読まないので大きさは 0
+            .addProperty(PropertySpec.builder("STRUCT_SIZE", LONG, KModifier.INTERNAL).addAnnotation(JVM_FIELD).initializer("0L").build())
+            .addInitializerBlock(init.build())
+            .addFunction(
+                GeneratorUtils.buildFromPointerBase("fromPointer", selfType, basePackage)
+                    .addCode("return %T(ptr, parent = parent, isPersistent = isPersistent)\n", selfType)
+                    .build(),
+            )
+            .addFunction(
+                FunSpec.builder("of")
+                    .addKdoc(
+                        "Returns a Rust proxy calling [impl]. It is released when closed or collected,\n" +
+                            "or by Rust when passed as an owned `Box<dyn %L>`.\n",
+                        meta.name,
+                    )
+                    .addParameter("impl", implType)
+                    .returns(selfType)
+                    .addCode(ofBody.build())
+                    .build(),
+            )
+            .build()
+
+        val classBuilder = TypeSpec.classBuilder(selfType)
+            .addKdoc(meta.docs.joinToString("\n"))
+            .superclass(ClassName(runtimePkg, "XrossNativeObject"))
+            .primaryConstructor(
+                FunSpec.constructorBuilder()
+                    .addModifiers(KModifier.INTERNAL)
+                    .addParameter("raw", MEMORY_SEGMENT)
+                    .addParameter("parent", xrossObject.copy(nullable = true))
+                    .addParameter("isPersistent", BOOLEAN)
+                    .build(),
+            )
+            .addSuperclassConstructorParameter("raw")
+            .addSuperclassConstructorParameter("parent")
+            .addSuperclassConstructorParameter("isPersistent")
+            .addType(implBuilder.build())
+            .addType(companion)
+
+        val fileSpec = FileSpec.builder(targetPackage, selfType.simpleName)
+            .addType(classBuilder.build())
+            .indent("    ")
+            .build()
+
+        GeneratorUtils.writeToDisk(fileSpec, outputDir)
+    }
+}
@@ -8,6 +8,7 @@ class TypeResolver(
     metadataDir: File,
 ) {
     private val shortNameToFqn = mutableMapOf<String, MutableSet<String>>()
+    private val errorSignatures = mutableSetOf<String>()
     private val json = Json { ignoreUnknownKeys = true }
 
     init {
@@ -18,6 +19,7 @@ class TypeResolver(
                     val name = def.name
                     val fqn = def.signature
                     shortNameToFqn.getOrPut(name) { mutableSetOf() }.add(fqn)
+                    if (def is XrossDefinition.Error) errorSignatures.add(fqn)
                 } catch (e: Exception) {
                     println("Failed to parse $file: ${e.message}")
                     // Ignore malformed JSON during scanning
@@ -26,6 +28,11 @@ class TypeResolver(
         }
     }
 
+    /**
+     * Whether the resolved [signature] names a `#[derive(XrossError)]` type.
+     */
+    fun isError(signature: String): Boolean = signature in errorSignatures
+
     fun resolve(
         signature: String,
         context: String = "Unknown",
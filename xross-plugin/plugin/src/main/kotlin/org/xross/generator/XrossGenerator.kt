@@ -25,6 +25,8 @@ object XrossGenerator {
             is XrossDefinition.Struct, is XrossDefinition.Enum, is XrossDefinition.Opaque ->
                 generateComplexType(resolvedMeta, outputDir, targetPackage, basePackage)
             is XrossDefinition.Function -> generateFunction(resolvedMeta, outputDir, targetPackage, basePackage)
+            is XrossDefinition.Trait -> TraitGenerator.generate(resolvedMeta, outputDir, targetPackage, basePackage)
+            is XrossDefinition.Error -> ErrorGenerator.generate(resolvedMeta, outputDir, targetPackage, basePackage)
         }
     }
 
@@ -33,6 +35,8 @@ object XrossGenerator {
         is XrossDefinition.Enum -> meta.copy(variants = meta.variants.map { v -> v.copy(fields = v.fields.map { it.copy(ty = resolveType(it.ty, resolver, "${meta.name}.${v.name}")) }) }, methods = resolveMethods(meta.methods, resolver, meta.name))
         is XrossDefinition.Opaque -> meta
         is XrossDefinition.Function -> meta.copy(method = resolveMethods(listOf(meta.method), resolver, meta.name).first())
+        is XrossDefinition.Trait -> meta.copy(methods = resolveMethods(meta.methods, resolver, meta.name))
+        is XrossDefinition.Error -> meta
     }
 
     private fun resolveMethods(methods: List<XrossMethod>, resolver: TypeResolver, context: String): List<XrossMethod> = methods.map { m ->
@@ -40,10 +44,14 @@ object XrossGenerator {
     }
 
     private fun resolveType(type: XrossType, resolver: TypeResolver, context: String): XrossType = when (type) {
-        is XrossType.Object -> type.copy(signature = resolver.resolve(type.signature, context))
+        is XrossType.Object -> resolver.resolve(type.signature, context).let { type.copy(signature = it, isError = resolver.isError(it)) }
         is XrossType.Optional -> type.copy(inner = resolveType(type.inner, resolver, context))
         is XrossType.Result -> type.copy(ok = resolveType(type.ok, resolver, context), err = resolveType(type.err, resolver, context))
+        is XrossType.Tuple -> type.copy(elements = type.elements.map { resolveType(it, resolver, context) })
+        is XrossType.Function -> type.copy(args = type.args.map { resolveType(it, resolver, context) }, ret = resolveType(type.ret, resolver, context))
         is XrossType.Async -> type.copy(inner = resolveType(type.inner, resolver, context))
+        is XrossType.Stream -> type.copy(inner = resolveType(type.inner, resolver, context))
+        is XrossType.Iterator -> type.copy(inner = resolveType(type.inner, resolver, context))
         else -> type
     }
 
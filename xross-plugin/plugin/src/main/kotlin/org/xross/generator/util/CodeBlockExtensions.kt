@@ -12,12 +12,13 @@ fun CodeBlock.Builder.addResourceConstruction(
     dropExpr: CodeBlock,
     flagType: ClassName,
 ) {
-    if (inner.isOwned) {
+    if (inner.isOwned || inner.isShared) {
         // オブジェクトごとに Arena を作らず、Cleaner/close() で直接 drop を呼ぶ
         addStatement("val res = %L.reinterpret(%L)", resRaw, sizeExpr)
 
         // 所有権を持つオブジェクトは parent = null
         addStatement("val resObj = %L(res, parent = null, isPersistent = false)", fromPointerExpr)
+        if (inner.isShared) addStatement("resObj.isSharedHandle = true")
         // Cleaner に drop を登録
         addStatement("resObj.registerNativeCleaner(%L)", dropExpr)
         addStatement("resObj")
@@ -70,12 +71,10 @@ fun CodeBlock.Builder.addFactoryBody(
         addStatement("val resRawObj = %L as %T", handleCall, memorySegment)
         addStatement("val isOk = resRawObj.get(java.lang.foreign.ValueLayout.JAVA_BYTE, 0L) != (0).toByte()")
         addStatement("val resRaw = resRawObj.get(java.lang.foreign.ValueLayout.ADDRESS, 8L)")
-        beginControlFlow("if (!isOk)")
-        addRustStringResolution("resRaw", "errVal", basePackage = basePackage)
-        addStatement("throw %T(errVal)", ClassName(runtimePkg, "XrossException"))
-        endControlFlow()
+        addStatement("if (!isOk) throw %T.takeError(resRaw)", ClassName(runtimePkg, "XrossErrors"))
     } else {
         addStatement("val resRaw = %L as %T", handleCall, memorySegment)
+        addHandleCheck(basePackage)
     }
 
     addStatement(
@@ -93,6 +92,16 @@ fun CodeBlock.Builder.addFactoryBody(
     return this
 }
 
+/**
+ * Throws the handle violation recorded by an export that cannot return errors (see `xross_take_handle_error`).
+ */
+fun CodeBlock.Builder.addHandleCheck(basePackage: String): CodeBlock.Builder = addStatement("%T.checkHandles()", ClassName("$basePackage.xross.runtime", "XrossRuntime"))
+
+/**
+ * Passes the slot key of the object [name] to Rust before its handle is used, rejecting a stale handle (see `xross_check_handle_key`).
+ */
+fun CodeBlock.Builder.addHandleKeyCheck(name: String, basePackage: String): CodeBlock.Builder = addStatement("%T.checkHandle($name)", ClassName("$basePackage.xross.runtime", "XrossRuntime"))
+
 fun CodeBlock.Builder.addResultAllocation(
     ty: XrossType.Result,
     valueName: String,
@@ -157,13 +166,21 @@ fun CodeBlock.Builder.addResultVariantResolution(
     selfType: ClassName,
     basePackage: String,
     dropHandleName: String = "dropHandle",
+    collection: CollectionHandles? = null,
 ) {
     when (type) {
         is XrossType.Object -> {
-            beginControlFlow("run")
-            val (sizeExpr, dropExpr, fromPointerExpr) = GeneratorUtils.compareExprs(targetTypeName, selfType, dropHandleName)
-            addResourceConstruction(type, ptrName, sizeExpr, fromPointerExpr, dropExpr, ClassName("", "UNUSED"))
-            endControlFlow()
+            if (type.isError) {
+                // XrossError 型は例外クラスとして生成され、ハンドルは fromHandle が解放する
+                add("%T.fromHandle(%L)", targetTypeName.copy(nullable = false), ptrName)
+            } else {
+                beginControlFlow("run")
+                // Arc<T> のハンドルは強参照を 1 つ持ち、_arc_drop で解放する
+                val dropName = if (type.isShared) "arcDropHandle" else dropHandleName
+                val (sizeExpr, dropExpr, fromPointerExpr) = GeneratorUtils.compareExprs(targetTypeName, selfType, dropName)
+                addResourceConstruction(type, ptrName, sizeExpr, fromPointerExpr, dropExpr, ClassName("", "UNUSED"))
+                endControlFlow()
+            }
         }
         is XrossType.RustString -> {
             beginControlFlow("run")
@@ -171,6 +188,25 @@ fun CodeBlock.Builder.addResultVariantResolution(
             addStatement("str")
             endControlFlow()
         }
+        is XrossType.Optional -> {
+            addOptionalResolution(type.inner, ptrName, selfType, basePackage, dropHandleName, collection)
+        }
+        is XrossType.Tuple -> {
+            addTupleResolution(type, ptrName, selfType, basePackage, dropHandleName)
+        }
+        is XrossType.Vec -> {
+            addArrayResolution(type, ptrName, boxed = true, targetTypeName, selfType, basePackage, dropHandleName)
+        }
+        is XrossType.VecDeque,
+        is XrossType.LinkedList,
+        is XrossType.HashSet,
+        is XrossType.BTreeSet,
+        is XrossType.BinaryHeap,
+        is XrossType.HashMap,
+        is XrossType.BTreeMap
+        -> {
+            addCollectionConstruction(type, ptrName, checkNotNull(collection) { "No accessor exports for $type" }, selfType, basePackage)
+        }
         is XrossType.F32 -> {
             add("%T.fromBits(%L.address().toInt())", Float::class, ptrName)
         }
@@ -206,23 +242,172 @@ fun CodeBlock.Builder.addOptionalResolution(
     selfType: ClassName,
     basePackage: String,
     dropHandleName: String = "dropHandle",
+    collection: CollectionHandles? = null,
 ) {
     beginControlFlow("run")
+    val optional = XrossType.Optional(inner)
+    if (optional.isTaggedOption) {
+        // XrossOption { isSome, value } は値渡しなので NULL ではなくタグで None を判定する
+        addStatement("val optSeg = $resRaw.reinterpret(%L)", optional.kotlinSize)
+        beginControlFlow("if (optSeg.get(%M, 0L) == (0).toByte())", FFMConstants.JAVA_BYTE)
+        addStatement("null")
+        nextControlFlow("else")
+        if (inner is XrossType.Bool) {
+            addStatement("optSeg.get(%M, %LL) != (0).toByte()", FFMConstants.JAVA_BYTE, inner.kotlinSize)
+        } else {
+            addStatement("optSeg.get(%M, %LL)", inner.layoutMember, inner.kotlinSize)
+        }
+        endControlFlow()
+        endControlFlow()
+        return
+    }
     beginControlFlow("if ($resRaw == %T.NULL)", MEMORY_SEGMENT)
         .addStatement("null")
     nextControlFlow("else")
     val innerType = GeneratorUtils.resolveReturnType(inner, basePackage)
-    addResultVariantResolution(inner, resRaw, innerType, selfType, basePackage, dropHandleName)
+    addResultVariantResolution(inner, resRaw, innerType, selfType, basePackage, dropHandleName, collection)
+    endControlFlow()
     endControlFlow()
+}
+
+/**
+ * Wraps the collection returned at [ptrName] in a read-only view over the exports bound to [collection].
+ * Strings and primitives are copied out of it; objects borrow from it until the view is closed.
+ */
+fun CodeBlock.Builder.addCollectionConstruction(
+    type: XrossType,
+    ptrName: String,
+    collection: CollectionHandles,
+    selfType: ClassName,
+    basePackage: String,
+) {
+    val runtimePkg = "$basePackage.xross.runtime"
+    beginControlFlow("run")
+    addStatement("if (%L == %T.NULL) throw %T(%S)", ptrName, MEMORY_SEGMENT, NullPointerException::class.asTypeName(), "Unexpected NULL return")
+    addStatement("val coll = %T(%L, %L, %L)", ClassName(runtimePkg, "XrossNativeCollection"), ptrName, collection.drop, collection.len)
+    val args = mutableListOf(CodeBlock.of("coll"))
+    val view = when (type) {
+        is XrossType.HashMap, is XrossType.BTreeMap -> {
+            val (key, value) = when (type) {
+                is XrossType.HashMap -> type.key to type.value
+                else -> (type as XrossType.BTreeMap).key to type.value
+            }
+            args.add(CodeBlock.of(collection.snapshot))
+            args.add(CodeBlock.of(checkNotNull(collection.values)))
+            args.add(collectionElementLayout(key))
+            args.add(collectionElementLayout(value))
+            args.add(collectionElementReader(key, selfType, basePackage))
+            args.add(collectionElementReader(value, selfType, basePackage))
+            args.add(collection.get?.let { collectionKeyLookup(key, it, withOut = true, basePackage) } ?: CodeBlock.of("null"))
+            args.add(collection.contains?.let { collectionKeyLookup(key, it, withOut = false, basePackage) } ?: CodeBlock.of("null"))
+            "XrossNativeMap"
+        }
+        is XrossType.HashSet, is XrossType.BTreeSet -> {
+            val elem = when (type) {
+                is XrossType.HashSet -> type.inner
+                else -> (type as XrossType.BTreeSet).inner
+            }
+            args.add(CodeBlock.of(collection.snapshot))
+            args.add(collectionElementLayout(elem))
+            args.add(collectionElementReader(elem, selfType, basePackage))
+            args.add(collection.contains?.let { collectionKeyLookup(elem, it, withOut = false, basePackage) } ?: CodeBlock.of("null"))
+            "XrossNativeSet"
+        }
+        else -> {
+            val elem = when (type) {
+                is XrossType.VecDeque -> type.inner
+                is XrossType.LinkedList -> type.inner
+                is XrossType.BinaryHeap -> type.inner
+                else -> throw IllegalArgumentException("Not a collection: $type")
+            }
+            args.add(CodeBlock.of(collection.snapshot))
+            args.add(CodeBlock.of(collection.get ?: "null"))
+            args.add(collectionElementLayout(elem))
+            args.add(collectionElementReader(elem, selfType, basePackage))
+            "XrossNativeList"
+        }
+    }
+    add("%T(\n", ClassName(runtimePkg, view))
+    indent()
+    args.forEach { add("%L,\n", it) }
+    unindent()
+    add(")\n")
     endControlFlow()
 }
 
+/**
+ * Layout of a collection element as written by the `snapshot`, `values` and `get` exports.
+ */
+private fun collectionElementLayout(ty: XrossType): CodeBlock = when {
+    ty.isTaggedOption || ty is XrossType.RustString -> ty.layoutCode
+    ty.isPrimitive -> CodeBlock.of("%M", ty.layoutMember)
+    else -> CodeBlock.of("%M", FFMConstants.ADDRESS)
+}
+
+/**
+ * Lambda converting a collection element written to `out`. Strings stay owned by the collection and are not freed.
+ */
+private fun collectionElementReader(ty: XrossType, selfType: ClassName, basePackage: String): CodeBlock {
+    val body = CodeBlock.builder()
+    when {
+        ty is XrossType.Optional && ty.isTaggedOption -> body.addOptionalResolution(ty.inner, "out", selfType, basePackage)
+        ty is XrossType.RustString -> {
+            body.addRustStringResolution("out", shouldFree = false, basePackage = basePackage)
+            body.addStatement("str")
+        }
+        ty is XrossType.Bool -> body.addStatement("out.get(%M, 0L) != (0).toByte()", FFMConstants.JAVA_BYTE)
+        ty.isPrimitive -> body.addStatement("out.get(%M, 0L)", ty.layoutMember)
+        ty is XrossType.Object && ty.ownership == XrossType.Ownership.Owned && !ty.isError -> {
+            // 要素はコレクションからの借用なので、コレクションを parent にして drop しない
+            val (sizeExpr, _, fromPointerExpr) = GeneratorUtils.compareExprs(GeneratorUtils.resolveReturnType(ty, basePackage), selfType)
+            body.addStatement("%L(out.get(%M, 0L).reinterpret(%L), parent = coll, isPersistent = false)", fromPointerExpr, FFMConstants.ADDRESS, sizeExpr)
+        }
+        else -> throw IllegalArgumentException("Unsupported element of a returned collection: $ty")
+    }
+    return CodeBlock.builder()
+        .add("{ out ->\n")
+        .indent()
+        .add(body.build())
+        .unindent()
+        .add("}")
+        .build()
+}
+
+/**
+ * Lambda calling the `get` ([withOut]) or `contains` export [handle] with a key, passed like a method argument.
+ */
+private fun collectionKeyLookup(key: XrossType, handle: String, withOut: Boolean, basePackage: String): CodeBlock {
+    val keyType = GeneratorUtils.resolveReturnType(key, basePackage)
+    val params = if (withOut) CodeBlock.of("key: %T, out: %T", keyType, MEMORY_SEGMENT) else CodeBlock.of("key: %T", keyType)
+    val out = if (withOut) ", out" else ""
+    val body = CodeBlock.builder()
+    when (key) {
+        is XrossType.RustString -> {
+            body.beginControlFlow("%T.ofConfined().use { arena ->", FFMConstants.ARENA)
+            body.addStatement("val (keyBytes, keyEnc) = %T.copyStringBytes(key, arena)", ClassName("$basePackage.xross.runtime", "XrossRuntime"))
+            body.addStatement("$handle.invokeExact(coll.segment, keyBytes, keyBytes.byteSize(), keyEnc$out) as Byte != (0).toByte()")
+            body.endControlFlow()
+        }
+        is XrossType.Object -> body.addStatement("$handle.invokeExact(coll.segment, key.segment$out) as Byte != (0).toByte()")
+        is XrossType.Bool -> body.addStatement("$handle.invokeExact(coll.segment, if (key) 1.toByte() else 0.toByte()$out) as Byte != (0).toByte()")
+        else -> body.addStatement("$handle.invokeExact(coll.segment, key$out) as Byte != (0).toByte()")
+    }
+    return CodeBlock.builder()
+        .add("{ %L ->\n", params)
+        .indent()
+        .add(body.build())
+        .unindent()
+        .add("}")
+        .build()
+}
+
 fun CodeBlock.Builder.addResultResolution(
     ty: XrossType.Result,
     resRaw: String,
     selfType: ClassName,
     basePackage: String,
     dropHandleName: String = "dropHandle",
+    collection: CollectionHandles? = null,
 ) {
     beginControlFlow("run")
     val runtimePkg = "$basePackage.xross.runtime"
@@ -232,17 +417,353 @@ fun CodeBlock.Builder.addResultResolution(
 
     beginControlFlow("if (isOk)")
     add("val okVal = ")
-    addResultVariantResolution(ty.ok, "ptr", GeneratorUtils.resolveReturnType(ty.ok, basePackage), selfType, basePackage, dropHandleName)
+    addResultVariantResolution(ty.ok, "ptr", GeneratorUtils.resolveReturnType(ty.ok, basePackage), selfType, basePackage, dropHandleName, collection)
     addStatement("Result.success(okVal)")
 
     nextControlFlow("else")
-    add("val errVal = ")
-    addResultVariantResolution(ty.err, "ptr", GeneratorUtils.resolveReturnType(ty.err, basePackage), selfType, basePackage, dropHandleName)
-    addStatement("Result.failure(%T(errVal))", ClassName(runtimePkg, "XrossException"))
+    add(
+        "Result.failure(%T.takeError(ptr, %L))\n",
+        ClassName(runtimePkg, "XrossErrors"),
+        errorValueMapper(ty.err, selfType, basePackage, dropHandleName),
+    )
     endControlFlow()
     endControlFlow()
 }
 
+/**
+ * The `value` mapper passed to `XrossErrors.takeError` for an `Err` of type [err]: only object errors
+ * hand their value over, other errors are represented by their message.
+ */
+fun errorValueMapper(
+    err: XrossType,
+    selfType: ClassName,
+    basePackage: String,
+    dropHandleName: String = "dropHandle",
+): CodeBlock {
+    if (err !is XrossType.Object) return CodeBlock.of("null")
+    return CodeBlock.builder()
+        .add("{ value ->\n")
+        .indent()
+        .apply { addResultVariantResolution(err, "value", GeneratorUtils.resolveReturnType(err, basePackage), selfType, basePackage, dropHandleName) }
+        .unindent()
+        .add("}")
+        .build()
+}
+
+private fun tupleComponent(valueName: String, index: Int, arity: Int): String = when (arity) {
+    2, 3 -> "$valueName.${listOf("first", "second", "third")[index]}"
+    else -> "$valueName[$index]"
+}
+
+/**
+ * Writes the tuple [valueName] into a new `#[repr(C)]` struct named [targetMemoryName].
+ * Owned objects are moved into Rust and relinquished on the JVM side.
+ */
+fun CodeBlock.Builder.addTupleAllocation(
+    ty: XrossType.Tuple,
+    valueName: String,
+    targetMemoryName: String,
+    arenaName: String = "java.lang.foreign.Arena.ofAuto()",
+    basePackage: String = "org.example",
+): CodeBlock.Builder {
+    val offsets = ty.offsets(incoming = true)
+    addStatement("val $targetMemoryName = $arenaName.allocate(%LL, %LL)", ty.byteSize(incoming = true), ty.byteAlign(incoming = true))
+    ty.elements.forEachIndexed { i, elem ->
+        val v = "${targetMemoryName}E$i"
+        val offset = offsets[i]
+        if (ty.elements.size in 2..3) {
+            addStatement("val $v = %L", tupleComponent(valueName, i, ty.elements.size))
+        } else {
+            addStatement("val $v = %L as %T", tupleComponent(valueName, i, ty.elements.size), GeneratorUtils.resolveReturnType(elem, basePackage))
+        }
+        when {
+            elem is XrossType.Bool -> {
+                addStatement("$targetMemoryName.set(%M, %LL, if ($v) 1.toByte() else 0.toByte())", FFMConstants.JAVA_BYTE, offset)
+            }
+            elem is XrossType.Optional && elem.isTaggedOption -> {
+                val inner = elem.inner
+                beginControlFlow("if ($v != null)")
+                addStatement("$targetMemoryName.set(%M, %LL, 1.toByte())", FFMConstants.JAVA_BYTE, offset)
+                if (inner is XrossType.Bool) {
+                    addStatement("$targetMemoryName.set(%M, %LL, if ($v) 1.toByte() else 0.toByte())", FFMConstants.JAVA_BYTE, offset + inner.kotlinSize)
+                } else {
+                    addStatement("$targetMemoryName.set(%M, %LL, $v)", inner.layoutMember, offset + inner.kotlinSize)
+                }
+                endControlFlow()
+            }
+            elem is XrossType.RustString || (elem is XrossType.Optional && elem.inner is XrossType.RustString) -> {
+                // XrossStringView { ptr, len, encoding = UTF-8 }; None は ptr = NULL
+                val isOptional = elem is XrossType.Optional
+                if (isOptional) beginControlFlow("if ($v != null)")
+                addStatement("val ${v}Bytes = $v.toByteArray(java.nio.charset.StandardCharsets.UTF_8)")
+                addStatement("$targetMemoryName.set(%M, %LL, $arenaName.allocateFrom(%M, *${v}Bytes))", FFMConstants.ADDRESS, offset, FFMConstants.JAVA_BYTE)
+                addStatement("$targetMemoryName.set(%M, %LL, ${v}Bytes.size.toLong())", FFMConstants.JAVA_LONG, offset + 8)
+                if (isOptional) endControlFlow()
+            }
+            elem is XrossType.Object -> {
+                addStatement("$targetMemoryName.set(%M, %LL, $v.segment)", FFMConstants.ADDRESS, offset)
+                if (elem.isOwned) addStatement("$v.relinquish()")
+            }
+            elem is XrossType.Optional && elem.inner is XrossType.Object -> {
+                addStatement("$targetMemoryName.set(%M, %LL, $v?.segment ?: %T.NULL)", FFMConstants.ADDRESS, offset, MEMORY_SEGMENT)
+                if (elem.inner.isOwned) addStatement("$v?.relinquish()")
+            }
+            elem.isPrimitive && elem != XrossType.Void && elem != XrossType.Pointer -> {
+                addStatement("$targetMemoryName.set(%M, %LL, $v)", elem.layoutMember, offset)
+            }
+            else -> throw IllegalArgumentException("Unsupported tuple element passed to Rust: $elem")
+        }
+    }
+    return this
+}
+
+/**
+ * Writes the elements of [valueName] into a new array of element representations named [targetMemoryName],
+ * passed to Rust with its length for a `Vec<T>` or `&[T]` argument.
+ * Strings are passed as string views and objects as their handles; owned objects of a [moved] `Vec` are relinquished.
+ */
+fun CodeBlock.Builder.addSequenceAllocation(
+    inner: XrossType,
+    valueName: String,
+    targetMemoryName: String,
+    moved: Boolean,
+    arenaName: String = "java.lang.foreign.Arena.ofAuto()",
+    basePackage: String = "org.example",
+): CodeBlock.Builder {
+    if (inner != XrossType.Bool && XrossType.Vec(inner).kotlinType !is ParameterizedTypeName) {
+        // IntArray などの数値配列はそのままコピーする
+        addStatement("val $targetMemoryName = $arenaName.allocateFrom(%M, *$valueName)", inner.layoutMember)
+        return this
+    }
+    val size = inner.reprSize(incoming = true)
+    addStatement("val $targetMemoryName = $arenaName.allocate(%LL * $valueName.size, %LL)", size, inner.reprAlign(incoming = true))
+    beginControlFlow("$valueName.forEachIndexed { i, e ->")
+    addStatement("val offset = i * %LL", size)
+    when {
+        inner is XrossType.Bool -> {
+            addStatement("$targetMemoryName.set(%M, offset, if (e) 1.toByte() else 0.toByte())", FFMConstants.JAVA_BYTE)
+        }
+        inner is XrossType.Optional && inner.isTaggedOption -> {
+            // None はゼロ初期化されたまま isSome = 0 になる
+            val value = inner.inner
+            beginControlFlow("if (e != null)")
+            addStatement("$targetMemoryName.set(%M, offset, 1.toByte())", FFMConstants.JAVA_BYTE)
+            if (value is XrossType.Bool) {
+                addStatement("$targetMemoryName.set(%M, offset + %LL, if (e) 1.toByte() else 0.toByte())", FFMConstants.JAVA_BYTE, value.kotlinSize)
+            } else {
+                addStatement("$targetMemoryName.set(%M, offset + %LL, e)", value.layoutMember, value.kotlinSize)
+            }
+            endControlFlow()
+        }
+        inner is XrossType.RustString || (inner is XrossType.Optional && inner.inner is XrossType.RustString) -> {
+            // XrossStringView { ptr, len, encoding }; None は ptr = NULL
+            val isOptional = inner is XrossType.Optional
+            if (isOptional) beginControlFlow("if (e != null)")
+            addStatement("val (bytes, encoding) = %T.copyStringBytes(e, $arenaName)", ClassName("$basePackage.xross.runtime", "XrossRuntime"))
+            addStatement("$targetMemoryName.set(%M, offset, bytes)", FFMConstants.ADDRESS)
+            addStatement("$targetMemoryName.set(%M, offset + 8L, bytes.byteSize())", FFMConstants.JAVA_LONG)
+            addStatement("$targetMemoryName.set(%M, offset + 16L, encoding)", FFMConstants.JAVA_BYTE)
+            if (isOptional) endControlFlow()
+        }
+        inner is XrossType.Object -> {
+            addStatement("if (e.segment == %T.NULL || !e.isValid) throw %T(%S)", MEMORY_SEGMENT, NullPointerException::class.asTypeName(), "Element of `$valueName` dropped or invalid")
+            addHandleKeyCheck("e", basePackage)
+            if (inner.isShared) addStatement("check(e.isSharedHandle) { %S }", "Elements of `$valueName` must be shared handles, returned by a method returning Arc<T>")
+            addStatement("$targetMemoryName.set(%M, offset, e.segment)", FFMConstants.ADDRESS)
+            if (moved && inner.isOwned) addStatement("e.relinquish()")
+        }
+        inner is XrossType.Optional && inner.inner is XrossType.Object -> {
+            addStatement("$targetMemoryName.set(%M, offset, e?.segment ?: %T.NULL)", FFMConstants.ADDRESS, MEMORY_SEGMENT)
+            if (moved && inner.inner.isOwned) addStatement("e?.relinquish()")
+        }
+        inner.isPrimitive && inner != XrossType.Void && inner != XrossType.Pointer -> {
+            addStatement("$targetMemoryName.set(%M, offset, e)", inner.layoutMember)
+        }
+        else -> throw IllegalArgumentException("Unsupported element passed to Rust: $inner")
+    }
+    endControlFlow()
+    return this
+}
+
+/**
+ * Decodes the `XrossArray` returned for a `Vec<T>` at [source], stored in place or, when [boxed], behind a pointer.
+ * Strings are freed after decoding and owned objects are handed over to the JVM.
+ */
+fun CodeBlock.Builder.addArrayResolution(
+    type: XrossType.Vec,
+    source: String,
+    boxed: Boolean,
+    targetTypeName: TypeName,
+    selfType: ClassName,
+    basePackage: String,
+    dropHandleName: String = "dropHandle",
+) {
+    val inner = type.inner
+    val reader = CodeBlock.builder()
+    when {
+        inner is XrossType.Optional && inner.isTaggedOption -> reader.addOptionalResolution(inner.inner, "elem", selfType, basePackage, dropHandleName)
+        inner is XrossType.RustString -> {
+            reader.addRustStringResolution("elem", basePackage = basePackage)
+            reader.addStatement("str")
+        }
+        inner is XrossType.Bool -> reader.addStatement("elem.get(%M, 0L) != (0).toByte()", FFMConstants.JAVA_BYTE)
+        inner.isPrimitive -> reader.addStatement("elem.get(%M, 0L)", inner.layoutMember)
+        else -> {
+            // 文字列と数値以外の要素はポインタで、オブジェクトの所有権は JVM に移る
+            reader.addStatement("val elemPtr = elem.get(%M, 0L)", FFMConstants.ADDRESS)
+            reader.addResultVariantResolution(inner, "elemPtr", GeneratorUtils.resolveReturnType(inner, basePackage), selfType, basePackage, dropHandleName)
+        }
+    }
+    add("%T.%L(%L) { elem ->\n", ClassName("$basePackage.xross.runtime", "XrossArrays"), if (boxed) "takeBoxed" else "take", source)
+    indent()
+    add(reader.build())
+    unindent()
+    add("}")
+    // 数値の Vec は IntArray などの配列で返す
+    val arrayType = targetTypeName.copy(nullable = false)
+    if (arrayType is ClassName) add(".to%L()", arrayType.simpleName)
+}
+
+/**
+ * Reads a tuple returned from Rust as a `#[repr(C)]` struct at [resRaw].
+ * Strings are freed after decoding and owned objects are handed over to the JVM.
+ */
+fun CodeBlock.Builder.addTupleResolution(
+    ty: XrossType.Tuple,
+    resRaw: String,
+    selfType: ClassName,
+    basePackage: String,
+    dropHandleName: String = "dropHandle",
+) {
+    val offsets = ty.offsets(incoming = false)
+    beginControlFlow("run")
+    addStatement("val tupleSeg = $resRaw.reinterpret(%LL)", ty.byteSize(incoming = false))
+    ty.elements.forEachIndexed { i, elem ->
+        val v = "tupleE$i"
+        val offset = offsets[i]
+        when {
+            elem is XrossType.Bool -> {
+                addStatement("val $v = tupleSeg.get(%M, %LL) != (0).toByte()", FFMConstants.JAVA_BYTE, offset)
+            }
+            elem is XrossType.Optional && elem.isTaggedOption -> {
+                addStatement("val ${v}Seg = tupleSeg.asSlice(%LL, %LL)", offset, elem.kotlinSize)
+                add("val $v = ")
+                addOptionalResolution(elem.inner, "${v}Seg", selfType, basePackage, dropHandleName)
+            }
+            elem is XrossType.RustString -> {
+                addRustStringResolution("tupleSeg.asSlice(${offset}L, 24L)", v, basePackage = basePackage)
+            }
+            elem.isPrimitive && elem != XrossType.Void && elem != XrossType.Pointer -> {
+                addStatement("val $v = tupleSeg.get(%M, %LL)", elem.layoutMember, offset)
+            }
+            else -> {
+                addStatement("val ${v}Ptr = tupleSeg.get(%M, %LL)", FFMConstants.ADDRESS, offset)
+                add("val $v = ")
+                addResultVariantResolution(elem, "${v}Ptr", GeneratorUtils.resolveReturnType(elem, basePackage), selfType, basePackage, dropHandleName)
+            }
+        }
+    }
+    val values = ty.elements.indices.joinToString(", ") { "tupleE$it" }
+    when (ty.elements.size) {
+        2 -> addStatement("%T($values)", Pair::class)
+        3 -> addStatement("%T($values)", Triple::class)
+        else -> addStatement("listOf($values)")
+    }
+    endControlFlow()
+}
+
+/**
+ * Builds the adapter lambda and `FunctionDescriptor` of an upcall from Rust that calls [callee] with [args]
+ * and returns [ret]. The adapter takes the context pointer first.
+ * Values lent by Rust are converted for the duration of the call, and owned objects are handed over to the JVM.
+ */
+fun upcallAdapter(
+    args: List<XrossType>,
+    ret: XrossType,
+    callee: String,
+    paramPrefix: String,
+    basePackage: String,
+): Pair<CodeBlock, CodeBlock> {
+    val runtimePkg = "$basePackage.xross.runtime"
+    val params = mutableListOf(CodeBlock.of("_: %T", MEMORY_SEGMENT))
+    val argLayouts = mutableListOf(CodeBlock.of("%M", FFMConstants.ADDRESS))
+    val values = args.mapIndexed { i, arg ->
+        val p = "$paramPrefix$i"
+        when {
+            arg is XrossType.Bool -> {
+                params.add(CodeBlock.of("$p: %T", BYTE))
+                argLayouts.add(CodeBlock.of("%M", FFMConstants.JAVA_BYTE))
+                CodeBlock.of("$p != 0.toByte()")
+            }
+            arg is XrossType.RustString -> {
+                // Rust が呼び出しの間だけ貸す XrossString なので解放しない
+                params.add(CodeBlock.of("$p: %T", MEMORY_SEGMENT))
+                argLayouts.add(FFMConstants.XROSS_STRING_LAYOUT_CODE)
+                CodeBlock.of("%T($p).toString()", ClassName(runtimePkg, "XrossString"))
+            }
+            arg is XrossType.Object && !arg.isShared -> {
+                params.add(CodeBlock.of("$p: %T", MEMORY_SEGMENT))
+                argLayouts.add(CodeBlock.of("%M", FFMConstants.ADDRESS))
+                val objType = GeneratorUtils.resolveReturnType(arg, basePackage)
+                if (arg.isOwned) {
+                    CodeBlock.of(
+                        "%T.fromPointer($p.reinterpret(%T.STRUCT_SIZE), parent = null, isPersistent = false).also { it.registerNativeCleaner(%T.dropHandle) }",
+                        objType,
+                        objType,
+                        objType,
+                    )
+                } else {
+                    // 借用は呼び出しの間だけ有効なので drop しない
+                    CodeBlock.of("%T.fromPointer($p.reinterpret(%T.STRUCT_SIZE), parent = null, isPersistent = true)", objType, objType)
+                }
+            }
+            arg.isPrimitive && arg != XrossType.Void && arg != XrossType.Pointer -> {
+                params.add(CodeBlock.of("$p: %T", arg.kotlinType))
+                argLayouts.add(CodeBlock.of("%M", arg.layoutMember))
+                CodeBlock.of(p)
+            }
+            else -> throw IllegalArgumentException("Unsupported upcall argument: $arg")
+        }
+    }
+    val descriptor = when {
+        ret is XrossType.Void -> CodeBlock.of("%T.ofVoid(%L)", FFMConstants.FUNCTION_DESCRIPTOR, argLayouts.joinToCode(", "))
+        ret.isPrimitive && ret != XrossType.Pointer ->
+            CodeBlock.of("%T.of(%M, %L)", FFMConstants.FUNCTION_DESCRIPTOR, ret.layoutMember, argLayouts.joinToCode(", "))
+        else -> throw IllegalArgumentException("Unsupported upcall return type: $ret")
+    }
+    val call = CodeBlock.of("$callee(%L)", values.joinToCode(", "))
+    val body = if (ret is XrossType.Bool) CodeBlock.of("if (%L) 1.toByte() else 0.toByte()", call) else call
+    val adapter = CodeBlock.builder()
+        .add("{ %L ->\n", params.joinToCode(", "))
+        .indent()
+        .add("%L\n", body)
+        .unindent()
+        .add("}")
+        .build()
+    return adapter to descriptor
+}
+
+/**
+ * Wraps the lambda [valueName] in an upcall stub and writes the `XrossCallback` passed to Rust into [targetMemoryName].
+ */
+fun CodeBlock.Builder.addCallbackAllocation(
+    ty: XrossType.Function,
+    valueName: String,
+    targetMemoryName: String,
+    arenaName: String = "java.lang.foreign.Arena.ofAuto()",
+    basePackage: String = "org.example",
+): CodeBlock.Builder {
+    val (adapter, descriptor) = upcallAdapter(ty.args, ty.ret, valueName, "${valueName}A", basePackage)
+    add("val $targetMemoryName = %T.create(\n", ClassName("$basePackage.xross.runtime", "XrossCallbacks"))
+    indent()
+    add("%L,\n", adapter)
+    add("%L,\n", descriptor)
+    add("$arenaName,\n")
+    unindent()
+    add(")\n")
+    return this
+}
+
 fun CodeBlock.Builder.addArgumentPreparation(
     type: XrossType,
     name: String,
@@ -290,16 +811,39 @@ fun CodeBlock.Builder.addArgumentPreparation(
                 addStatement("throw %T(%S + $name.segment + %S + $name.isValid)", NullPointerException::class.asTypeName(), "Arg invalid: segment=", ", isValid=")
                 endControlFlow()
             }
+            addHandleKeyCheck(name, basePackage)
+            if (type.isShared) {
+                // Arc<T> の引数は Box のハンドルからは作れない
+                addStatement("check($name.isSharedHandle) { %S }", "`$name` must be a shared handle, returned by a method returning Arc<T>")
+            }
             callArgs.add(CodeBlock.of("$name.segment"))
         }
 
         is XrossType.Bool -> callArgs.add(CodeBlock.of("if ($name) 1.toByte() else 0.toByte()"))
         is XrossType.Optional -> {
-            addStatement(
-                "val ${name}Memory = if ($name == null) %T.NULL else %L",
-                MEMORY_SEGMENT,
-                GeneratorUtils.generateAllocMsg(type.inner, name, arenaName),
-            )
+            if (type.isTaggedOption) {
+                // None は isSome = 0 のゼロ初期化された XrossOption として渡す
+                addStatement("val ${name}Memory = $arenaName.allocate(%L)", type.layoutCode)
+                beginControlFlow("if ($name != null)")
+                addStatement("${name}Memory.set(%M, 0L, 1.toByte())", FFMConstants.JAVA_BYTE)
+                if (type.inner is XrossType.Bool) {
+                    addStatement("${name}Memory.set(%M, %LL, if ($name) 1.toByte() else 0.toByte())", FFMConstants.JAVA_BYTE, type.inner.kotlinSize)
+                } else {
+                    addStatement("${name}Memory.set(%M, %LL, $name)", type.inner.layoutMember, type.inner.kotlinSize)
+                }
+                endControlFlow()
+            } else if (type.inner is XrossType.Tuple) {
+                beginControlFlow("val ${name}Memory = if ($name == null) %T.NULL else run", MEMORY_SEGMENT)
+                addTupleAllocation(type.inner, name, "tupleMemory", arenaName, basePackage)
+                addStatement("tupleMemory")
+                endControlFlow()
+            } else {
+                addStatement(
+                    "val ${name}Memory = if ($name == null) %T.NULL else %L",
+                    MEMORY_SEGMENT,
+                    GeneratorUtils.generateAllocMsg(type.inner, name, arenaName),
+                )
+            }
             callArgs.add(CodeBlock.of("${name}Memory"))
         }
 
@@ -308,14 +852,24 @@ fun CodeBlock.Builder.addArgumentPreparation(
             callArgs.add(CodeBlock.of("${name}Memory"))
         }
 
+        is XrossType.Tuple -> {
+            addTupleAllocation(type, name, "${name}Memory", arenaName, basePackage)
+            callArgs.add(CodeBlock.of("${name}Memory"))
+        }
+
+        is XrossType.Function -> {
+            addCallbackAllocation(type, name, "${name}Callback", arenaName, basePackage)
+            callArgs.add(CodeBlock.of("${name}Callback"))
+        }
+
         is XrossType.Slice, is XrossType.Vec -> {
-            val isNullable = type is XrossType.Optional // This might need refinement depending on how nullability is tracked
-            // Actually, we can check if the type name is nullable
-            val kotlinType = GeneratorUtils.resolveReturnType(type, basePackage)
-            if (kotlinType.isNullable) {
-                addStatement("val ${name}Seg = if ($name == null) %T.NULL else %T.ofArray($name)", MEMORY_SEGMENT, MEMORY_SEGMENT)
-            } else {
+            val inner = if (type is XrossType.Vec) type.inner else (type as XrossType.Slice).inner
+            val heapAccess = handleMode is org.xross.structures.HandleMode.Critical && handleMode.allowHeapAccess
+            if (heapAccess && type.kotlinType !is ParameterizedTypeName && inner != XrossType.Bool) {
+                // ヒープアクセスが許可された critical 呼び出しでは数値配列をコピーせずに渡す
                 addStatement("val ${name}Seg = %T.ofArray($name)", MEMORY_SEGMENT)
+            } else {
+                addSequenceAllocation(inner, name, "${name}Seg", moved = type is XrossType.Vec, arenaName, basePackage)
             }
             callArgs.add(CodeBlock.of("${name}Seg"))
             callArgs.add(CodeBlock.of("$name.size.toLong()"))
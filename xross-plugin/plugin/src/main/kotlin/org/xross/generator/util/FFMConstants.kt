@@ -3,6 +3,7 @@ package org.xross.generator.util
 import com.squareup.kotlinpoet.ClassName
 import com.squareup.kotlinpoet.MemberName
 import com.squareup.kotlinpoet.asTypeName
+import org.xross.structures.XrossType
 import java.lang.foreign.*
 
 object FFMConstants {
@@ -32,7 +33,7 @@ object FFMConstants {
     )
 
     val XROSS_TASK_LAYOUT_CODE = com.squareup.kotlinpoet.CodeBlock.of(
-        "%T.structLayout(%M.withName(%S), %M.withName(%S), %M.withName(%S))",
+        "%T.structLayout(%M.withName(%S), %M.withName(%S), %M.withName(%S), %M.withName(%S), %M.withName(%S))",
         MEMORY_LAYOUT,
         ADDRESS,
         "taskPtr",
@@ -40,6 +41,34 @@ object FFMConstants {
         "pollFn",
         ADDRESS,
         "dropFn",
+        ADDRESS,
+        "cancelFn",
+        ADDRESS,
+        "statusFn",
+    )
+
+    val XROSS_STREAM_LAYOUT_CODE = com.squareup.kotlinpoet.CodeBlock.of(
+        "%T.structLayout(%M.withName(%S), %M.withName(%S), %M.withName(%S), %M.withName(%S))",
+        MEMORY_LAYOUT,
+        ADDRESS,
+        "streamPtr",
+        ADDRESS,
+        "pollNextFn",
+        ADDRESS,
+        "cancelFn",
+        ADDRESS,
+        "dropFn",
+    )
+
+    val XROSS_CALLBACK_LAYOUT_CODE = com.squareup.kotlinpoet.CodeBlock.of(
+        "%T.structLayout(%M.withName(%S), %M.withName(%S), %M.withName(%S))",
+        MEMORY_LAYOUT,
+        ADDRESS,
+        "func",
+        ADDRESS,
+        "ctx",
+        ADDRESS,
+        "release",
     )
 
     val XROSS_STRING_LAYOUT_CODE = com.squareup.kotlinpoet.CodeBlock.of(
@@ -53,6 +82,49 @@ object FFMConstants {
         "cap",
     )
 
+    val XROSS_ARRAY_LAYOUT_CODE = com.squareup.kotlinpoet.CodeBlock.of(
+        "%T.structLayout(%M.withName(%S), %M.withName(%S), %M.withName(%S), %M.withName(%S), %M.withName(%S))",
+        MEMORY_LAYOUT,
+        ADDRESS,
+        "ptr",
+        JAVA_LONG,
+        "len",
+        JAVA_LONG,
+        "cap",
+        JAVA_LONG,
+        "elemSize",
+        JAVA_LONG,
+        "elemAlign",
+    )
+
+    /**
+     * Layout of `XrossOption<T>` for a primitive [inner]: the `isSome` byte, then the value at its natural alignment.
+     */
+    fun xrossOptionLayoutCode(inner: XrossType): com.squareup.kotlinpoet.CodeBlock {
+        val padding = inner.kotlinSize - 1
+        return if (padding > 0) {
+            com.squareup.kotlinpoet.CodeBlock.of(
+                "%T.structLayout(%M.withName(%S), %T.paddingLayout(%L), %M.withName(%S))",
+                MEMORY_LAYOUT,
+                JAVA_BYTE,
+                "isSome",
+                MEMORY_LAYOUT,
+                padding,
+                inner.layoutMember,
+                "value",
+            )
+        } else {
+            com.squareup.kotlinpoet.CodeBlock.of(
+                "%T.structLayout(%M.withName(%S), %M.withName(%S))",
+                MEMORY_LAYOUT,
+                JAVA_BYTE,
+                "isSome",
+                inner.layoutMember,
+                "value",
+            )
+        }
+    }
+
     val XROSS_STRING_VIEW_LAYOUT_CODE = com.squareup.kotlinpoet.CodeBlock.of(
         "%T.structLayout(%M.withName(%S), %M.withName(%S), %M.withName(%S), %T.paddingLayout(7))",
         MEMORY_LAYOUT,
@@ -22,7 +22,7 @@ object FieldBodyGenerator {
 
     fun buildGetterBody(ctx: FieldContext): CodeBlock {
         val body = CodeBlock.builder()
-        GeneratorUtils.addAliveCheck(body, "Access error")
+        GeneratorUtils.addAliveCheck(body, ctx.basePackage, "Access error")
 
         body.apply {
             if (ctx.backingFieldName != null) {
@@ -37,7 +37,14 @@ object FieldBodyGenerator {
             }
 
             when (val ty = ctx.field.ty) {
-                is XrossType.Object -> {
+                is XrossType.Object -> if (ty.isShared) {
+                    // Arc<T> フィールドは getter が強参照を 1 つ取ったハンドルを返す
+                    val handleName = GeneratorUtils.getPropertyHandleName(ctx.handleBaseName, ty, true)
+                    addStatement("val resRaw = $handleName.invokeExact(this.segment) as %T", MEMORY_SEGMENT)
+                    addHandleCheck(ctx.basePackage)
+                    add("res = ")
+                    addResultVariantResolution(ty, "resRaw", ctx.kType, ctx.selfType, ctx.basePackage)
+                } else {
                     val isOwned = ty.ownership == XrossType.Ownership.Owned
                     val isBoxed = ty.ownership == XrossType.Ownership.Boxed
                     val (sizeExpr, _, fromPointerExpr) = GeneratorUtils.compareExprs(ctx.kType, ctx.selfType)
@@ -63,7 +70,16 @@ object FieldBodyGenerator {
 
                 is XrossType.Optional -> {
                     val handleName = GeneratorUtils.getPropertyHandleName(ctx.handleBaseName, ty, true)
-                    addStatement("val resRaw = $handleName.invokeExact(this.segment) as %T", MEMORY_SEGMENT)
+                    if (ty.isTaggedOption) {
+                        addStatement(
+                            "val resRaw = $handleName.invokeExact(java.lang.foreign.Arena.ofAuto() as %T, this.segment) as %T",
+                            SegmentAllocator::class.asTypeName(),
+                            MEMORY_SEGMENT,
+                        )
+                    } else {
+                        addStatement("val resRaw = $handleName.invokeExact(this.segment) as %T", MEMORY_SEGMENT)
+                    }
+                    addHandleCheck(ctx.basePackage)
                     add("res = ")
                     addOptionalResolution(ty.inner, "resRaw", ctx.selfType, ctx.basePackage)
                 }
@@ -75,15 +91,26 @@ object FieldBodyGenerator {
                         SegmentAllocator::class.asTypeName(),
                         MEMORY_SEGMENT,
                     )
+                    addHandleCheck(ctx.basePackage)
                     add("res = ")
                     addResultResolution(ty, "resRaw", ctx.selfType, ctx.basePackage)
                 }
 
+                is XrossType.Tuple -> {
+                    val handleName = GeneratorUtils.getPropertyHandleName(ctx.handleBaseName, ty, true)
+                    addStatement("val outRaw = java.lang.foreign.Arena.ofAuto().allocate(%LL, %LL)", ty.byteSize(incoming = false), ty.byteAlign(incoming = false))
+                    addStatement("$handleName.invoke(this.segment, outRaw)")
+                    addHandleCheck(ctx.basePackage)
+                    add("res = ")
+                    addTupleResolution(ty, "outRaw", ctx.selfType, ctx.basePackage)
+                }
+
                 is XrossType.RustString -> {
                     val handleName = GeneratorUtils.getPropertyHandleName(ctx.handleBaseName, ty, true)
                     if (handleName.isNotEmpty()) {
                         // Inline String conversion to avoid XrossString object overhead
                         addStatement("val outRaw = java.lang.foreign.Arena.ofAuto().run { $handleName.invokeExact(this as %T, this@${className(ctx.selfType)}.segment) as %T }", SegmentAllocator::class.asTypeName(), MEMORY_SEGMENT)
+                        addHandleCheck(ctx.basePackage)
                         addStatement("val ptr = outRaw.get(%T.ADDRESS, 0L)", ValueLayout::class)
                         addStatement("val len = outRaw.get(%T.JAVA_LONG, 8L)", ValueLayout::class)
                         beginControlFlow("res = if (ptr == %T.NULL || len == 0L)", MEMORY_SEGMENT)
@@ -131,12 +158,16 @@ object FieldBodyGenerator {
 
     fun buildSetterBody(ctx: FieldContext): CodeBlock {
         val body = CodeBlock.builder()
-        GeneratorUtils.addAliveCheck(body, "Invalid Access")
+        GeneratorUtils.addAliveCheck(body, ctx.basePackage, "Invalid Access")
 
         when (val ty = ctx.field.ty) {
             is XrossType.Object -> {
                 body.addStatement("if (v.segment == %T.NULL || !v.isValid) throw %T(%S)", MEMORY_SEGMENT, NullPointerException::class, "Invalid Arg")
-                if (ty.ownership == XrossType.Ownership.Owned) {
+                body.addHandleKeyCheck("v", ctx.basePackage)
+                if (ty.isShared) {
+                    body.addStatement("${GeneratorUtils.getPropertyHandleName(ctx.handleBaseName, ty, false)}.invoke(this.segment, v.segment)")
+                    body.addHandleCheck(ctx.basePackage)
+                } else if (ty.ownership == XrossType.Ownership.Owned) {
                     val (sizeExpr, _, _) = GeneratorUtils.compareExprs(ctx.kType, ctx.selfType)
                     body.addStatement("this.segment.asSlice(${ctx.offsetName}, %L).copyFrom(v.segment)", sizeExpr)
                 } else {
@@ -144,12 +175,13 @@ object FieldBodyGenerator {
                 }
             }
 
-            is XrossType.RustString, is XrossType.Optional, is XrossType.Result -> {
+            is XrossType.RustString, is XrossType.Optional, is XrossType.Result, is XrossType.Tuple -> {
                 val handleName = GeneratorUtils.getPropertyHandleName(ctx.handleBaseName, ty, false)
                 if (handleName.isNotEmpty()) {
                     val callArgs = mutableListOf<CodeBlock>()
                     body.addArgumentPreparation(ty, "v", callArgs, basePackage = ctx.basePackage, arenaName = "java.lang.foreign.Arena.ofAuto()")
                     body.addStatement("$handleName.invoke(this.segment, ${callArgs.joinToString(", ")})")
+                    body.addHandleCheck(ctx.basePackage)
                 }
             }
 
@@ -215,9 +215,38 @@ object GeneratorUtils {
         is XrossType.Object -> getClassName(type.signature, basePackage)
         is XrossType.Optional -> resolveReturnType(type.inner, basePackage).copy(nullable = true)
         is XrossType.Result -> ClassName("kotlin", "Result").parameterizedBy(resolveReturnType(type.ok, basePackage))
+        is XrossType.Tuple -> XrossType.tupleKotlinType(type.elements.map { resolveReturnType(it, basePackage) })
+        is XrossType.Function -> LambdaTypeName.get(
+            parameters = type.args.map { resolveReturnType(it, basePackage) }.toTypedArray(),
+            returnType = resolveReturnType(type.ret, basePackage),
+        )
+        is XrossType.Async -> resolveReturnType(type.inner, basePackage)
+        is XrossType.Stream -> ClassName("kotlinx.coroutines.flow", "Flow").parameterizedBy(resolveReturnType(type.inner, basePackage))
+        is XrossType.Iterator -> ClassName("kotlin.sequences", "Sequence").parameterizedBy(resolveReturnType(type.inner, basePackage))
+        // 数値の要素は IntArray などの配列のまま、それ以外は List で受け渡す
+        is XrossType.Slice -> if (type.kotlinType is ParameterizedTypeName) LIST.parameterizedBy(resolveReturnType(type.inner, basePackage)) else type.kotlinType
+        is XrossType.Vec -> if (type.kotlinType is ParameterizedTypeName) LIST.parameterizedBy(resolveReturnType(type.inner, basePackage)) else type.kotlinType
+        is XrossType.VecDeque -> LIST.parameterizedBy(resolveReturnType(type.inner, basePackage))
+        is XrossType.LinkedList -> LIST.parameterizedBy(resolveReturnType(type.inner, basePackage))
+        is XrossType.BinaryHeap -> LIST.parameterizedBy(resolveReturnType(type.inner, basePackage))
+        is XrossType.HashSet -> SET.parameterizedBy(resolveReturnType(type.inner, basePackage))
+        is XrossType.BTreeSet -> SET.parameterizedBy(resolveReturnType(type.inner, basePackage))
+        is XrossType.HashMap -> MAP.parameterizedBy(resolveReturnType(type.key, basePackage), resolveReturnType(type.value, basePackage))
+        is XrossType.BTreeMap -> MAP.parameterizedBy(resolveReturnType(type.key, basePackage), resolveReturnType(type.value, basePackage))
         else -> type.kotlinType
     }
 
+    /**
+     * Returns the collection carried by a return type, looking through `Option` and the `Ok` side of `Result`
+     * like the `retAccessors` exports do.
+     */
+    fun returnedCollection(type: XrossType): XrossType? = when {
+        type.isCollection -> type
+        type is XrossType.Optional -> returnedCollection(type.inner)
+        type is XrossType.Result -> returnedCollection(type.ok)
+        else -> null
+    }
+
     /**
      * Returns the name of the MethodHandle for a given XrossMethod.
      */
@@ -263,7 +292,7 @@ object GeneratorUtils {
     ): Triple<CodeBlock, CodeBlock, CodeBlock> {
         val isSelf = targetTypeName.copy(nullable = false) == selfType
         val sizeExpr = if (isSelf) CodeBlock.of("STRUCT_SIZE") else CodeBlock.of("%T.STRUCT_SIZE", targetTypeName)
-        val dropExpr = if (isSelf) CodeBlock.of(dropHandleName) else CodeBlock.of("%T.dropHandle", targetTypeName)
+        val dropExpr = if (isSelf) CodeBlock.of(dropHandleName) else CodeBlock.of("%T.%L", targetTypeName, dropHandleName)
         val fromPointerExpr = if (isSelf) CodeBlock.of("fromPointer") else CodeBlock.of("%T.fromPointer", targetTypeName)
         return Triple(sizeExpr, dropExpr, fromPointerExpr)
     }
@@ -377,10 +406,11 @@ object GeneratorUtils {
     }
 
     /**
-     * Adds a check to ensure the object is still alive (not NULL and valid).
+     * Adds a check to ensure the object is still alive (not NULL, valid and, with `checked-handles`, not stale).
      */
-    fun addAliveCheck(body: CodeBlock.Builder, message: String = "Access error") {
+    fun addAliveCheck(body: CodeBlock.Builder, basePackage: String, message: String = "Access error") {
         body.addStatement("if (this.segment == %T.NULL || !this.isValid) throw %T(%S)", MEMORY_SEGMENT, NullPointerException::class, message)
+        body.addHandleKeyCheck("this", basePackage)
     }
 
     /**
@@ -401,6 +431,19 @@ object GeneratorUtils {
      * Prepares arguments and optionally an Arena if needed by the arguments.
      * Returns the name of the arena to use.
      */
+    /**
+     * Returns true if preparing [args] allocates, so the call is wrapped in a confined arena.
+     */
+    fun needsArgumentArena(args: List<org.xross.structures.XrossField>): Boolean = args.any {
+        it.ty is XrossType.RustString ||
+            it.ty is XrossType.Optional ||
+            it.ty is XrossType.Result ||
+            it.ty is XrossType.Tuple ||
+            it.ty is XrossType.Function ||
+            it.ty is XrossType.Slice ||
+            it.ty is XrossType.Vec
+    }
+
     fun prepareArgumentsAndArena(
         method: org.xross.structures.XrossMethod,
         body: CodeBlock.Builder,
@@ -430,7 +473,7 @@ object GeneratorUtils {
         arenaName: String? = null,
         namePrefix: String = "",
     ): String {
-        val needsArena = args.any { it.ty is XrossType.RustString || it.ty is XrossType.Optional || it.ty is XrossType.Result }
+        val needsArena = needsArgumentArena(args)
         val finalArenaName = arenaName ?: if (needsArena) "arena" else "java.lang.foreign.Arena.ofAuto()"
 
         if (needsArena && arenaName == null) {
@@ -452,3 +495,23 @@ object GeneratorUtils {
         return finalArenaName
     }
 }
+
+/**
+ * Names of the companion handles bound to the `retAccessors` exports of [method], e.g. `wordCountsRetLenHandle`.
+ */
+class CollectionHandles(method: org.xross.structures.XrossMethod) {
+    val accessors = checkNotNull(method.retAccessors) { "Collection returned by ${method.name} has no accessor exports" }
+    private val prefix = method.name.toCamelCase()
+
+    val len get() = "${prefix}RetLenHandle"
+    val get get() = accessors.get?.let { "${prefix}RetGetHandle" }
+    val contains get() = accessors.contains?.let { "${prefix}RetContainsHandle" }
+    val snapshot get() = "${prefix}RetSnapshotHandle"
+    val values get() = accessors.values?.let { "${prefix}RetValuesHandle" }
+    val drop get() = "${prefix}RetDropHandle"
+
+    /**
+     * All handles declared for the collection.
+     */
+    val all get() = listOfNotNull(len, get, contains, snapshot, values, drop)
+}
@@ -26,4 +26,9 @@ sealed class HandleMode {
     @Serializable
     @SerialName("panicable")
     data object Panicable : HandleMode()
+
+    /** Synchronous method run on a blocking thread pool. Exported like an async method, so [XrossMethod.isAsync] is set. */
+    @Serializable
+    @SerialName("blocking")
+    data object Blocking : HandleMode()
 }
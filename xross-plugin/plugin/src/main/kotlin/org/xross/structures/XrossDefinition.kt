@@ -20,6 +20,11 @@ sealed class XrossDefinition {
     abstract val isCopy: Boolean
     abstract val symbolPrefix: String
 
+    /**
+     * Whether the type is handed to the JVM as `Arc<T>` and exports `{symbolPrefix}_arc_clone` / `_arc_drop`.
+     */
+    abstract val isShared: Boolean
+
     /**
      * A standard Rust struct.
      */
@@ -34,6 +39,7 @@ sealed class XrossDefinition {
         override val methods: List<XrossMethod> = emptyList(),
         override val docs: List<String> = emptyList(),
         override val isCopy: Boolean = false,
+        override val isShared: Boolean = false,
     ) : XrossDefinition()
 
     /**
@@ -50,6 +56,7 @@ sealed class XrossDefinition {
         override val methods: List<XrossMethod> = emptyList(),
         override val docs: List<String> = emptyList(),
         override val isCopy: Boolean = false,
+        override val isShared: Boolean = false,
     ) : XrossDefinition()
 
     /**
@@ -67,6 +74,7 @@ sealed class XrossDefinition {
         override val docs: List<String> = emptyList(),
         val isClonable: Boolean = true,
         override val isCopy: Boolean = false,
+        override val isShared: Boolean = false,
     ) : XrossDefinition()
 
     /**
@@ -85,5 +93,43 @@ sealed class XrossDefinition {
         override val symbolPrefix: String get() = symbol
         override val methods: List<XrossMethod> get() = listOf(method)
         override val isCopy: Boolean get() = false
+        override val isShared: Boolean get() = false
+    }
+
+    /**
+     * A trait implemented on the JVM and called by Rust through a vtable.
+     * Methods are listed in vtable slot order.
+     */
+    @Serializable
+    @SerialName("trait")
+    data class Trait(
+        override val signature: String,
+        override val symbolPrefix: String,
+        override val packageName: String,
+        override val name: String,
+        override val methods: List<XrossMethod> = emptyList(),
+        override val docs: List<String> = emptyList(),
+    ) : XrossDefinition() {
+        override val isCopy: Boolean get() = false
+        override val isShared: Boolean get() = false
+    }
+
+    /**
+     * An error type deriving `XrossError`, thrown on the JVM as one exception class per variant.
+     * A struct is recorded as a single variant named after the type.
+     */
+    @Serializable
+    @SerialName("error")
+    data class Error(
+        override val signature: String,
+        override val symbolPrefix: String,
+        override val packageName: String,
+        override val name: String,
+        val variants: List<XrossErrorVariant> = emptyList(),
+        override val docs: List<String> = emptyList(),
+    ) : XrossDefinition() {
+        override val methods: List<XrossMethod> get() = emptyList()
+        override val isCopy: Boolean get() = false
+        override val isShared: Boolean get() = false
     }
 }
@@ -0,0 +1,17 @@
+package org.xross.structures
+
+import kotlinx.serialization.Serializable
+
+/**
+ * Metadata for a single variant of an error type.
+ */
+@Serializable
+data class XrossErrorVariant(
+    val name: String,
+    /** Message template from `#[error("...")]`, if any. The runtime message is always the `Display` output. */
+    val message: String? = null,
+    val fields: List<XrossField> = emptyList(),
+    /** Field holding the underlying error, reported through the `source()` chain. */
+    val source: String? = null,
+    val docs: List<String> = emptyList(),
+)
@@ -14,8 +14,39 @@ data class XrossMethod(
     val isConstructor: Boolean,
     val isDefault: Boolean = false,
     val isAsync: Boolean = false,
+    /** `{symbol}_notify`, reporting completion of an async method through an upcall. */
+    val notifySymbol: String? = null,
     val args: List<XrossField>,
     val ret: XrossType,
+    /** Helper exports for consuming and releasing a returned iterator. */
+    val retIterator: XrossIteratorAccessors? = null,
+    /** Helper exports for reading and releasing a returned collection. */
+    val retAccessors: XrossCollectionAccessors? = null,
     val safety: XrossThreadSafety,
     val docs: List<String> = emptyList(),
 )
+
+/**
+ * Native helper exports generated for an iterator returned by a method.
+ */
+@Serializable
+data class XrossIteratorAccessors(
+    val next: String,
+    val sizeHint: String,
+    val nextChunk: String? = null,
+    val drop: String,
+)
+
+/**
+ * Native helper exports generated for a collection returned by a method.
+ * Non-primitive elements they hand out are borrowed from the collection and stay valid until [drop] is called.
+ */
+@Serializable
+data class XrossCollectionAccessors(
+    val len: String,
+    val get: String? = null,
+    val contains: String? = null,
+    val snapshot: String,
+    val values: String? = null,
+    val drop: String,
+)
@@ -18,4 +18,7 @@ enum class XrossMethodType {
 
     /** Consumes ownership of self. The Kotlin instance should be invalidated after calling this. */
     OwnedInstance,
+
+    /** Takes a new strong reference to self (`self: Arc<Self>`). Only valid on shared handles; the instance stays valid. */
+    SharedInstance,
 }
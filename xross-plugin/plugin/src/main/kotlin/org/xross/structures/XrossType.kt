@@ -45,14 +45,14 @@ sealed class XrossType {
     data class BTreeMap(val key: XrossType, val value: XrossType) : XrossType()
 
     /**
-     * Ownership model for bridged types.
+     * Ownership model for bridged types. A [Shared] handle (`Arc<T>`) holds one strong reference.
      */
-    enum class Ownership { Owned, Boxed, Ref, MutRef }
+    enum class Ownership { Owned, Boxed, Ref, MutRef, Shared }
 
     /**
-     * A user-defined object type.
+     * A user-defined object type. [isError] marks a `#[derive(XrossError)]` type, set when the signature is resolved.
      */
-    data class Object(val signature: String, val ownership: Ownership = Ownership.Owned) : XrossType()
+    data class Object(val signature: String, val ownership: Ownership = Ownership.Owned, val isError: Boolean = false) : XrossType()
 
     /**
      * An optional type.
@@ -64,11 +64,49 @@ sealed class XrossType {
      */
     data class Result(val ok: XrossType, val err: XrossType) : XrossType()
 
+    /**
+     * A tuple of values, passed as a `#[repr(C)]` struct of the element representations.
+     */
+    data class Tuple(val elements: List<XrossType>) : XrossType() {
+        /**
+         * Offsets of the elements in the struct passed to Rust ([incoming]) or returned from it.
+         */
+        fun offsets(incoming: Boolean): List<Long> {
+            var offset = 0L
+            return elements.map { e ->
+                offset = alignUp(offset, e.reprAlign(incoming))
+                offset.also { offset += e.reprSize(incoming) }
+            }
+        }
+
+        fun byteAlign(incoming: Boolean): Long = elements.maxOfOrNull { it.reprAlign(incoming) } ?: 1L
+
+        fun byteSize(incoming: Boolean): Long {
+            val end = offsets(incoming).lastOrNull()?.plus(elements.last().reprSize(incoming)) ?: 0L
+            return alignUp(end, byteAlign(incoming))
+        }
+    }
+
+    /**
+     * A callback supplied by the JVM, passed to Rust as an `XrossCallback` upcall stub.
+     */
+    data class Function(val args: List<XrossType>, val ret: XrossType) : XrossType()
+
     /**
      * An asynchronous type.
      */
     data class Async(val inner: XrossType) : XrossType()
 
+    /**
+     * An asynchronous stream of values, surfaced as a `Flow`.
+     */
+    data class Stream(val inner: XrossType) : XrossType()
+
+    /**
+     * A lazily consumed sequence of values, surfaced as a once-only `Sequence` read through the `retIterator` helper exports.
+     */
+    data class Iterator(val inner: XrossType) : XrossType()
+
     /**
      * Returns the KotlinPoet [TypeName] for this type.
      */
@@ -105,7 +143,7 @@ sealed class XrossType {
                 Bool -> BooleanArray::class.asTypeName()
                 else -> List::class.asClassName().parameterizedBy(inner.kotlinType)
             }
-            is VecDeque -> ClassName("kotlin.collections", "ArrayDeque").parameterizedBy(inner.kotlinType)
+            is VecDeque -> List::class.asClassName().parameterizedBy(inner.kotlinType)
             is LinkedList -> List::class.asClassName().parameterizedBy(inner.kotlinType)
             is HashSet -> Set::class.asClassName().parameterizedBy(inner.kotlinType)
             is BTreeSet -> Set::class.asClassName().parameterizedBy(inner.kotlinType)
@@ -114,7 +152,11 @@ sealed class XrossType {
             is BTreeMap -> Map::class.asClassName().parameterizedBy(key.kotlinType, value.kotlinType)
             is Optional -> inner.kotlinType.copy(nullable = true)
             is Result -> ok.kotlinType
+            is Tuple -> tupleKotlinType(elements.map { it.kotlinType })
+            is Function -> LambdaTypeName.get(parameters = args.map { it.kotlinType }.toTypedArray(), returnType = ret.kotlinType)
             is Async -> inner.kotlinType
+            is Stream -> ClassName("kotlinx.coroutines.flow", "Flow").parameterizedBy(inner.kotlinType)
+            is Iterator -> ClassName("kotlin.sequences", "Sequence").parameterizedBy(inner.kotlinType)
             Pointer, is Object -> ClassName("java.lang.foreign", "MemorySegment")
         }
 
@@ -150,7 +192,11 @@ sealed class XrossType {
         get() = when (this) {
             is Result -> FFMConstants.XROSS_RESULT_LAYOUT_CODE
             is RustString -> FFMConstants.XROSS_STRING_LAYOUT_CODE
+            is Vec -> FFMConstants.XROSS_ARRAY_LAYOUT_CODE
+            is Optional -> if (isTaggedOption) FFMConstants.xrossOptionLayoutCode(inner) else CodeBlock.of("%M", layoutMember)
             is Async -> FFMConstants.XROSS_TASK_LAYOUT_CODE
+            is Stream -> FFMConstants.XROSS_STREAM_LAYOUT_CODE
+            is Function -> FFMConstants.XROSS_CALLBACK_LAYOUT_CODE
             else -> CodeBlock.of("%M", layoutMember)
         }
 
@@ -162,6 +208,8 @@ sealed class XrossType {
             is Object -> ownership == Ownership.Owned || ownership == Ownership.Boxed
             is Result,
             is Async,
+            is Stream,
+            is Iterator,
             is Vec,
             is VecDeque,
             is LinkedList,
@@ -174,13 +222,23 @@ sealed class XrossType {
             else -> false
         }
 
+    /**
+     * Returns true if this is an `Arc<T>` handle, released with `{prefix}_arc_drop` instead of `{prefix}_drop`.
+     */
+    val isShared: Boolean
+        get() = this is Object && ownership == Ownership.Shared
+
     val isComplex: Boolean
         get() =
             this is Object ||
                 this is Optional ||
                 this is Result ||
+                this is Tuple ||
+                this is Function ||
                 this is RustString ||
                 this is Async ||
+                this is Stream ||
+                this is Iterator ||
                 this is Slice ||
                 this is Vec ||
                 this is VecDeque ||
@@ -192,6 +250,25 @@ sealed class XrossType {
                 this is BTreeMap
     val isPrimitive: Boolean get() = !isComplex
 
+    /**
+     * Returns true if this is a collection returned as a boxed pointer and read through the `retAccessors` exports.
+     */
+    val isCollection: Boolean
+        get() = this is VecDeque ||
+            this is LinkedList ||
+            this is HashSet ||
+            this is BTreeSet ||
+            this is BinaryHeap ||
+            this is HashMap ||
+            this is BTreeMap
+
+    /**
+     * Returns true if this is an optional primitive, passed by value as a tagged `XrossOption { isSome, value }`
+     * so that `Some(0)` stays distinguishable from `None`.
+     */
+    val isTaggedOption: Boolean
+        get() = this is Optional && inner.isPrimitive && inner != Void && inner != Pointer
+
     /**
      * Returns the size in bytes for the primitive type.
      */
@@ -201,8 +278,13 @@ sealed class XrossType {
             is I64, is U64, is F64, is Pointer, is RustString -> 8L
             is ISize, is USize -> if (java.lang.foreign.ValueLayout.ADDRESS.byteSize() <= 4L) 4L else 8L
             is Result -> 16L
-            is Async -> 24L
-            is Slice, is Vec -> 16L
+            is Optional -> if (isTaggedOption) inner.kotlinSize * 2 else 8L
+            is Tuple -> byteSize(incoming = false)
+            is Async -> 40L
+            is Stream -> 32L
+            is Function -> 24L
+            is Slice -> 16L
+            is Vec -> 40L
             is VecDeque, is LinkedList, is HashSet, is BTreeSet, is BinaryHeap, is HashMap, is BTreeMap -> 8L
             is Object -> 8L
             is Bool, is I8, is U8 -> 1L
@@ -210,4 +292,36 @@ sealed class XrossType {
             is Void -> 0L
             else -> 8L
         }
+
+    /**
+     * Size of this type as a field of a tuple struct passed to Rust ([incoming]) or returned from it.
+     * Primitives and tagged options are stored inline, strings as a string struct and everything else as a pointer.
+     * An optional string is a nullable string view when passed to Rust, but a pointer when returned.
+     */
+    fun reprSize(incoming: Boolean): Long = when {
+        this is RustString -> 24L
+        this is Optional && inner is RustString -> if (incoming) 24L else 8L
+        isTaggedOption -> kotlinSize
+        isPrimitive && this != Void && this != Pointer -> kotlinSize
+        else -> 8L
+    }
+
+    fun reprAlign(incoming: Boolean): Long = when {
+        this is Optional && isTaggedOption -> inner.kotlinSize
+        this is RustString || (this is Optional && inner is RustString) -> 8L
+        else -> reprSize(incoming)
+    }
+
+    companion object {
+        /**
+         * Returns the Kotlin type of a tuple: a [Pair] or [Triple], or a list of values for other arities.
+         */
+        fun tupleKotlinType(elements: List<TypeName>): TypeName = when (elements.size) {
+            2 -> Pair::class.asClassName().parameterizedBy(elements[0], elements[1])
+            3 -> Triple::class.asClassName().parameterizedBy(elements[0], elements[1], elements[2])
+            else -> List::class.asClassName().parameterizedBy(ANY.copy(nullable = true))
+        }
+
+        private fun alignUp(value: Long, align: Long): Long = (value + align - 1) / align * align
+    }
 }
@@ -83,7 +83,17 @@ object XrossTypeSerializer : KSerializer<XrossType> {
                             deserializeRecursive(obj["err"]!!),
                         )
                     }
+                    "Tuple" -> XrossType.Tuple(body.jsonArray.map { deserializeRecursive(it) })
+                    "Function" -> {
+                        val obj = body.jsonObject
+                        XrossType.Function(
+                            obj["args"]!!.jsonArray.map { deserializeRecursive(it) },
+                            deserializeRecursive(obj["ret"]!!),
+                        )
+                    }
                     "Async" -> XrossType.Async(deserializeRecursive(body))
+                    "Stream" -> XrossType.Stream(deserializeRecursive(body))
+                    "Iterator" -> XrossType.Iterator(deserializeRecursive(body))
                     else -> throw IllegalArgumentException("Unknown complex type: $typeKey")
                 }
             }
@@ -129,7 +139,16 @@ object XrossTypeSerializer : KSerializer<XrossType> {
                     put("err", serializeRecursive(value.err))
                 }
             }
+            is XrossType.Tuple -> buildJsonObject { put("Tuple", JsonArray(value.elements.map { serializeRecursive(it) })) }
+            is XrossType.Function -> buildJsonObject {
+                putJsonObject("Function") {
+                    put("args", JsonArray(value.args.map { serializeRecursive(it) }))
+                    put("ret", serializeRecursive(value.ret))
+                }
+            }
             is XrossType.Async -> buildJsonObject { put("Async", serializeRecursive(value.inner)) }
+            is XrossType.Stream -> buildJsonObject { put("Stream", serializeRecursive(value.inner)) }
+            is XrossType.Iterator -> buildJsonObject { put("Iterator", serializeRecursive(value.inner)) }
             else -> {
                 val name = nameToPrimitive.entries.find { it.value == value }?.key
                     ?: throw IllegalArgumentException("Unknown type instance: $value")